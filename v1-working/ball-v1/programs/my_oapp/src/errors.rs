@@ -4,4 +4,5 @@ use anchor_lang::prelude::error_code;
 pub enum MyOAppError {
     InvalidBallLength,
     InvalidMessageLength,
+    InvalidMessageType, // msg_type is neither VANILLA_TYPE nor ABA_TYPE
 }
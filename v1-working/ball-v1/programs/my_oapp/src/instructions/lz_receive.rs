@@ -1,9 +1,12 @@
-use crate::{consts::*, *};
+use crate::{consts::*, errors::MyOAppError, *};
 use anchor_lang::prelude::*;
 use ethnum::U256;
 use oapp::{
     endpoint::{
-        cpi::accounts::Clear, instructions::ClearParams, ConstructCPIContext, ID as ENDPOINT_ID,
+        cpi::accounts::Clear,
+        instructions::{ClearParams, SendParams},
+        ConstructCPIContext,
+        ID as ENDPOINT_ID,
     },
     LzReceiveParams,
 };
@@ -52,7 +55,14 @@ impl LzReceive<'_> {
         )?;
 
         // From here on, you can process the message as needed by your use case.
-        let ball = uint256_msg_codec::decode(&params.message)?;
+        // A bare uint256 is a one-way ping; an ABA message also asks for the ball back.
+        let aba_msg = uint256_msg_codec::decode_aba(&params.message)?;
+        require!(
+            aba_msg.msg_type == uint256_msg_codec::VANILLA_TYPE
+                || aba_msg.msg_type == uint256_msg_codec::ABA_TYPE,
+            MyOAppError::InvalidMessageType
+        );
+        let ball = aba_msg.ball;
         let store = &mut ctx.accounts.store;
         let old_ball = store.ball;
         let old_ball_ethnum = U256::from_be_bytes(old_ball);
@@ -68,6 +78,63 @@ impl LzReceive<'_> {
             src_eid: params.src_eid,
         });
 
+        // One-way pings are done here, exactly as before ABA support
+        if aba_msg.msg_type != uint256_msg_codec::ABA_TYPE {
+            return Ok(());
+        }
+
+        // ABA pattern: send response back
+        // Decrement ball for return message
+        let return_ball_ethnum = new_ball_ethnum.saturating_sub(U256::ONE);
+        let return_ball = return_ball_ethnum.to_be_bytes();
+
+        // Encode return message (vanilla type - return messages are always vanilla)
+        let return_message = uint256_msg_codec::encode(&return_ball);
+
+        // Update store with decremented ball
+        store.set_ball(return_ball);
+
+        // Prepare options for return message
+        // Use the return_options from the ABA message (same as Ethereum does)
+        // The enforced_options will combine with them to ensure proper formatting
+        // Note: Even if return_options are empty, enforced_options should add ExecutorLzReceiveOption
+        // for vanilla messages (msgType 1) to ensure the Executor can execute the return message
+        let return_options = ctx
+            .accounts
+            .peer
+            .enforced_options
+            .combine_options(&None::<Vec<u8>>, &aba_msg.return_options)?;
+
+        // Send back to src_eid (the origin chain)
+        // Estimate return message fee: Use 2x the base Sol->ETH fee as a safety buffer
+        // The executor should ensure sufficient native fee is forwarded in the initial message.
+        let estimated_return_fee = BASE_SOL_TO_ETH_FEE
+            .checked_mul(RETURN_FEE_MULTIPLIER)
+            .unwrap_or(BASE_SOL_TO_ETH_FEE * RETURN_FEE_MULTIPLIER);
+
+        let send_params = SendParams {
+            dst_eid: params.src_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message: return_message,
+            options: return_options,
+            native_fee: estimated_return_fee,
+            lz_token_fee: 0, // No LZ token fee for return
+        };
+
+        // Send return message via Endpoint CPI
+        // As in v2, the accounts needed for the Send CPI follow the Clear accounts in
+        // remaining_accounts. They are typically fetched off-chain using the endpoint SDK's
+        // getSendIXAccountMetaForCPI method
+        let accounts_for_send = &ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..];
+
+        oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            accounts_for_send,
+            seeds,
+            send_params,
+        )?;
+
         Ok(())
     }
 }
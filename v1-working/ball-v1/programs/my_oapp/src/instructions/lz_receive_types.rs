@@ -47,6 +47,12 @@ impl LzReceiveTypes<'_> {
         );
         accounts.extend(accounts_for_clear);
 
+        // Note: For ABA messages, accounts needed for the return leg's Endpoint::send CPI
+        // should be provided as additional remaining_accounts after the clear accounts. These
+        // depend on the message library and destination chain configuration, and are
+        // typically fetched off-chain using the endpoint SDK's getSendIXAccountMetaForCPI
+        // method. One-way pings need none.
+
         Ok(accounts)
     }
 }
@@ -29,6 +29,9 @@ pub struct QuoteSendParams {
     pub receiver: [u8; 32],
     pub options: Vec<u8>,
     pub pay_in_lz_token: bool,
+    // The same as in SendMessageParams, so the quoted message matches the sent one
+    pub msg_type: u16,
+    pub return_options: Vec<u8>,
 }
 
 impl<'info> QuoteSend<'info> {
@@ -37,7 +40,7 @@ impl<'info> QuoteSend<'info> {
         let ball = ctx.accounts.store.ball;
         let ball_ethnum = U256::from_be_bytes(ball);
         let new_ball = ball_ethnum.saturating_sub(U256::ONE).to_be_bytes();
-        let message = uint256_msg_codec::encode(&new_ball);
+        let message = Send::encode_message(&new_ball, params.msg_type, &params.return_options)?;
 
         // Ask the Endpoint how much a send would cost
         let quote_params = QuoteParams {
@@ -1,4 +1,4 @@
-use crate::{consts::*, *};
+use crate::{consts::*, errors::MyOAppError, *};
 use anchor_lang::prelude::*;
 use ethnum::U256;
 use oapp::endpoint::{
@@ -32,6 +32,8 @@ pub struct SendMessageParams {
     pub options: Vec<u8>,
     pub native_fee: u64,
     pub lz_token_fee: u64,
+    pub msg_type: u16, // VANILLA_TYPE (one-way, as before) or ABA_TYPE (ask for the ball back)
+    pub return_options: Vec<u8>, // Options for the return message (B→A); ABA_TYPE only
 }
 
 impl<'info> Send<'info> {
@@ -43,7 +45,7 @@ impl<'info> Send<'info> {
         let ball_ethnum = U256::from_be_bytes(ball);
         let new_ball_ethnum = ball_ethnum.saturating_sub(U256::ONE);
         let new_ball = new_ball_ethnum.to_be_bytes();
-        let message = uint256_msg_codec::encode(&new_ball);
+        let message = Self::encode_message(&new_ball, params.msg_type, &params.return_options)?;
 
         // Emit event tracking the ball value
         emit!(crate::events::BallSent {
@@ -77,4 +79,20 @@ impl<'info> Send<'info> {
         )?;
         Ok(())
     }
+
+    /// Encode `new_ball` as `msg_type`: a bare uint256 for VANILLA_TYPE (byte-identical to
+    /// what v1 always sent) or an ABA message carrying `return_options`.
+    pub fn encode_message(
+        new_ball: &[u8; 32],
+        msg_type: u16,
+        return_options: &[u8],
+    ) -> Result<Vec<u8>> {
+        match msg_type {
+            uint256_msg_codec::VANILLA_TYPE => Ok(uint256_msg_codec::encode(new_ball)),
+            uint256_msg_codec::ABA_TYPE => {
+                Ok(uint256_msg_codec::encode_aba(new_ball, return_options))
+            }
+            _ => err!(MyOAppError::InvalidMessageType),
+        }
+    }
 }
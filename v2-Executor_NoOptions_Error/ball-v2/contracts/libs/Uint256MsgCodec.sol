@@ -4,9 +4,17 @@ pragma solidity ^0.8.22;
 
 error InvalidMsgLength();
 error InvalidStringValueLength();
+error ReturnOptionsTooLong();
+error MessageTooLarge();
 
 library Uint256MsgCodec {
     uint16 public constant ABA_TYPE = 2;
+    /// @dev Return leg sent by the Solana program; never triggers a further response
+    uint16 public constant PONG_TYPE = 3;
+    /// @dev Must match MAX_RETURN_OPTIONS_LEN in the Solana program's consts.rs
+    uint256 public constant MAX_RETURN_OPTIONS_LEN = 512;
+    /// @dev Must match MAX_MESSAGE_LEN in the Solana program's consts.rs
+    uint256 public constant MAX_MESSAGE_LEN = 2048;
 
     /// @notice Encodes a uint256 into bytes (just ABI encode it)
     function encode(uint256 _value) internal pure returns (bytes memory) {
@@ -24,6 +32,7 @@ library Uint256MsgCodec {
         uint256 _value,
         bytes calldata _returnOptions
     ) internal pure returns (bytes memory) {
+        if (_returnOptions.length > MAX_RETURN_OPTIONS_LEN) revert ReturnOptionsTooLong();
         return abi.encode(_value, ABA_TYPE, _returnOptions);
     }
 
@@ -38,6 +47,7 @@ library Uint256MsgCodec {
         // ABA format: abi.encode(uint256, uint16, bytes)
         // Minimum size for ABA: 32 (uint256) + 32 (uint16 padded) + 32 (offset) + 32 (length) = 128 bytes
         // Vanilla format: abi.encode(uint256) = 32 bytes
+        if (_msg.length > MAX_MESSAGE_LEN) revert MessageTooLarge();
         if (_msg.length == 32) {
             // Vanilla message
             value = abi.decode(_msg, (uint256));
@@ -46,6 +56,7 @@ library Uint256MsgCodec {
         } else {
             // ABA message
             (value, msgType, returnOptions) = abi.decode(_msg, (uint256, uint16, bytes));
+            if (returnOptions.length > MAX_RETURN_OPTIONS_LEN) revert ReturnOptionsTooLong();
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+// Every public message decoder, on arbitrary bytes:
+// cargo +nightly fuzz run decoders
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use my_oapp::uint256_msg_codec as codec;
+
+fuzz_target!(|message: &[u8]| {
+    let _ = codec::decode(message);
+    let _ = codec::decode_u256(message);
+    let _ = codec::decode_typed_vanilla(message);
+    let _ = codec::decode_counter(message);
+    let _ = codec::decode_ball_compose(message);
+    let _ = codec::decode_i256(message);
+    let _ = codec::peek_msg_type(message);
+    let _ = codec::decode_game_over(message);
+    let _ = codec::decode_aba_ref(message);
+    let _ = codec::decode_aba_wrapped(message);
+    let _ = codec::decode_aba_lenient(message);
+    let _ = codec::decode_packed(message);
+    let _ = codec::decode_compact(message);
+    let _ = codec::decode_aba_compact(message);
+    let _ = codec::decode_aba_compact_ref(message);
+    let _ = codec::decode_auto(message);
+    let _ = codec::decode_with_memo(message);
+    let _ = codec::decode_batch(message);
+    let _ = codec::decode_batch_message(message);
+    let _ = codec::decode_v2(message);
+    let _ = codec::decode_any(message);
+    let _ = codec::decode_aba_u256(message);
+    let _ = codec::decode_tagged(message);
+    let _ = codec::split_tag(message);
+
+    // An ABA message that decodes strictly encodes back into one that decodes the same way.
+    // Vanilla messages are left out: they decode to `VANILLA_TYPE`, which `encode` doesn't write.
+    match codec::decode_aba(message) {
+        Ok(decoded) if decoded.msg_type != codec::VANILLA_TYPE => {
+            let encoded = decoded.encode().expect("decoded message re-encodes");
+            let again = codec::decode_aba(&encoded).expect("re-encoded message decodes");
+            assert_eq!(again.ball, decoded.ball);
+            assert_eq!(again.msg_type, decoded.msg_type);
+            assert_eq!(again.return_options, decoded.return_options);
+            assert_eq!(again.compose_msg, decoded.compose_msg);
+            assert_eq!(again.memo, decoded.memo);
+            assert_eq!(again.seq, decoded.seq);
+        },
+        _ => {},
+    }
+});
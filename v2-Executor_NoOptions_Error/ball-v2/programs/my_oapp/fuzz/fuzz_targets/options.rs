@@ -0,0 +1,26 @@
+// The type-3 options checks, on arbitrary bytes split into enforced and caller options:
+// cargo +nightly fuzz run options
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use my_oapp::consts::MAX_RETURN_OPTIONS_LEN;
+use my_oapp::options_codec::{merge_type3, validate_type3, worker_options};
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks where the enforced options end
+    let Some((&split, data)) = data.split_first() else {
+        return;
+    };
+    let (enforced, extra) = data.split_at((split as usize).min(data.len()));
+
+    let _ = validate_type3(data, MAX_RETURN_OPTIONS_LEN);
+    let _ = worker_options(data);
+    let valid = |options: &[u8]| validate_type3(options, usize::MAX).is_ok();
+    for reject_conflicts in [false, true] {
+        // Merging well-formed options gives well-formed options
+        match merge_type3(enforced, extra, reject_conflicts) {
+            Ok(merged) if valid(enforced) && valid(extra) => assert!(valid(&merged)),
+            _ => {},
+        }
+    }
+});
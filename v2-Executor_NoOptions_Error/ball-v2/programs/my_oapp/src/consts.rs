@@ -1,9 +1,66 @@
 pub const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
 pub const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
 pub const PEER_SEED: &[u8] = b"Peer"; // Not used by the Executor.
+pub const PENDING_RETURN_SEED: &[u8] = b"PendingReturn"; // Not used by the Executor.
+pub const RETURN_ACCOUNTS_SEED: &[u8] = b"ReturnAccounts"; // Passed to lz_receive_types via LzReceiveTypesAccounts.
+pub const RECEIPT_SEED: &[u8] = b"Receipt"; // Not used by the Executor.
+pub const SEND_ALLOWLIST_SEED: &[u8] = b"SendAllowlist"; // Not used by the Executor.
+pub const ALLOWED_RECEIVERS_SEED: &[u8] = b"AllowedReceivers"; // Not used by the Executor.
+pub const FEE_QUOTE_CACHE_SEED: &[u8] = b"FeeQuoteCache"; // Returned to the Executor by lz_receive_types.
+pub const BALL_HISTORY_SEED: &[u8] = b"BallHistory"; // Returned to the Executor by lz_receive_types.
+pub const USER_BALL_SEED: &[u8] = b"UserBall"; // Returned to the Executor by lz_receive_types.
 
 // Base estimate for Solana -> Ethereum messaging fee (in lamports)
 // This is used as a reference point for estimating return message fees in ABA pattern
 // Actual cost may vary, so we use a multiplier for safety
 pub const BASE_SOL_TO_ETH_FEE: u64 = 6_365_917; // Base cost for Sol->ETH trip
 pub const RETURN_FEE_MULTIPLIER: u64 = 2; // Use 2x as safety buffer for return message
+// Lamports kept back from a native drop to the Store before the rest is used as the
+// return message's native fee
+pub const RETURN_FEE_RESERVE: u64 = 10_000;
+// Slots a FeeQuoteCache quote is used for by a new Store (about a minute)
+pub const DEFAULT_FEE_QUOTE_MAX_AGE_SLOTS: u64 = 150;
+// `SendMessageParams.native_fee` value asking send to quote the fee itself
+pub const AUTO_NATIVE_FEE: u64 = u64::MAX;
+
+// Upper bound for the return options carried inside an ABA message.
+// Must match `MAX_RETURN_OPTIONS_LEN` in the EVM contract's Uint256MsgCodec.
+pub const MAX_RETURN_OPTIONS_LEN: usize = 512;
+
+// Upper bound for the compose message carried inside an ABA message.
+pub const MAX_COMPOSE_MSG_LEN: usize = 512;
+
+// Upper bound for the number of values in a batch message, keeping lz_receive within compute.
+pub const MAX_BATCH_LEN: usize = 32;
+
+// Upper bound for the memo attached to a message, in bytes.
+pub const MAX_MEMO_LEN: usize = 64;
+
+// Upper bound for the encoded TLV extension area of an ABA message, in bytes.
+pub const MAX_EXTENSIONS_LEN: usize = 256;
+
+// Upper bound for a whole inbound message, in bytes. Leaves room for every field at its
+// maximum plus the MAGIC prefix and version header.
+// Must match `MAX_MESSAGE_LEN` in the EVM contract's Uint256MsgCodec.
+pub const MAX_MESSAGE_LEN: usize = 2048;
+
+// Largest retry window `init_store` accepts; the size of each peer's processed-nonce bitmap.
+// Must be a multiple of 64.
+pub const MAX_NONCE_WINDOW: u16 = 256;
+
+// Most Endpoint::send accounts `set_return_accounts` caches per peer for lz_receive_types.
+pub const MAX_RETURN_SEND_ACCOUNTS: usize = 40;
+
+// Most destinations one `send_multi` sends to, keeping it within compute and account limits.
+pub const MAX_SEND_MULTI_DESTINATIONS: usize = 4;
+
+// Most pings one `ping_many` sends, keeping it within compute.
+pub const MAX_PING_MANY: u8 = 5;
+
+// Sizes of the Store's zero-padded UTF-8 label and metadata URI, in bytes.
+pub const STORE_LABEL_LEN: usize = 32;
+pub const METADATA_URI_LEN: usize = 96;
+
+// Largest size `extend_store` grows an account to, in bytes. Also the most one instruction
+// may grow an account by (MAX_PERMITTED_DATA_INCREASE).
+pub const MAX_EXTENDED_ACCOUNT_SIZE: usize = 10_240;
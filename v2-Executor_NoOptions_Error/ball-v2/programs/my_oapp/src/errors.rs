@@ -5,4 +5,69 @@ pub enum MyOAppError {
     InvalidBallLength,
     InvalidMessageLength,
     InvalidMessageType, // Message is not ABA type
+    InvalidMessageOffset, // ABA offset word is not where the canonical layout puts it
+    TrailingMessageBytes, // Non-zero padding or extra bytes after the last ABA tail
+    ReturnOptionsTooLong, // Return options exceed MAX_RETURN_OPTIONS_LEN
+    UnsupportedCodecVersion, // Message version header is newer (or older) than CODEC_VERSION
+    ComposeMsgTooLong, // Compose message exceeds MAX_COMPOSE_MSG_LEN
+    InvalidBatchLength, // Batch is empty or exceeds MAX_BATCH_LEN
+    MemoTooLong, // Memo exceeds MAX_MEMO_LEN
+    ExtensionsTooLong, // Encoded TLV extensions exceed MAX_EXTENSIONS_LEN
+    InvalidExtension, // Truncated TLV entry, or a known extension with the wrong length
+    ForeignMessage, // Message lacks the MAGIC prefix while Store.require_magic is set
+    MessageTooShort, // Message ends before a fixed-size part is complete
+    MessageLengthMismatch, // A length word disagrees with the bytes actually present
+    UnknownMessageType, // Valid type word, but not a type lz_receive (or the decoder) handles
+    MessageTooLarge, // Message exceeds MAX_MESSAGE_LEN
+    ValueTooLarge, // Ball does not fit the requested integer width
+    ZeroStep, // A zero step would keep the ball bouncing without progress
+    GameOver, // The ball reached zero; start a new game before sending
+    NotEnoughRemainingAccounts, // Fewer remaining accounts than the Endpoint CPI needs
+    InvalidNonceWindow, // Retry window exceeds MAX_NONCE_WINDOW
+    OutOfOrder, // Inbound nonce does not follow the last one applied from this peer
+    TooManyReturnAccounts, // More cached send accounts than MAX_RETURN_SEND_ACCOUNTS
+    PeerPaused, // Inbound messages from this peer are paused by the admin
+    BallMismatch, // Reply ball doesn't follow the ball last sent to this peer
+    BallUnderflow, // Decrement would leave the ball's range under UnderflowPolicy::Error
+    InvalidReturnOptions, // Inbound return options are not well-formed type-3 options
+    InvalidSendAccounts, // A return send account was not derived for the return destination
+    AlreadyCleared, // Payload hash is gone for a nonce this program never applied
+    TooManySenders, // Peer already accepts MAX_EXTRA_SENDERS senders besides its address
+    LastSender, // Removing this sender would leave the peer with none
+    NotReceiptOwner, // Only the admin or the rent payer may close a Receipt
+    BallDepleted, // Unsigned ball has nothing left to send; start a new game
+    TooManyDestinations, // send_multi lists more than MAX_SEND_MULTI_DESTINATIONS peers
+    DuplicateDestination, // send_multi lists the same dst_eid twice
+    InvalidPeerAccount, // Account is not the PeerConfig PDA for the destination
+    FeeAboveMax, // Fee quoted inside send exceeds the caller's max_fee
+    ConflictingOptions, // Send options duplicate an executor option and the Store rejects that
+    DuplicateClientOrder, // client_order_id was already used by a recent send to this peer
+    SendNotPermitted, // Signer may not send under the Store's SendPermission
+    AllowlistFull, // SendAllowlist already holds MAX_ALLOWLISTED_SENDERS signers
+    LzTokenUnavailable, // lz_token_fee given but the Endpoint has no LZ token mint
+    InvalidLzTokenAccounts, // LZ token accounts missing, mismatched or not passed to the send
+    MemoNotCarried, // Memo given for a counter or compact send, which has no room for one
+    CooldownActive, // min_send_interval_slots haven't passed since the last send to this peer
+    ReceiverNotAllowed, // receiver_override is not in the AllowedReceivers PDA for the eid
+    TooManyReceivers, // AllowedReceivers already holds MAX_ALLOWED_RECEIVERS receivers
+    TooManyPings, // ping_many count is 0 or above MAX_PING_MANY
+    FeeBudgetExceeded, // ping_many's native fees would go past max_total_native_fee
+    OptionsGivenTwice, // Both raw options and option_items were passed to a send
+    PingDefaultsNotSet, // send_ping to a peer without default or enforced send options
+    QuoteCacheStale, // send_ping's FeeQuoteCache is older than its max_age_slots
+    ZeroInitialBall, // init_store given a zero initial_ball without allow_zero_ball
+    NotPendingAdmin, // accept_admin signed by a key other than Store.pending_admin
+    NotAdminOrGuardian, // pause_peer signed by neither the admin nor the guardian
+    StoreNeedsMigration, // Store.version is below what the instruction needs; run migrate_store
+    InvalidAccountSize, // extend_store size below the current one or above the maximum
+    InvalidUserBall, // user_ball isn't the UserBall PDA of the message's origin caller
+    UserBallUnsupported, // send_user_ping in counter mode or to a compact peer
+    InvalidMetadata, // Store label or metadata URI isn't zero-padded UTF-8
+    InvalidBallBounds, // min_ball above max_ball
+    BallOutOfBounds, // set_ball value outside min_ball..=max_ball
+    BallBelowMinimum, // Send of a ball below Store.min_ball
+    PingInFlight, // Send while a ping is in flight and Store.single_flight is set
+    TooManyPendingReturns, // Store.max_pending_returns PendingReturns are open; retry later
+    SyncPeerMissing, // set_ball with sync but without the peer account
+    SyncNotCarried, // set_ball sync to a compact peer, which can't decode a vanilla ball
 }
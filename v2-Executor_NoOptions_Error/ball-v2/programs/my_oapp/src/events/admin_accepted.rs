@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct AdminAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey, // Current admin, still in charge
+    pub pending_admin: Pubkey, // Becomes admin once it signs accept_admin
+}
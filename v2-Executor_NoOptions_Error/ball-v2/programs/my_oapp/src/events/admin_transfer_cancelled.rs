@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct AdminTransferCancelled {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // Proposal that was dropped
+}
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct AutoReturnsExhausted {
+    pub src_eid: u32,
+    pub guid: [u8; 32], // Inbound message that was applied without a return
+}
@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct BallAdminSet {
+    pub admin: Pubkey,
+    pub old_ball: Vec<u8>,
+    pub new_ball: Vec<u8>,
+    pub synced_eid: Option<u32>, // Peer the value was also sent to, if any
+    pub sync_guid: Option<[u8; 32]>,
+}
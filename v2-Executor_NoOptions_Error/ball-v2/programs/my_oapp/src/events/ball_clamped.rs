@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct BallClamped {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub received_ball: Vec<u8>,
+    pub applied_ball: Vec<u8>, // The Store's min_ball or max_ball
+}
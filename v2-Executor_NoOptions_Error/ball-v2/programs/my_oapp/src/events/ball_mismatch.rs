@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct BallMismatch {
+    pub src_eid: u32,
+    pub expected_ball: Vec<u8>, // Last ball sent to the peer, decremented by the step
+    pub received_ball: Vec<u8>,
+    pub rejected: bool, // Strict peer: the message was cleared without being applied
+}
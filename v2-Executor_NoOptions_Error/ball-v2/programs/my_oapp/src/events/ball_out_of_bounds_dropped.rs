@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct BallOutOfBoundsDropped {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub received_ball: Vec<u8>, // Outside the Store's min_ball..=max_ball
+}
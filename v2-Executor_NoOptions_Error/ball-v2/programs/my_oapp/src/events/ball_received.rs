@@ -1,3 +1,4 @@
+use crate::{EidTouch, GameMode};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -6,5 +7,17 @@ pub struct BallReceived {
     pub new_ball: Vec<u8>,
     pub old_ball_str: String,
     pub new_ball_str: String,
+    pub new_ball_u64: Option<u64>, // New ball as a compact integer, if it fits
     pub src_eid: u32,
+    pub memo: String, // Empty when the message carried none
+    pub origin_caller: Option<[u8; 32]>, // Source-chain account that sent the ping, if known
+    pub seq: u64, // Sender's application sequence number (0 = not carried)
+    pub nonce: u64,
+    pub guid: [u8; 32],
+    pub sender: [u8; 32], // Which of the peer's allowed senders sent the message
+    pub received_at: i64, // Solana clock when the ball was applied
+    pub inbound_count: u64, // Messages cleared from this peer, including this one
+    pub total_received: u64, // Messages the Store has cleared from any peer, including this one
+    pub mode: GameMode, // Whether the value is a ball or a counter
+    pub previous_touch: Option<EidTouch>, // Store.touches entry before this receive's
 }
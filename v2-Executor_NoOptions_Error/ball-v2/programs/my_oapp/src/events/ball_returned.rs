@@ -0,0 +1,19 @@
+use crate::{EidTouch, GameMode, ReturnFeeSource};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct BallReturned {
+    pub return_ball: Vec<u8>,
+    pub return_ball_str: String,
+    pub dst_eid: u32,
+    pub receiver: [u8; 32], // Peer address, or the admin's return receiver override
+    pub options_len: u32, // Length of the return options after combining with enforced options
+    pub native_fee: u64,  // Native fee supplied to the return's SendParams
+    pub fee_source: ReturnFeeSource, // Native drop, cached quote or constant estimate
+    pub outbound_count: u64, // Balls sent to this peer, including this return
+    pub total_sent: u64, // Balls the Store has sent to any peer, including this return
+    pub return_guid: [u8; 32], // Endpoint guid of the return message, as seen on the EVM side
+    pub return_nonce: u64,     // Outbound nonce the Endpoint assigned to the return
+    pub mode: GameMode, // Whether the value is a ball or a counter
+    pub previous_touch: Option<EidTouch>, // Store.touches entry before this return's
+}
@@ -1,3 +1,4 @@
+use crate::{EidTouch, GameMode};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -6,5 +7,16 @@ pub struct BallSent {
     pub new_ball: Vec<u8>,
     pub current_ball_str: String,
     pub new_ball_str: String,
+    pub new_ball_u64: Option<u64>, // New ball as a compact integer, if it fits
     pub dst_eid: u32,
+    pub seq: u64,
+    pub outbound_count: u64, // Balls sent to this peer, including this one
+    pub total_sent: u64, // Balls the Store has sent to any peer, including this one
+    pub mode: GameMode, // Whether the value is a ball or a counter
+    pub client_order_id: u64, // Caller's id for this send (0 = none)
+    pub memo: String, // Empty when the send carried none
+    pub receiver: [u8; 32], // Peer address, or the allowed override the send targeted
+    pub guid: [u8; 32], // Assigned by the Endpoint
+    pub nonce: u64,
+    pub previous_touch: Option<EidTouch>, // Store.touches entry before this send's
 }
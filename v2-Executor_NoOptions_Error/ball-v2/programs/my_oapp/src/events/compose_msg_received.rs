@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ComposeMsgReceived {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub compose_msg: Vec<u8>,
+}
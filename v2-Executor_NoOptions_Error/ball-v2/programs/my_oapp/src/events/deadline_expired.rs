@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct DeadlineExpired {
+    pub src_eid: u32,
+    pub deadline: u64,
+    pub now: i64,
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct GameOver {
+    pub src_eid: u32,
+    pub final_ball: Vec<u8>,
+    pub sent: bool, // True when this chain ended the game, false when the peer did
+}
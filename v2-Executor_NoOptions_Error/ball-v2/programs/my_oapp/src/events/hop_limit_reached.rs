@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct HopLimitReached {
+    pub src_eid: u32,
+    pub hop_count: u16,
+    pub max_hop_count: u16,
+}
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct MetadataUpdated {
+    pub old_label: String,
+    pub new_label: String,
+    pub old_metadata_uri: String,
+    pub new_metadata_uri: String,
+}
@@ -1,5 +1,61 @@
 pub mod ball_sent;
 pub mod ball_received;
+pub mod hop_limit_reached;
+pub mod compose_msg_received;
+pub mod sequence_gap;
+pub mod deadline_expired;
+pub mod game_over;
+pub mod auto_returns_exhausted;
+pub mod return_fee_unavailable;
+pub mod return_parked;
+pub mod return_retried;
+pub mod ball_returned;
+pub mod ball_mismatch;
+pub mod stale_message_dropped;
+pub mod unknown_message_dropped;
+pub mod raw_message_sent;
+pub mod store_initialized;
+pub mod admin_proposed;
+pub mod admin_accepted;
+pub mod admin_transfer_cancelled;
+pub mod peer_paused;
+pub mod user_ball_sent;
+pub mod user_ball_received;
+pub mod metadata_updated;
+pub mod ball_clamped;
+pub mod pending_returns_cap_reached;
+pub mod ball_admin_set;
+pub mod return_underflow_skipped;
+pub mod return_options_rejected;
+pub mod ball_out_of_bounds_dropped;
 
 pub use ball_sent::*;
-pub use ball_received::*;
\ No newline at end of file
+pub use ball_received::*;
+pub use hop_limit_reached::*;
+pub use compose_msg_received::*;
+pub use sequence_gap::*;
+pub use deadline_expired::*;
+pub use game_over::*;
+pub use auto_returns_exhausted::*;
+pub use return_fee_unavailable::*;
+pub use return_parked::*;
+pub use return_retried::*;
+pub use ball_returned::*;
+pub use ball_mismatch::*;
+pub use stale_message_dropped::*;
+pub use unknown_message_dropped::*;
+pub use raw_message_sent::*;
+pub use store_initialized::*;
+pub use admin_proposed::*;
+pub use admin_accepted::*;
+pub use admin_transfer_cancelled::*;
+pub use peer_paused::*;
+pub use user_ball_sent::*;
+pub use user_ball_received::*;
+pub use metadata_updated::*;
+pub use ball_clamped::*;
+pub use pending_returns_cap_reached::*;
+pub use ball_admin_set::*;
+pub use return_underflow_skipped::*;
+pub use return_options_rejected::*;
+pub use ball_out_of_bounds_dropped::*;
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PeerPaused {
+    pub remote_eid: u32,
+    pub paused_by: Pubkey, // Admin or guardian
+}
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PendingReturnsCapReached {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub pending_returns: u16, // Open PendingReturns, at or above the cap
+    pub max_pending_returns: u16,
+}
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct RawMessageSent {
+    pub dst_eid: u32,
+    pub message_len: u32,
+    pub guid: [u8; 32], // Assigned by the Endpoint
+    pub nonce: u64,
+}
@@ -0,0 +1,11 @@
+use crate::ReturnFeeSource;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ReturnFeeUnavailable {
+    pub src_eid: u32,
+    pub guid: [u8; 32], // Inbound message whose return was parked
+    pub required: u64,  // Fallback fee estimate in lamports
+    pub available: u64, // Store lamports above the rent-exempt minimum
+    pub fee_source: ReturnFeeSource, // Cached quote or constant behind `required`
+}
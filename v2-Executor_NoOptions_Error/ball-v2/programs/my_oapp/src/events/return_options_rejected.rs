@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ReturnOptionsRejected {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub return_options_len: u32, // Length of the malformed return options the message carried
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ReturnParked {
+    pub dst_eid: u32,
+    pub guid: [u8; 32], // Inbound message the return answers (PendingReturn seed)
+    pub pending_return: Pubkey,
+}
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ReturnRetried {
+    pub dst_eid: u32,
+    pub guid: [u8; 32], // Inbound message the return answers (PendingReturn seed)
+}
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ReturnUnderflowSkipped {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub ball: Vec<u8>, // Ball applied from the message, left as the Store's
+    pub step: Vec<u8>, // Step larger than what the ball has left
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct SequenceGap {
+    pub src_eid: u32,
+    pub expected_seq: u64,
+    pub received_seq: u64,
+}
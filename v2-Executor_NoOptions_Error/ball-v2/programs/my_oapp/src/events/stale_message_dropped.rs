@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct StaleMessageDropped {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub sent_at: u64, // Origin timestamp carried by the message
+    pub ttl: u64,
+    pub now: i64, // Solana clock when the message was delivered
+}
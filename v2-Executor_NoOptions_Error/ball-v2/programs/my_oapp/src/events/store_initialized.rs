@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct StoreInitialized {
+    pub admin: Pubkey,
+    pub store_id: u64,
+    pub initial_ball: Vec<u8>, // Ball the first game starts from, as a big-endian uint256
+    pub initial_ball_str: String,
+    pub nonce_window: u16,
+}
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UnknownMessageDropped {
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub msg_type: u16, // Type word with no handler; the message was cleared but not applied
+}
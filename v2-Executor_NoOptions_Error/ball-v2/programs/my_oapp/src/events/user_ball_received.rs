@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UserBallReceived {
+    pub user: Pubkey,
+    pub old_ball: Vec<u8>,
+    pub new_ball: Vec<u8>,
+    pub src_eid: u32,
+    pub guid: [u8; 32],
+    pub received_count: u64, // Messages applied to this user's ball, including this one
+}
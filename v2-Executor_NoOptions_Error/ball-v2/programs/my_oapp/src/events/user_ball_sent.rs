@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UserBallSent {
+    pub user: Pubkey,
+    pub current_ball: Vec<u8>,
+    pub new_ball: Vec<u8>,
+    pub dst_eid: u32,
+    pub seq: u64,
+    pub sent_count: u64, // Pings sent for this user, including this one
+    pub guid: [u8; 32], // Assigned by the Endpoint
+    pub nonce: u64,
+}
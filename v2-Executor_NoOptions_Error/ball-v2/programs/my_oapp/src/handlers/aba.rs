@@ -0,0 +1,203 @@
+use super::{Inbound, Response};
+use crate::{consts::*, uint256_msg_codec::AbaMessageRef, *};
+use anchor_lang::prelude::*;
+use ethnum::U256;
+
+/// An ABA ping: apply the ball and build the decremented return leg.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    aba_msg: AbaMessageRef,
+) -> Result<Option<Response>> {
+    let params = inbound.params;
+    // The decoded fields borrow from params.message; nothing is copied until it is sent
+    let ball = aba_msg.ball_u256();
+    // A message whose origin caller has a UserBall plays that user's game instead of the
+    // Store's, and so isn't checked against the Store's outstanding ball
+    let mut user_ball = LzReceive::user_ball(ctx, aba_msg.known_origin_caller())?;
+    // A strict peer's mismatched reply is dropped: no ball update and no return
+    if user_ball.is_none()
+        && !LzReceive::check_consistency(
+            &mut ctx.accounts.peer,
+            &mut ctx.accounts.store,
+            params.src_eid,
+            ball,
+            aba_msg.step_u256(),
+        )
+    {
+        return Ok(None);
+    }
+
+    LzReceive::track_seq(&mut ctx.accounts.peer, params.src_eid, aba_msg.seq);
+    // The wire value is what the consistency check compares; the bounded one is played
+    let Some(ball) = LzReceive::bound_ball(&ctx.accounts.store, params, ball) else {
+        return Ok(None);
+    };
+
+    // Update ball
+    match &mut user_ball {
+        Some(user_ball) => LzReceive::receive_user_ball(ctx, user_ball, ball, params)?,
+        None => LzReceive::receive_ball(
+            &mut ctx.accounts.store,
+            ball,
+            params,
+            aba_msg.memo_lossy(),
+            aba_msg.known_origin_caller(),
+            aba_msg.seq,
+            inbound.inbound_count,
+        )?,
+    }
+    LzReceive::compose_ball(ctx, params.guid, ball)?;
+    LzReceive::record_ball(ctx, params.src_eid, ball.to_be_bytes(), BallDirection::Received)?;
+    let store = &mut ctx.accounts.store;
+
+    // An unsigned ball that is already zero has nothing left to decrement. Bouncing it
+    // back would only burn fees, so the game ends here and the send accounts, which may
+    // be omitted for such messages, are never touched. A user's game just stops; the
+    // Store's game isn't over.
+    if !store.signed_ball && ball == U256::ZERO && user_ball.is_some() {
+        return Ok(None);
+    }
+    if !store.signed_ball && ball == U256::ZERO {
+        store.game_over = true;
+        emit!(crate::events::GameOver {
+            src_eid: params.src_eid,
+            final_ball: ball.to_be_bytes().to_vec(),
+            sent: false,
+        });
+        return Ok(None);
+    }
+
+    // Surface the compose payload until a composer is wired in
+    if !aba_msg.compose_msg.is_empty() {
+        emit!(crate::events::ComposeMsgReceived {
+            src_eid: params.src_eid,
+            guid: params.guid,
+            compose_msg: aba_msg.compose_msg.to_vec(),
+        });
+    }
+
+    // One-way message: the ball is applied but nothing is sent back, so the send accounts
+    // aren't needed (`lz_receive_types` leaves them out) and no drop is consumed
+    if aba_msg.no_return()? {
+        return Ok(None);
+    }
+
+    // Stop bouncing once the ball has travelled the configured number of legs.
+    // The inbound update above is kept; only the return leg is skipped.
+    let max_hop_count = store.max_hop_count;
+    if max_hop_count != 0 && aba_msg.hop_count >= max_hop_count {
+        emit!(crate::events::HopLimitReached {
+            src_eid: params.src_eid,
+            hop_count: aba_msg.hop_count,
+            max_hop_count,
+        });
+        return Ok(None);
+    }
+
+    // Honour a sender-supplied deadline; unknown extension types are ignored
+    if let Some(deadline) = aba_msg.deadline()? {
+        let now = Clock::get()?.unix_timestamp;
+        if now > i64::try_from(deadline).unwrap_or(i64::MAX) {
+            emit!(crate::events::DeadlineExpired { src_eid: params.src_eid, deadline, now });
+            return Ok(None);
+        }
+    }
+
+    // Malformed return options would only fail deep inside the Endpoint::send CPI. Like an
+    // underflow below, they skip the return leg rather than fail after Clear.
+    if options_codec::validate_type3(aba_msg.return_options, MAX_RETURN_OPTIONS_LEN).is_err() {
+        emit!(crate::events::ReturnOptionsRejected {
+            src_eid: params.src_eid,
+            guid: params.guid,
+            return_options_len: aba_msg.return_options.len() as u32,
+        });
+        return Ok(None);
+    }
+
+    // ABA pattern: send response back
+    // Decrement ball by the sender's step (1 unless the message says otherwise). Under
+    // UnderflowPolicy::Error a step past the ball's range skips the return leg: Clear has
+    // already run, and failing here would leave the message undeliverable.
+    let step = aba_msg.step_u256();
+    let Ok(return_ball) = store.underflow_policy.decrement(ball, step, store.signed_ball) else {
+        emit!(crate::events::ReturnUnderflowSkipped {
+            src_eid: params.src_eid,
+            guid: params.guid,
+            ball: ball.to_be_bytes().to_vec(),
+            step: step.to_be_bytes().to_vec(),
+        });
+        return Ok(None);
+    };
+    let return_ball = return_ball.to_be_bytes();
+
+    let Some((return_fee, fee_source)) = super::fund_return(store, inbound)? else {
+        return Ok(None);
+    };
+    // An unsigned ball that reaches zero ends the game instead of bouncing once more
+    let game_over = !store.signed_ball && return_ball == [0u8; 32];
+    let return_type = if game_over {
+        uint256_msg_codec::GAME_OVER_TYPE
+    } else {
+        uint256_msg_codec::PONG_TYPE
+    };
+
+    // Encode return message as PONG (or GAME_OVER) so the origin chain can dispatch on its
+    // type instead of the payload length. Returns never request a further response.
+    // The inbound guid is echoed so the origin chain can pair the return with its ping.
+    let return_message = if inbound.compact {
+        uint256_msg_codec::encode_aba_compact(
+            uint256_msg_codec::ball_as_u128(&return_ball)?,
+            return_type,
+            &[],
+        )?
+    } else {
+        uint256_msg_codec::AbaMessage {
+            ball: return_ball,
+            msg_type: return_type,
+            hop_count: aba_msg.hop_count.saturating_add(1),
+            origin_guid: params.guid,
+            // Echoed for a user's game so the answer is routed back to their UserBall
+            origin_caller: if user_ball.is_some() { aba_msg.origin_caller } else { [0u8; 32] },
+            seq: store.take_seq(),
+            step: aba_msg.step,
+            ..Default::default()
+        }
+        .encode()?
+    };
+    let return_message = uint256_msg_codec::tag_if(return_message, inbound.require_magic);
+
+    // Update store (or the user's ball) with decremented ball
+    if let Some(user_ball) = &mut user_ball {
+        user_ball.ball = return_ball;
+        user_ball.save(&ctx.accounts.user_ball)?;
+    } else {
+        store.set_ball(return_ball);
+    }
+    if game_over && user_ball.is_none() {
+        store.game_over = true;
+        emit!(crate::events::GameOver {
+            src_eid: params.src_eid,
+            final_ball: return_ball.to_vec(),
+            sent: true,
+        });
+    }
+
+    // Prepare options for return message
+    // Use the return_options from the ABA message (same as Ethereum does)
+    // The enforced_options will combine with them to ensure proper formatting
+    // Note: Even if return_options are empty, enforced_options should add ExecutorLzReceiveOption
+    // for return messages to ensure the Executor can execute the return message
+    // The return-leg enforced options (`ReturnEnforcedOptions`, falling back to the send
+    // ones when unset) should include ExecutorLzReceiveOption with appropriate gas for
+    // the return message execution, which usually differs from a ping's
+    let return_options = super::return_options(&ctx.accounts.peer, aba_msg.return_options)?;
+
+    Ok(Some(Response {
+        return_ball,
+        message: return_message,
+        options: return_options,
+        native_fee: return_fee,
+        fee_source,
+    }))
+}
@@ -0,0 +1,34 @@
+use super::{Inbound, Response};
+use crate::*;
+use anchor_lang::prelude::*;
+
+/// Batches fold into the ball (last value wins) and never trigger a return.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    values: &[[u8; 32]],
+) -> Result<Option<Response>> {
+    if let Some(last) = values.last() {
+        let last = uint256_msg_codec::decode_u256(last)?;
+        let Some(last) = LzReceive::bound_ball(&ctx.accounts.store, inbound.params, last) else {
+            return Ok(None);
+        };
+        LzReceive::receive_ball(
+            &mut ctx.accounts.store,
+            last,
+            inbound.params,
+            String::new(),
+            None,
+            0,
+            inbound.inbound_count,
+        )?;
+        LzReceive::compose_ball(ctx, inbound.params.guid, last)?;
+        LzReceive::record_ball(
+            ctx,
+            inbound.params.src_eid,
+            last.to_be_bytes(),
+            BallDirection::Received,
+        )?;
+    }
+    Ok(None)
+}
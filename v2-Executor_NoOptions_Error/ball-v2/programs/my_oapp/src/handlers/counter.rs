@@ -0,0 +1,49 @@
+use super::{Inbound, Response};
+use crate::*;
+use anchor_lang::prelude::*;
+use ethnum::U256;
+
+/// A counter from the peer: keep it and send back the next one. The exchange has no end of
+/// its own; the auto-return budget and the fee are what stop it.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    counter: [u8; 32],
+) -> Result<Option<Response>> {
+    let params = inbound.params;
+    let value = U256::from_be_bytes(counter);
+    LzReceive::receive_ball(
+        &mut ctx.accounts.store,
+        value,
+        params,
+        String::new(),
+        None,
+        0,
+        inbound.inbound_count,
+    )?;
+    LzReceive::compose_ball(ctx, params.guid, value)?;
+    LzReceive::record_ball(ctx, params.src_eid, value.to_be_bytes(), BallDirection::Received)?;
+    let store = &mut ctx.accounts.store;
+
+    let Some((return_fee, fee_source)) = super::fund_return(store, inbound)? else {
+        return Ok(None);
+    };
+
+    let return_ball = uint256_msg_codec::increment_counter(&counter);
+    let return_message = uint256_msg_codec::tag_if(
+        uint256_msg_codec::encode_counter(&return_ball),
+        inbound.require_magic,
+    );
+    store.set_ball(return_ball);
+
+    // Counters carry no return options, so only the peer's enforced ones apply
+    let return_options = super::return_options(&ctx.accounts.peer, &[])?;
+
+    Ok(Some(Response {
+        return_ball,
+        message: return_message,
+        options: return_options,
+        native_fee: return_fee,
+        fee_source,
+    }))
+}
@@ -0,0 +1,45 @@
+use super::{Inbound, Response};
+use crate::{uint256_msg_codec::AbaMessageRef, *};
+use anchor_lang::prelude::*;
+
+/// The peer ended the game: keep its final ball and never answer.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    aba_msg: AbaMessageRef,
+) -> Result<Option<Response>> {
+    let params = inbound.params;
+    // A strict peer's mismatched final ball is dropped and doesn't end the game
+    if !LzReceive::check_consistency(
+        &mut ctx.accounts.peer,
+        &mut ctx.accounts.store,
+        params.src_eid,
+        aba_msg.ball_u256(),
+        aba_msg.step_u256(),
+    ) {
+        return Ok(None);
+    }
+    let ball = aba_msg.ball_u256();
+    let Some(ball) = LzReceive::bound_ball(&ctx.accounts.store, params, ball) else {
+        return Ok(None);
+    };
+    let store = &mut ctx.accounts.store;
+    LzReceive::receive_ball(
+        store,
+        ball,
+        params,
+        aba_msg.memo_lossy(),
+        aba_msg.known_origin_caller(),
+        aba_msg.seq,
+        inbound.inbound_count,
+    )?;
+    store.game_over = true;
+    emit!(crate::events::GameOver {
+        src_eid: params.src_eid,
+        final_ball: ball.to_be_bytes().to_vec(),
+        sent: false,
+    });
+    LzReceive::compose_ball(ctx, params.guid, ball)?;
+    LzReceive::record_ball(ctx, params.src_eid, ball.to_be_bytes(), BallDirection::Received)?;
+    Ok(None)
+}
@@ -0,0 +1,236 @@
+// Per-message-type handling for `lz_receive`. `handle` decodes the cleared message and routes
+// it to one handler per type; each applies the message to the Store and returns the return
+// leg to send, if any. Sending (or parking) the return stays in `LzReceive::apply`.
+pub mod aba;
+pub mod batch;
+pub mod counter;
+pub mod game_over;
+pub mod vanilla;
+
+use crate::{
+    consts::*,
+    uint256_msg_codec::{DecodedMessage, MsgCodecError},
+    *,
+};
+use anchor_lang::prelude::*;
+use oapp::LzReceiveParams;
+
+/// What `lz_receive` knows about the inbound message besides its body
+pub struct Inbound<'a> {
+    pub params: &'a LzReceiveParams,
+    // Peer's inbound counter after this message was cleared
+    pub inbound_count: u64,
+    // Store lamports before Clear, to measure a prepaid return fee
+    pub store_lamports: u64,
+    // Fresh FeeQuoteCache fee for the return leg (None = stale or no cache)
+    pub quoted_fee: Option<u64>,
+    // Peer exchanges the compact layout
+    pub compact: bool,
+    // Tag the return with the codec's MAGIC prefix
+    pub require_magic: bool,
+}
+
+/// A return leg a handler wants sent back to the source chain
+pub struct Response {
+    pub return_ball: [u8; 32],
+    pub message: Vec<u8>,
+    pub options: Vec<u8>,
+    // Fee to send with, or None when the Store can't cover it (the return is parked)
+    pub native_fee: Option<u64>,
+    pub fee_source: ReturnFeeSource,
+}
+
+/// Decode `message` (MAGIC prefix already removed) and run the handler for its type.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    message: &[u8],
+) -> Result<Option<Response>> {
+    // Compact peers only speak the compact layout; for everyone else both the legacy
+    // and the versioned layouts are accepted
+    let decoded = if inbound.compact {
+        uint256_msg_codec::decode_aba_compact_ref(message).map(DecodedMessage::Aba)
+    } else {
+        uint256_msg_codec::decode_any(message)
+    };
+    let decoded = match decoded {
+        // A well-formed message of a type this program doesn't handle
+        Err(MsgCodecError::UnknownMsgType) => {
+            let msg_type = if inbound.compact {
+                let at = uint256_msg_codec::COMPACT_BALL_SIZE;
+                u16::from_be_bytes([message[at], message[at + 1]])
+            } else {
+                uint256_msg_codec::peek_msg_type(message)?
+            };
+            return unknown(inbound, msg_type);
+        }
+        decoded => decoded?,
+    };
+    match decoded {
+        DecodedMessage::Vanilla(ball) => vanilla::handle(ctx, inbound, ball),
+        DecodedMessage::Batch(values) => batch::handle(ctx, inbound, &values),
+        // Counters are only played in counter mode
+        DecodedMessage::Counter(counter) if ctx.accounts.store.mode == GameMode::Counter => {
+            counter::handle(ctx, inbound, counter)
+        }
+        DecodedMessage::Counter(_) => unknown(inbound, uint256_msg_codec::COUNTER_TYPE),
+        DecodedMessage::Aba(aba_msg) => {
+            // A message past its TTL (plus the grace window for clock skew) has been
+            // cleared but changes nothing: no ball update and no return
+            if let Some((sent_at, ttl)) = aba_msg.ttl()? {
+                let now = Clock::get()?.unix_timestamp;
+                let expires_at = sent_at
+                    .saturating_add(ttl)
+                    .saturating_add(ctx.accounts.store.ttl_grace_secs);
+                if ttl != 0 && now > i64::try_from(expires_at).unwrap_or(i64::MAX) {
+                    emit!(crate::events::StaleMessageDropped {
+                        src_eid: inbound.params.src_eid,
+                        guid: inbound.params.guid,
+                        sent_at,
+                        ttl,
+                        now,
+                    });
+                    return Ok(None);
+                }
+            }
+            match aba_msg.msg_type {
+                uint256_msg_codec::ABA_TYPE => aba::handle(ctx, inbound, aba_msg),
+                uint256_msg_codec::GAME_OVER_TYPE => game_over::handle(ctx, inbound, aba_msg),
+                // Pongs end the exchange on the EVM side and are never sent here
+                msg_type => unknown(inbound, msg_type),
+            }
+        }
+    }
+}
+
+/// Types without a handler are cleared and reported instead of failing, since a failure
+/// would roll back the Clear and block the channel behind this nonce.
+fn unknown(inbound: &Inbound, msg_type: u16) -> Result<Option<Response>> {
+    emit!(crate::events::UnknownMessageDropped {
+        src_eid: inbound.params.src_eid,
+        guid: inbound.params.guid,
+        msg_type,
+    });
+    Ok(None)
+}
+
+/// Work out the return leg's fee, and where it came from, and charge a covered one to the
+/// auto-return budget. `Ok(None)` means the budget is spent and no return is sent; a None
+/// fee that it can't be covered, so the return is parked without using up the budget.
+fn fund_return(
+    store: &mut Account<Store>,
+    inbound: &Inbound,
+) -> Result<Option<(Option<u64>, ReturnFeeSource)>> {
+    let rent_floor = Rent::get()?.minimum_balance(store.to_account_info().data_len());
+    let (fee, fee_source) =
+        return_fee(inbound.store_lamports, store.fee_baseline.max(rent_floor), inbound.quoted_fee);
+    // Park the return when the Store's spare lamports can't cover the estimate rather than
+    // failing the whole instruction. A drop is always covered, since it is already there.
+    let available = inbound.store_lamports.saturating_sub(rent_floor);
+    let return_fee = if available < fee {
+        emit!(crate::events::ReturnFeeUnavailable {
+            src_eid: inbound.params.src_eid,
+            guid: inbound.params.guid,
+            required: fee,
+            available,
+            fee_source,
+        });
+        None
+    } else {
+        Some(fee)
+    };
+
+    // Circuit breaker: once the admin-set budget is spent, keep applying inbound balls
+    // but stop paying for returns
+    if let Some(remaining) = store.max_auto_returns {
+        if remaining == 0 {
+            emit!(crate::events::AutoReturnsExhausted {
+                src_eid: inbound.params.src_eid,
+                guid: inbound.params.guid,
+            });
+            return Ok(None);
+        }
+        if return_fee.is_some() {
+            store.max_auto_returns = Some(remaining - 1);
+        }
+    }
+    Ok(Some((return_fee, fee_source)))
+}
+
+/// The return fee for a Store holding `store_lamports` before Clear, and where it came from.
+/// Use exactly what the sender native-dropped above `baseline` (minus a small reserve);
+/// without a usable drop fall back to an estimate.
+fn return_fee(
+    store_lamports: u64,
+    baseline: u64,
+    quoted_fee: Option<u64>,
+) -> (u64, ReturnFeeSource) {
+    let dropped = store_lamports.saturating_sub(baseline);
+    if dropped > RETURN_FEE_RESERVE {
+        return (dropped - RETURN_FEE_RESERVE, ReturnFeeSource::NativeDrop);
+    }
+    // Estimate return message fee: Use 2x a fresh cached quote, or else 2x the base
+    // Sol->ETH fee, as a safety buffer
+    // This accounts for:
+    // - Base messaging cost (Sol->ETH)
+    // - Network conditions and gas price variations
+    // - Safety margin for successful execution
+    let (base_fee, fee_source) = match quoted_fee {
+        Some(quoted_fee) => (quoted_fee, ReturnFeeSource::CachedQuote),
+        None => (BASE_SOL_TO_ETH_FEE, ReturnFeeSource::Estimate),
+    };
+    (base_fee.saturating_mul(RETURN_FEE_MULTIPLIER), fee_source)
+}
+
+/// Return-leg options: the peer's return enforced options combined with `extra`, plus the
+/// peer's native drop when one is configured.
+pub(crate) fn return_options(peer: &PeerConfig, extra: &[u8]) -> Result<Vec<u8>> {
+    let return_options = peer.enforced_options.combine_return_options(&extra.to_vec())?;
+    // Optionally fund follow-up work on the peer chain with a native drop
+    Ok(if peer.return_drop_amount != 0 {
+        options_codec::append_worker_option(
+            return_options,
+            &options_builder::native_drop_option(
+                peer.return_drop_amount,
+                &peer.return_drop_receiver,
+            ),
+        )
+    } else {
+        return_options
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENT_FLOOR: u64 = 2_000_000;
+    const ESTIMATE: u64 = BASE_SOL_TO_ETH_FEE * RETURN_FEE_MULTIPLIER;
+
+    #[test]
+    fn a_native_drop_pays_for_the_return() {
+        let drop = 5_000_000;
+        let fee = return_fee(RENT_FLOOR + drop, RENT_FLOOR, Some(1));
+        assert!(fee == (drop - RETURN_FEE_RESERVE, ReturnFeeSource::NativeDrop));
+        // Lamports already counted in the fee baseline are not a drop
+        let fee = return_fee(RENT_FLOOR + drop, RENT_FLOOR + drop, None);
+        assert!(fee == (ESTIMATE, ReturnFeeSource::Estimate));
+    }
+
+    #[test]
+    fn without_a_drop_the_return_fee_is_estimated() {
+        let fee = return_fee(RENT_FLOOR, RENT_FLOOR, None);
+        assert!(fee == (ESTIMATE, ReturnFeeSource::Estimate));
+        let fee = return_fee(RENT_FLOOR, RENT_FLOOR, Some(1_000));
+        assert!(fee == (1_000 * RETURN_FEE_MULTIPLIER, ReturnFeeSource::CachedQuote));
+    }
+
+    #[test]
+    fn a_drop_within_the_reserve_falls_back_to_the_estimate() {
+        let store_lamports = RENT_FLOOR + RETURN_FEE_RESERVE;
+        let (fee, fee_source) = return_fee(store_lamports, RENT_FLOOR, None);
+        assert!(fee_source == ReturnFeeSource::Estimate);
+        // More than the Store can spare, so lz_receive parks the return
+        assert!(store_lamports - RENT_FLOOR < fee);
+    }
+}
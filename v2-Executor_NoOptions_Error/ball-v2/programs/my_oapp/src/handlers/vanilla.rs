@@ -0,0 +1,33 @@
+use super::{Inbound, Response};
+use crate::*;
+use anchor_lang::prelude::*;
+use ethnum::U256;
+
+/// One-way pings (and v1 peers) only update the ball; only ABA triggers a return.
+pub fn handle(
+    ctx: &mut Context<LzReceive>,
+    inbound: &Inbound,
+    ball: [u8; 32],
+) -> Result<Option<Response>> {
+    let ball = U256::from_be_bytes(ball);
+    let Some(ball) = LzReceive::bound_ball(&ctx.accounts.store, inbound.params, ball) else {
+        return Ok(None);
+    };
+    LzReceive::receive_ball(
+        &mut ctx.accounts.store,
+        ball,
+        inbound.params,
+        String::new(),
+        None,
+        0,
+        inbound.inbound_count,
+    )?;
+    LzReceive::compose_ball(ctx, inbound.params.guid, ball)?;
+    LzReceive::record_ball(
+        ctx,
+        inbound.params.src_eid,
+        ball.to_be_bytes(),
+        BallDirection::Received,
+    )?;
+    Ok(None)
+}
@@ -0,0 +1,105 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(params: CloseReceiptParams)]
+pub struct CloseReceipt<'info> {
+    #[account(
+        constraint = closer.key() == store.admin || closer.key() == receipt.payer
+            @ MyOAppError::NotReceiptOwner
+    )]
+    /// Admin of the OApp store, or the account that paid the receipt's rent
+    pub closer: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [RECEIPT_SEED, &store.key().to_bytes(), &params.guid],
+        bump = receipt.bump
+    )]
+    /// Receipt written by lz_receive for the message with this guid
+    pub receipt: Account<'info, Receipt>,
+    /// CHECK: only receives the Receipt rent back; must be the account that paid it
+    #[account(mut, address = receipt.payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CloseReceiptParams {
+    pub guid: [u8; 32], // Guid of the inbound message the receipt describes
+}
+
+impl CloseReceipt<'_> {
+    pub fn apply(_ctx: &mut Context<CloseReceipt>, _params: &CloseReceiptParams) -> Result<()> {
+        // Closing is done by the `close` constraint
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use std::collections::BTreeSet;
+
+    /// Check the close_receipt accounts signed by `closer` for a receipt paid by `payer` on
+    /// a Store administered by `admin`.
+    fn check_close(admin: Pubkey, payer: Pubkey, closer: Pubkey) -> Result<()> {
+        let guid = [7; 32];
+        let (store_key, store_bump) =
+            Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(0)], &crate::ID);
+        let (receipt_key, receipt_bump) =
+            Pubkey::find_program_address(&[RECEIPT_SEED, &store_key.to_bytes(), &guid], &crate::ID);
+        let mut store_data = Vec::new();
+        Store::new(admin, store_bump, Pubkey::new_unique(), 0).try_serialize(&mut store_data)?;
+        let receipt = Receipt {
+            bump: receipt_bump,
+            payer,
+            src_eid: 30101,
+            nonce: 1,
+            old_ball: [0; 32],
+            new_ball: [0; 32],
+            return_guid: None,
+            return_parked: false,
+        };
+        let mut receipt_data = Vec::new();
+        receipt.try_serialize(&mut receipt_data)?;
+
+        let system = anchor_lang::system_program::ID;
+        let mut accounts = [
+            TestAccount::signer(closer),
+            TestAccount::new(store_key, crate::ID, false, store_data),
+            TestAccount::new(receipt_key, crate::ID, false, receipt_data),
+            TestAccount::new(payer, system, false, Vec::new()),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut ix_data = Vec::new();
+        CloseReceiptParams { guid }.serialize(&mut ix_data).unwrap();
+        CloseReceipt::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &ix_data,
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn admin_or_rent_payer_may_close() {
+        let (admin, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(check_close(admin, payer, admin).is_ok());
+        assert!(check_close(admin, payer, payer).is_ok());
+    }
+
+    #[test]
+    fn anyone_else_may_not_close() {
+        let (admin, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let err = check_close(admin, payer, Pubkey::new_unique()).unwrap_err();
+        let expected = anchor_lang::error::Error::from(MyOAppError::NotReceiptOwner);
+        assert_eq!(ProgramError::from(err), ProgramError::from(expected));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Gives up on a parked return: the PendingReturn is closed without sending, its rent goes
+// back to the payer and it no longer counts towards Store.max_pending_returns.
+
+#[derive(Accounts)]
+#[instruction(params: DrainPendingReturnParams)]
+pub struct DrainPendingReturn<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [PENDING_RETURN_SEED, &store.key().to_bytes(), &params.guid],
+        bump = pending_return.bump
+    )]
+    /// Return leg parked by lz_receive, closed unsent
+    pub pending_return: Account<'info, PendingReturn>,
+    /// CHECK: only receives the PendingReturn rent back; must be the account that paid it
+    #[account(mut, address = pending_return.payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct DrainPendingReturnParams {
+    pub guid: [u8; 32], // Guid of the inbound message whose return was parked
+}
+
+impl DrainPendingReturn<'_> {
+    pub fn apply(
+        ctx: &mut Context<DrainPendingReturn>,
+        _params: &DrainPendingReturnParams,
+    ) -> Result<()> {
+        // Closing is done by the `close` constraint
+        ctx.accounts.store.close_pending_return();
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+
+// Grows the Store or the LzReceiveTypesAccounts PDA to an admin-chosen size, zero-filling the
+// new tail, so an account sized by an older build fits a newer layout. Unlike
+// `migrate_store` nothing is upgraded; the size is the caller's, between the current size
+// and MAX_EXTENDED_ACCOUNT_SIZE. `extend_peer_config` grows a PeerConfig created by an older
+// build to the current `PeerConfig::SIZE`, which `set_peer_config` requires.
+
+#[derive(Accounts)]
+pub struct ExtendStore<'info> {
+    #[account(mut)]
+    /// Admin of the OApp store; pays for the extra rent
+    pub admin: Signer<'info>,
+    /// CHECK: An older Store may not deserialize as the current one, so it is handled as
+    /// raw bytes; checked as in `MigrateStore`.
+    #[account(mut, owner = crate::ID)]
+    pub store: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendLzReceiveTypes<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store; pays for the extra rent
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    /// CHECK: Handled as raw bytes for the same reason as the Store in `ExtendStore`
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [LZ_RECEIVE_TYPES_SEED, &store.key().to_bytes()],
+        bump
+    )]
+    pub lz_receive_types_accounts: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: ExtendPeerConfigParams)]
+pub struct ExtendPeerConfig<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store; pays for the extra rent
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    /// CHECK: Handled as raw bytes for the same reason as the Store in `ExtendStore`
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
+        bump
+    )]
+    pub peer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ExtendPeerConfigParams {
+    pub remote_eid: u32,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ExtendAccountParams {
+    pub new_size: u32,
+}
+
+impl ExtendStore<'_> {
+    pub fn apply(ctx: &mut Context<ExtendStore>, params: &ExtendAccountParams) -> Result<()> {
+        let store = ctx.accounts.store.to_account_info();
+        MigrateStore::check_admin(&store, &ctx.accounts.admin.key())?;
+        grow_account(
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            &store,
+            params.new_size as usize,
+        )
+    }
+}
+
+impl ExtendLzReceiveTypes<'_> {
+    pub fn apply(
+        ctx: &mut Context<ExtendLzReceiveTypes>,
+        params: &ExtendAccountParams,
+    ) -> Result<()> {
+        let info = ctx.accounts.lz_receive_types_accounts.to_account_info();
+        grow_account(
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            &info,
+            params.new_size as usize,
+        )?;
+        // The grown tail is zeroed, so fill in the ReturnAccounts key it now has room for
+        let (return_accounts, _) = Pubkey::find_program_address(
+            &[RETURN_ACCOUNTS_SEED, &ctx.accounts.store.key().to_bytes()],
+            ctx.program_id,
+        );
+        LzReceiveTypesAccounts::write_return_accounts(&info, &return_accounts)
+    }
+}
+
+impl ExtendPeerConfig<'_> {
+    pub fn apply(ctx: &mut Context<ExtendPeerConfig>) -> Result<()> {
+        let peer = ctx.accounts.peer.to_account_info();
+        if peer.data_len() >= PeerConfig::SIZE {
+            return Ok(());
+        }
+        grow_account(&ctx.accounts.admin, &ctx.accounts.system_program, &peer, PeerConfig::SIZE)
+    }
+}
+
+/// Realloc `account` to `new_size` bytes, zero-filling the added tail, with `payer` topping
+/// up the rent. Shrinking and sizes above MAX_EXTENDED_ACCOUNT_SIZE are rejected.
+pub(crate) fn grow_account<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    account: &AccountInfo<'info>,
+    new_size: usize,
+) -> Result<()> {
+    require!(
+        new_size >= account.data_len() && new_size <= MAX_EXTENDED_ACCOUNT_SIZE,
+        MyOAppError::InvalidAccountSize
+    );
+    if new_size == account.data_len() {
+        return Ok(());
+    }
+    let shortfall = Rent::get()?.minimum_balance(new_size).saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_size, true)?;
+    Ok(())
+}
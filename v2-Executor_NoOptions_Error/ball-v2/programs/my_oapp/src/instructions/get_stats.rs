@@ -0,0 +1,44 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Read-only view of the Store's game state, totals and metadata, for clients that simulate
+// it instead of decoding the account.
+
+#[derive(Accounts)]
+pub struct GetStats<'info> {
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+}
+
+/// The Store's game state, totals and metadata, as returned by `get_stats`.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct StoreStats {
+    pub ball: [u8; 32],
+    pub mode: GameMode,
+    pub game_over: bool,
+    pub next_seq: u64,
+    pub total_sent: u64,
+    pub total_received: u64,
+    pub in_flight: u16, // Pings sent whose expected answer hasn't arrived
+    pub recent_touches: Vec<EidTouch>, // Last sends and receives, newest first
+    pub label: String,
+    pub metadata_uri: String,
+}
+
+impl GetStats<'_> {
+    pub fn apply(ctx: &Context<GetStats>) -> Result<StoreStats> {
+        let store = &ctx.accounts.store;
+        Ok(StoreStats {
+            ball: store.ball,
+            mode: store.mode,
+            game_over: store.game_over,
+            next_seq: store.next_seq,
+            total_sent: store.total_sent,
+            total_received: store.total_received,
+            in_flight: store.in_flight,
+            recent_touches: store.recent_touches(),
+            label: Store::padded_str(&store.label),
+            metadata_uri: Store::padded_str(&store.metadata_uri),
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Creates the Store's BallHistory ring; send and lz_receive record into it from then on.
+
+#[derive(Accounts)]
+pub struct InitHistory<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store; pays the history's rent
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    #[account(
+        init,
+        payer = admin,
+        space = BallHistory::SIZE,
+        seeds = [BALL_HISTORY_SEED, &store.key().to_bytes()],
+        bump
+    )]
+    pub ball_history: Box<Account<'info, BallHistory>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl InitHistory<'_> {
+    pub fn apply(ctx: &mut Context<InitHistory>) -> Result<()> {
+        ctx.accounts.ball_history.bump = ctx.bumps.ball_history;
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{consts::*, *};
+use crate::{consts::*, errors::MyOAppError, *};
 
 use oapp::endpoint::{instructions::RegisterOAppParams, ID as ENDPOINT_ID};
 
@@ -15,9 +15,9 @@ pub struct InitStore<'info> {
         init,
         payer = payer,
         space = Store::SIZE,
-        seeds = [STORE_SEED], // You can namespace this further if your program manages multiple stores.
-        // e.g. If there can be a store for each user, you can use something like:
-        // seeds = [STORE_SEED, &user.key().as_ref()]
+        // Namespaced by store id so one deployment can host several games; id 0 is the
+        // original [STORE_SEED] address.
+        seeds = [STORE_SEED, &Store::id_seed(params.id)],
         bump
     )]
     pub store: Account<'info, Store>,
@@ -36,23 +36,52 @@ pub struct InitStore<'info> {
 pub struct InitStoreParams {
     pub admin: Pubkey,
     pub endpoint: Pubkey,
+    pub nonce_window: u16, // Recent nonces per peer whose retries are no-ops (0 = disabled)
+    // Ball the first game starts from, as a big-endian uint256 (None = Store::INITIAL_BALL)
+    pub initial_ball: Option<[u8; 32]>,
+    pub allow_zero_ball: bool, // Accept a zero initial_ball, which can't be sent unsigned
+    pub id: u64, // Store id the Store PDA is derived from (0 = the single-store address)
+    pub label: [u8; STORE_LABEL_LEN], // Name of this deployment, zero-padded UTF-8
+    pub metadata_uri: [u8; METADATA_URI_LEN], // Zero-padded UTF-8
 }
 
 impl InitStore<'_> {
     pub fn apply(ctx: &mut Context<InitStore>, params: &InitStoreParams) -> Result<()> {
-        ctx.accounts
-            .store
-            .set_inner(Store::new(params.admin, ctx.bumps.store, params.endpoint));
+        require!(params.nonce_window <= MAX_NONCE_WINDOW, MyOAppError::InvalidNonceWindow);
+        ctx.accounts.store.set_inner(Store::new(
+            params.admin,
+            ctx.bumps.store,
+            params.endpoint,
+            params.nonce_window,
+        ));
+        ctx.accounts.store.id = params.id;
+        // Native drops are measured from here, so nothing is counted as one before the first
+        ctx.accounts.store.fee_baseline = ctx.accounts.store.to_account_info().lamports();
+        ctx.accounts.store.set_metadata(params.label, params.metadata_uri)?;
+        if let Some(initial_ball) = params.initial_ball {
+            require!(
+                initial_ball != [0u8; 32] || params.allow_zero_ball,
+                MyOAppError::ZeroInitialBall
+            );
+            ctx.accounts.store.set_ball(initial_ball);
+            ctx.accounts.store.initial_ball = initial_ball;
+        }
+        let store = ctx.accounts.store.key();
+        let (return_accounts, _) = Pubkey::find_program_address(
+            &[RETURN_ACCOUNTS_SEED, &store.to_bytes()],
+            ctx.program_id,
+        );
         ctx.accounts
             .lz_receive_types_accounts
-            .set_inner(LzReceiveTypesAccounts::new(ctx.accounts.store.key()));
+            .set_inner(LzReceiveTypesAccounts::new(store, return_accounts));
         // the above lines are required for all OApp implementations
 
         // Prepare the delegate address for the OApp registration.
         let register_params = RegisterOAppParams { delegate: ctx.accounts.store.admin };
 
         // The Store PDA 'signs' CPI to the Endpoint program to register the OApp.
-        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+        let id_seed = Store::id_seed(ctx.accounts.store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[ctx.accounts.store.bump]];
         oapp::endpoint_cpi::register_oapp(
             ENDPOINT_ID,
             ctx.accounts.store.key(),
@@ -61,6 +90,14 @@ impl InitStore<'_> {
             register_params,
         )?;
 
+        let initial_ball = uint256_msg_codec::decode_u256(&ctx.accounts.store.ball)?;
+        emit!(crate::events::StoreInitialized {
+            admin: ctx.accounts.store.admin,
+            store_id: params.id,
+            initial_ball: initial_ball.to_be_bytes().to_vec(),
+            initial_ball_str: uint256_msg_codec::ball_to_string(initial_ball, false),
+            nonce_window: params.nonce_window,
+        });
         Ok(())
     }
 }
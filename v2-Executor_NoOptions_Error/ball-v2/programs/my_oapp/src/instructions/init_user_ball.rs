@@ -0,0 +1,36 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Creates the signer's own UserBall, starting from the Store's initial ball, so they can
+// play with `send_user_ping` alongside the Store's game.
+
+#[derive(Accounts)]
+pub struct InitUserBall<'info> {
+    #[account(mut)]
+    /// Owner of the new UserBall; pays its rent
+    pub user: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        init,
+        payer = user,
+        space = UserBall::SIZE,
+        seeds = [USER_BALL_SEED, &store.key().to_bytes(), &user.key().to_bytes()],
+        bump
+    )]
+    pub user_ball: Account<'info, UserBall>,
+    pub system_program: Program<'info, System>,
+}
+
+impl InitUserBall<'_> {
+    pub fn apply(ctx: &mut Context<InitUserBall>) -> Result<()> {
+        ctx.accounts.store.require_version(1)?;
+        ctx.accounts.user_ball.set_inner(UserBall {
+            bump: ctx.bumps.user_ball,
+            user: ctx.accounts.user.key(),
+            ball: ctx.accounts.store.initial_ball,
+            ..Default::default()
+        });
+        Ok(())
+    }
+}
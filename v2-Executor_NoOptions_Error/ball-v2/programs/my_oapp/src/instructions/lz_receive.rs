@@ -3,10 +3,14 @@ use anchor_lang::prelude::*;
 use ethnum::U256;
 use oapp::{
     endpoint::{
-        cpi::accounts::Clear,
-        instructions::{ClearParams, SendParams},
+        cpi::accounts::{Clear, Send as EndpointSend, SendCompose},
+        instructions::{ClearParams, SendComposeParams, SendParams},
         ConstructCPIContext,
+        ENDPOINT_SEED,
         ID as ENDPOINT_ID,
+        NONCE_SEED,
+        PAYLOAD_HASH_SEED,
+        SEND_LIBRARY_CONFIG_SEED,
     },
     LzReceiveParams,
 };
@@ -17,25 +21,108 @@ pub struct LzReceive<'info> {
     /// OApp Store PDA.  This account represents the "address" of your OApp on
     /// Solana and can contain any state relevant to your application.
     /// Customize the fields in `Store` as needed.
-    #[account(mut, seeds = [STORE_SEED], bump = store.bump)]
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
     pub store: Account<'info, Store>,
-    /// Peer config PDA for the sending chain. Ensures `params.sender` can only be the allowed peer from that remote chain.
+    /// Peer config PDA for the sending chain. Ensures `params.sender` can only be one of the allowed senders from that remote chain.
     #[account(
+        mut,
         seeds = [PEER_SEED, &store.key().to_bytes(), &params.src_eid.to_be_bytes()],
         bump = peer.bump,
-        constraint = params.sender == peer.peer_address
+        constraint = peer.is_sender(&params.sender)
     )]
     pub peer: Account<'info, PeerConfig>,
+    /// Pays the rent of a PendingReturn when the return leg has to be parked, and of the
+    /// Receipt while receipts are enabled (the Executor).
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Only created, by `park_return`, when the return leg can't be sent. The seeds
+    /// tie it to the inbound guid.
+    #[account(
+        mut,
+        seeds = [PENDING_RETURN_SEED, &store.key().to_bytes(), &params.guid],
+        bump
+    )]
+    pub pending_return: UncheckedAccount<'info>,
+    /// CHECK: Only created, by `write_receipt`, while `Store.write_receipts` is set. The
+    /// seeds tie it to the inbound guid.
+    #[account(
+        mut,
+        seeds = [RECEIPT_SEED, &store.key().to_bytes(), &params.guid],
+        bump
+    )]
+    pub receipt: UncheckedAccount<'info>,
+    /// CHECK: Only read when it exists, since `refresh_quote` creates it. The seeds tie it
+    /// to the source eid, where the return leg goes.
+    #[account(
+        seeds = [FEE_QUOTE_CACHE_SEED, &store.key().to_bytes(), &params.src_eid.to_be_bytes()],
+        bump
+    )]
+    pub fee_quote_cache: UncheckedAccount<'info>,
+    /// CHECK: Only written when it exists, since `init_history` creates it.
+    #[account(mut, seeds = [BALL_HISTORY_SEED, &store.key().to_bytes()], bump)]
+    pub ball_history: UncheckedAccount<'info>,
+    /// CHECK: UserBall PDA of the message's origin caller, checked in `user_ball` against
+    /// the decoded message. Only used when `init_user_ball` has created it.
+    #[account(mut)]
+    pub user_ball: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 impl LzReceive<'_> {
     pub fn apply(ctx: &mut Context<LzReceive>, params: &LzReceiveParams) -> Result<()> {
-        // The OApp Store PDA is used to sign the CPI to the Endpoint program.
-        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+        // Reject oversized payloads before spending compute on the Clear CPI
+        require!(params.message.len() <= MAX_MESSAGE_LEN, MyOAppError::MessageTooLarge);
+
+        // A paused peer's message fails before Clear, so it can be retried once unpaused
+        ctx.accounts.peer.require_inbound_open()?;
+
+        // An Executor retry of a nonce that was already applied succeeds without doing
+        // anything, instead of failing in Endpoint::clear or sending a second return
+        let nonce_window = ctx.accounts.store.nonce_window;
+        if ctx.accounts.peer.processed_nonces.contains(params.nonce, nonce_window) {
+            return Ok(());
+        }
 
         // The first Clear::MIN_ACCOUNTS_LEN accounts were returned by
         // `lz_receive_types` and are required for Endpoint::clear
-        let accounts_for_clear = &ctx.remaining_accounts[0..Clear::MIN_ACCOUNTS_LEN];
+        let send_start = Self::send_accounts_start(&ctx.accounts.store);
+        let (accounts_for_clear, _) =
+            Self::split_remaining_accounts(ctx.remaining_accounts, send_start)?;
+
+        // A failing Clear CPI can't be caught, so a message whose payload hash is already
+        // gone is recognised up front. Only this program can clear its messages, and it does
+        // so in the same transaction that applies them, so a nonce at or below the last one
+        // applied is a redelivery and succeeds as a no-op. Anything else was never verified.
+        if Self::payload_cleared(accounts_for_clear, &ctx.accounts.store.key(), params) {
+            if params.nonce <= ctx.accounts.peer.last_processed_nonce {
+                return Ok(());
+            }
+            return err!(MyOAppError::AlreadyCleared);
+        }
+
+        // With the PendingReturn cap reached, a message that could park another return is
+        // refused before Clear so it stays deliverable once some are retried or drained
+        let store = &ctx.accounts.store;
+        if store.pending_returns_full() && LzReceiveTypes::may_return(store, &params.message) {
+            emit!(crate::events::PendingReturnsCapReached {
+                src_eid: params.src_eid,
+                guid: params.guid,
+                pending_returns: store.pending_returns,
+                max_pending_returns: store.max_pending_returns,
+            });
+            return err!(MyOAppError::TooManyPendingReturns);
+        }
+
+        ctx.accounts.peer.accept_nonce(params.nonce)?;
+
+        // A native drop from the EVM sender lands on the Store before lz_receive runs;
+        // snapshot the balance before Clear so the prepaid return fee can be measured
+        let store_lamports = ctx.accounts.store.to_account_info().lamports();
+
+        // The OApp Store PDA is used to sign the CPI to the Endpoint program.
+        let id_seed = Store::id_seed(ctx.accounts.store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[ctx.accounts.store.bump]];
+
         // Call the Endpoint::clear CPI to clear the message from the Endpoint program.
         // This is necessary to ensure the message is processed only once and to
         // prevent replays.
@@ -53,97 +140,763 @@ impl LzReceive<'_> {
                 message: params.message.clone(),
             },
         )?;
+        if nonce_window != 0 {
+            ctx.accounts.peer.processed_nonces.insert(params.nonce, nonce_window);
+        }
+        let peer = &mut ctx.accounts.peer;
+        peer.inbound_count = peer.inbound_count.wrapping_add(1);
+        let inbound_count = peer.inbound_count;
+        let store = &mut ctx.accounts.store;
+        store.total_received = store.total_received.wrapping_add(1);
+        store.touch(params.src_eid, BallDirection::Received)?;
 
-        // Decode ABA message - only ABA flows are supported
-        let aba_msg = uint256_msg_codec::decode_aba(&params.message)?;
-        
-        // Verify this is an ABA message type
-        require!(
-            aba_msg.msg_type == uint256_msg_codec::ABA_TYPE,
-            MyOAppError::InvalidMessageType
-        );
+        // Messages from a correctly wired peer can be told apart by the MAGIC prefix
+        let (tagged, message) = uint256_msg_codec::split_tag(&params.message);
+        let require_magic = ctx.accounts.store.require_magic;
+        require!(tagged || !require_magic, MyOAppError::ForeignMessage);
 
-        // Update ball
-        let store = &mut ctx.accounts.store;
-        let old_ball = store.ball;
-        let old_ball_ethnum = U256::from_be_bytes(old_ball);
-        let new_ball_ethnum = U256::from_be_bytes(aba_msg.ball);
-        store.set_ball(aba_msg.ball);
+        // Decoding, the TTL check and the per-type handling live in `handlers`; only ABA
+        // messages hand back a return leg
+        let inbound = handlers::Inbound {
+            params,
+            inbound_count,
+            store_lamports,
+            quoted_fee: Self::cached_quote(&ctx.accounts.fee_quote_cache)?,
+            compact: ctx.accounts.peer.compact,
+            require_magic,
+        };
+        let old_ball = ctx.accounts.store.ball;
+        let (return_guid, return_parked) = match handlers::handle(ctx, &inbound, message)? {
+            Some(response) => match Self::send_return(ctx, params, response)? {
+                Some(return_guid) => (Some(return_guid), false),
+                None => (None, true),
+            },
+            None => (None, false),
+        };
 
-        // Emit event tracking the ball value
-        emit!(crate::events::BallReceived {
-            old_ball: old_ball.to_vec(),
-            new_ball: aba_msg.ball.to_vec(),
-            old_ball_str: old_ball_ethnum.to_string(),
-            new_ball_str: new_ball_ethnum.to_string(),
-            src_eid: params.src_eid,
-        });
+        if ctx.accounts.store.write_receipts {
+            Self::write_receipt(ctx, params, old_ball, return_guid, return_parked)?;
+        }
+        Ok(())
+    }
+
+    /// Send the return leg a handler built, or park it when it can't be sent. Returns the
+    /// guid of the sent message, or None when it was parked.
+    fn send_return(
+        ctx: &mut Context<LzReceive>,
+        params: &LzReceiveParams,
+        response: handlers::Response,
+    ) -> Result<Option<[u8; 32]>> {
+        let handlers::Response {
+            return_ball,
+            message: return_message,
+            options: return_options,
+            native_fee: return_fee,
+            fee_source,
+        } = response;
+        let id_seed = Store::id_seed(ctx.accounts.store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[ctx.accounts.store.bump]];
+
+        // For ABA pattern, the return message accounts should be provided
+        // as additional remaining_accounts after the clear accounts
+        // (after the send_compose accounts when a composer is configured)
+        let send_start = Self::send_accounts_start(&ctx.accounts.store);
+        let (_, accounts_for_send) =
+            Self::split_remaining_accounts(ctx.remaining_accounts, send_start)?;
+
+        // A failing Send CPI aborts the whole transaction (rolling back Clear), and Solana
+        // gives no way to catch it. Returns that are known not to go through, because the fee
+        // or the send accounts are missing, are parked instead so the inbound leg commits.
+        // In split-return mode every return is parked for `dispatch_return`.
+        let split_return = ctx.accounts.store.split_return;
+        let return_fee = match return_fee {
+            Some(fee)
+                if !split_return && accounts_for_send.len() >= EndpointSend::MIN_ACCOUNTS_LEN =>
+            {
+                fee
+            }
+            _ => {
+                Self::park_return(
+                    ctx,
+                    params.src_eid,
+                    params.guid,
+                    return_message,
+                    return_options,
+                )?;
+                return Ok(None);
+            }
+        };
+
+        let options_len = return_options.len() as u32;
+        let receiver = ctx.accounts.peer.return_address();
+        Self::check_send_accounts(
+            accounts_for_send,
+            &ctx.accounts.store.key(),
+            &mut ctx.accounts.peer,
+            params.src_eid,
+        )?;
 
-        // ABA pattern: always send response back
-        // Decrement ball for return message
-        let ball_ethnum = U256::from_be_bytes(aba_msg.ball);
-        let return_ball_ethnum = ball_ethnum.saturating_sub(U256::ONE);
-        let return_ball = return_ball_ethnum.to_be_bytes();
-        
-        // Encode return message (vanilla type - return messages are always vanilla)
-        let return_message = uint256_msg_codec::encode(&return_ball);
-        
-        // Update store with decremented ball
-        store.set_ball(return_ball);
-        
-        // Prepare options for return message
-        // Use the return_options from the ABA message (same as Ethereum does)
-        // The enforced_options will combine with them to ensure proper formatting
-        // Note: Even if return_options are empty, enforced_options should add ExecutorLzReceiveOption
-        // for vanilla messages (msgType 1) to ensure the Executor can execute the return message
-        // The enforced_options are configured via layerzero.config.ts and should include
-        // ExecutorLzReceiveOption with appropriate gas for the return message execution
-        let return_options = ctx
-            .accounts
-            .peer
-            .enforced_options
-            .combine_options(&None::<Vec<u8>>, &aba_msg.return_options)?;
-        
         // Prepare SendParams for the return message
         // Send back to src_eid (the origin chain)
-        // Estimate return message fee: Use 2x the base Sol->ETH fee as a safety buffer
-        // This accounts for:
-        // - Base messaging cost (Sol->ETH)
-        // - Network conditions and gas price variations
-        // - Safety margin for successful execution
-        // Note: The actual fee may vary, but this provides a reasonable estimate.
-        // The executor should ensure sufficient native fee is forwarded in the initial message.
-        let estimated_return_fee = consts::BASE_SOL_TO_ETH_FEE
-            .checked_mul(consts::RETURN_FEE_MULTIPLIER)
-            .unwrap_or(consts::BASE_SOL_TO_ETH_FEE * consts::RETURN_FEE_MULTIPLIER);
-        
         let send_params = SendParams {
             dst_eid: params.src_eid,
-            receiver: ctx.accounts.peer.peer_address,
+            receiver,
             message: return_message,
             options: return_options,
-            native_fee: estimated_return_fee,
+            native_fee: return_fee,
             lz_token_fee: 0, // No LZ token fee for return
         };
         
         // Send return message via Endpoint CPI
         // Note: remaining_accounts after Clear::MIN_ACCOUNTS_LEN should contain
-        // accounts needed for Send CPI (returned by send_types instruction)
+        // accounts needed for Send CPI (returned by lz_receive_types from ReturnAccounts)
         // These accounts are typically fetched off-chain using the endpoint SDK's
         // getSendIXAccountMetaForCPI method
-        
-        // For ABA pattern, the return message accounts should be provided
-        // as additional remaining_accounts after the clear accounts
-        let accounts_for_send = &ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..];
-        
-        oapp::endpoint_cpi::send(
+        let messaging_receipt = oapp::endpoint_cpi::send(
             ENDPOINT_ID,
             ctx.accounts.store.key(),
             accounts_for_send,
             seeds,
             send_params,
         )?;
+        // The receipt's guid is what the return is known by on the EVM side. It is also
+        // left as the instruction's return data so callers can read it from a simulation.
+        let mut return_data = Vec::new();
+        messaging_receipt.serialize(&mut return_data)?;
+        anchor_lang::solana_program::program::set_return_data(&return_data);
+
+        // Only reached when the Send CPI succeeded
+        Self::record_ball(ctx, params.src_eid, return_ball, BallDirection::Sent)?;
+        let peer = &mut ctx.accounts.peer;
+        peer.outbound_count = peer.outbound_count.wrapping_add(1);
+        let store = &mut ctx.accounts.store;
+        // The drop was spent on this return; the next one is measured from what is left
+        store.fee_baseline = store.to_account_info().lamports();
+        store.total_sent = store.total_sent.wrapping_add(1);
+        store.touch(params.src_eid, BallDirection::Sent)?;
+        emit!(crate::events::BallReturned {
+            return_ball: return_ball.to_vec(),
+            return_ball_str: uint256_msg_codec::ball_to_string(
+                U256::from_be_bytes(return_ball),
+                ctx.accounts.store.signed_ball,
+            ),
+            dst_eid: params.src_eid,
+            receiver,
+            options_len,
+            native_fee: return_fee,
+            fee_source,
+            outbound_count: ctx.accounts.peer.outbound_count,
+            total_sent: ctx.accounts.store.total_sent,
+            return_guid: messaging_receipt.guid,
+            return_nonce: messaging_receipt.nonce,
+            mode: ctx.accounts.store.mode,
+            previous_touch: ctx.accounts.store.previous_touch(),
+        });
+
+        Ok(Some(messaging_receipt.guid))
+    }
+
+    /// Record what this message did in a `Receipt` PDA keyed by its guid. A guid that
+    /// already has a receipt fails here, since the account can't be created twice.
+    fn write_receipt(
+        ctx: &Context<LzReceive>,
+        params: &LzReceiveParams,
+        old_ball: [u8; 32],
+        return_guid: Option<[u8; 32]>,
+        return_parked: bool,
+    ) -> Result<()> {
+        let receipt = ctx.accounts.receipt.to_account_info();
+        let store_key = ctx.accounts.store.key().to_bytes();
+        let bump = ctx.bumps.receipt;
+        let seeds: &[&[u8]] = &[RECEIPT_SEED, &store_key, &params.guid, &[bump]];
+        Self::create_guid_account(ctx, &receipt, seeds, Receipt::SIZE)?;
+
+        let record = Receipt {
+            bump,
+            payer: ctx.accounts.payer.key(),
+            src_eid: params.src_eid,
+            nonce: params.nonce,
+            old_ball,
+            new_ball: ctx.accounts.store.ball,
+            return_guid,
+            return_parked,
+        };
+        record.try_serialize(&mut &mut receipt.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// Create a PDA keyed by the message guid, paid by the Executor. The guid is public before
+    /// delivery, so anyone can fund the address first and make `create_account` fail for
+    /// good; like Anchor's `init`, a funded account is topped up, allocated and assigned
+    /// instead. An account this program already owns still fails in `allocate`.
+    fn create_guid_account<'info>(
+        ctx: &Context<LzReceive<'info>>,
+        account: &AccountInfo<'info>,
+        seeds: &[&[u8]],
+        space: usize,
+    ) -> Result<()> {
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let rent = Rent::get()?.minimum_balance(space);
+        if account.lamports() == 0 {
+            return anchor_lang::system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program,
+                    anchor_lang::system_program::CreateAccount { from: payer, to: account.clone() },
+                    &[seeds],
+                ),
+                rent,
+                space as u64,
+                ctx.program_id,
+            );
+        }
+        let shortfall = rent.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer { from: payer, to: account.clone() },
+                ),
+                shortfall,
+            )?;
+        }
+        anchor_lang::system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::Allocate { account_to_allocate: account.clone() },
+                &[seeds],
+            ),
+            space as u64,
+        )?;
+        anchor_lang::system_program::assign(
+            CpiContext::new_with_signer(
+                system_program,
+                anchor_lang::system_program::Assign { account_to_assign: account.clone() },
+                &[seeds],
+            ),
+            ctx.program_id,
+        )
+    }
+
+    /// Record a return leg that can't be sent now in a `PendingReturn` PDA keyed by the
+    /// inbound guid, so `retry_return` can send it later.
+    fn park_return(
+        ctx: &mut Context<LzReceive>,
+        dst_eid: u32,
+        guid: [u8; 32],
+        message: Vec<u8>,
+        options: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.store.open_pending_return();
+        let pending_return = ctx.accounts.pending_return.to_account_info();
+        let space = PendingReturn::space(message.len(), options.len());
+        let store_key = ctx.accounts.store.key().to_bytes();
+        let bump = ctx.bumps.pending_return;
+        let seeds: &[&[u8]] = &[PENDING_RETURN_SEED, &store_key, &guid, &[bump]];
+        Self::create_guid_account(ctx, &pending_return, seeds, space)?;
+
+        let pending = PendingReturn {
+            bump,
+            payer: ctx.accounts.payer.key(),
+            dst_eid,
+            guid,
+            message,
+            options,
+        };
+        pending.try_serialize(&mut &mut pending_return.try_borrow_mut_data()?[..])?;
+
+        emit!(crate::events::ReturnParked {
+            dst_eid,
+            guid,
+            pending_return: pending_return.key(),
+        });
+        Ok(())
+    }
+
+    /// The FeeQuoteCache quote for the return leg while it is fresh. A missing cache and a
+    /// stale one both leave the fee to the estimate; a stale one is logged.
+    fn cached_quote(fee_quote_cache: &UncheckedAccount) -> Result<Option<u64>> {
+        if fee_quote_cache.owner != &crate::ID || fee_quote_cache.data_is_empty() {
+            return Ok(None);
+        }
+        let data = fee_quote_cache.try_borrow_data()?;
+        let cache = FeeQuoteCache::try_deserialize(&mut &data[..])?;
+        let slot = Clock::get()?.slot;
+        let fresh_fee = cache.fresh_fee(slot);
+        if fresh_fee.is_none() {
+            msg!("Fee quote from slot {} is stale at slot {}", cache.quoted_slot, slot);
+        }
+        Ok(fresh_fee)
+    }
+
+    /// Whether the payload hash PDA for this message (index 4 of the Clear accounts, after
+    /// the endpoint program, receiver, OApp registry and nonce) exists no more. A different
+    /// account at that index is left for Endpoint::clear to reject.
+    fn payload_cleared(
+        accounts_for_clear: &[AccountInfo],
+        store: &Pubkey,
+        params: &LzReceiveParams,
+    ) -> bool {
+        let (payload_hash, _) = Pubkey::find_program_address(
+            &[
+                PAYLOAD_HASH_SEED,
+                store.as_ref(),
+                &params.src_eid.to_be_bytes(),
+                &params.sender[..],
+                &params.nonce.to_be_bytes(),
+            ],
+            &ENDPOINT_ID,
+        );
+        accounts_for_clear
+            .get(4)
+            .is_some_and(|account| account.key() == payload_hash && account.lamports() == 0)
+    }
+
+    /// Check that the accounts handed to the Send CPI were derived for this return: the
+    /// endpoint program, the Store as sender and the endpoint PDAs that depend on the
+    /// destination, so a wrong account list fails with `InvalidSendAccounts` (and a log line
+    /// naming the first mismatch) instead of inside the endpoint. The send-library accounts
+    /// that follow depend on on-chain configuration and are left to the endpoint.
+    /// The PDAs are derived from the bumps cached in the peer's `send_pda_bumps`; they are
+    /// only searched for on the first return, or after the return receiver changed.
+    fn check_send_accounts(
+        accounts: &[AccountInfo],
+        store: &Pubkey,
+        peer: &mut PeerConfig,
+        dst_eid: u32,
+    ) -> Result<()> {
+        let eid = dst_eid.to_be_bytes();
+        let receiver = peer.return_address();
+        let send_library_config: &[&[u8]] = &[SEND_LIBRARY_CONFIG_SEED, store.as_ref(), &eid];
+        let default_send_library_config: &[&[u8]] = &[SEND_LIBRARY_CONFIG_SEED, &eid];
+        let endpoint_settings: &[&[u8]] = &[ENDPOINT_SEED];
+        let nonce: &[&[u8]] = &[NONCE_SEED, store.as_ref(), &eid, &receiver[..]];
+
+        let bumps = match peer.send_pda_bumps.filter(|bumps| bumps.receiver == receiver) {
+            Some(bumps) => bumps,
+            None => {
+                let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &ENDPOINT_ID).1;
+                SendPdaBumps {
+                    receiver,
+                    send_library_config: find(send_library_config),
+                    default_send_library_config: find(default_send_library_config),
+                    endpoint_settings: find(endpoint_settings),
+                    nonce: find(nonce),
+                }
+            },
+        };
+        let pda = |seeds: &[&[u8]], bump: u8| {
+            let bump = [bump];
+            let mut seeds = seeds.to_vec();
+            seeds.push(&bump[..]);
+            Pubkey::create_program_address(&seeds, &ENDPOINT_ID).unwrap_or_default()
+        };
+        // Positions in the slice passed to endpoint_cpi::send, which starts with the
+        // endpoint program followed by the Endpoint::send accounts
+        let expected = [
+            (0, "endpoint program", ENDPOINT_ID),
+            (1, "sender", *store),
+            (3, "send library config", pda(send_library_config, bumps.send_library_config)),
+            (
+                4,
+                "default send library config",
+                pda(default_send_library_config, bumps.default_send_library_config),
+            ),
+            (6, "endpoint settings", pda(endpoint_settings, bumps.endpoint_settings)),
+            (7, "nonce", pda(nonce, bumps.nonce)),
+        ];
+        for (index, name, key) in expected {
+            let actual = accounts.get(index).map(|account| account.key());
+            if actual != Some(key) {
+                msg!("Send account {} ({}) should be {}, got {:?}", index, name, key, actual);
+                return err!(MyOAppError::InvalidSendAccounts);
+            }
+        }
+        peer.send_pda_bumps = Some(bumps);
+        Ok(())
+    }
+
+    /// Where the Endpoint::send accounts start in remaining_accounts: right after the clear
+    /// accounts, or after the send_compose accounts when a composer is configured.
+    fn send_accounts_start(store: &Store) -> usize {
+        if store.composer.is_some() {
+            Clear::MIN_ACCOUNTS_LEN + SendCompose::MIN_ACCOUNTS_LEN
+        } else {
+            Clear::MIN_ACCOUNTS_LEN
+        }
+    }
+
+    /// Split remaining_accounts into the Endpoint::clear accounts and the Endpoint::send
+    /// accounts starting at `send_start`. Too few accounts for Clear fail with a readable
+    /// error that logs both counts, instead of a panic; missing send accounts give an empty
+    /// slice, so the return is parked.
+    fn split_remaining_accounts<T>(accounts: &[T], send_start: usize) -> Result<(&[T], &[T])> {
+        require_gte!(
+            accounts.len(),
+            Clear::MIN_ACCOUNTS_LEN,
+            MyOAppError::NotEnoughRemainingAccounts
+        );
+        Ok((&accounts[..Clear::MIN_ACCOUNTS_LEN], accounts.get(send_start..).unwrap_or(&[])))
+    }
+
+    /// Record a ball lz_receive applied or returned in the BallHistory, if it exists.
+    pub(crate) fn record_ball(
+        ctx: &Context<LzReceive>,
+        eid: u32,
+        ball: [u8; 32],
+        direction: BallDirection,
+    ) -> Result<()> {
+        let entry = BallHistoryEntry { ball, eid, direction, slot: Clock::get()?.slot };
+        BallHistory::record(&ctx.accounts.ball_history, &entry)
+    }
+
+    /// The UserBall an ABA message's origin caller routes it to, or None when the message
+    /// has no origin caller or the caller has no UserBall (the Store's ball is played).
+    /// With an origin caller, `user_ball` must be its UserBall PDA, as lz_receive_types
+    /// returns it, whether or not it exists.
+    pub(crate) fn user_ball(
+        ctx: &Context<LzReceive>,
+        origin_caller: Option<[u8; 32]>,
+    ) -> Result<Option<UserBall>> {
+        let Some(user) = origin_caller else {
+            return Ok(None);
+        };
+        require_keys_eq!(
+            ctx.accounts.user_ball.key(),
+            UserBall::address(&ctx.accounts.store.key(), &user),
+            MyOAppError::InvalidUserBall
+        );
+        UserBall::load(&ctx.accounts.user_ball)
+    }
+
+    /// Write the inbound ball to a user's UserBall and emit `UserBallReceived`. The Store's
+    /// ball and last-message fields are left alone.
+    pub(crate) fn receive_user_ball(
+        ctx: &Context<LzReceive>,
+        user_ball: &mut UserBall,
+        new_ball: U256,
+        params: &LzReceiveParams,
+    ) -> Result<()> {
+        let old_ball = user_ball.ball;
+        user_ball.ball = new_ball.to_be_bytes();
+        user_ball.received_count = user_ball.received_count.wrapping_add(1);
+        user_ball.save(&ctx.accounts.user_ball)?;
+        emit!(crate::events::UserBallReceived {
+            user: user_ball.user,
+            old_ball: old_ball.to_vec(),
+            new_ball: user_ball.ball.to_vec(),
+            src_eid: params.src_eid,
+            guid: params.guid,
+            received_count: user_ball.received_count,
+        });
+        Ok(())
+    }
+
+    /// Queue the received ball for the configured composer, if any, via Endpoint::send_compose.
+    /// The compose message is `encode_ball_compose(ball, guid)`.
+    pub(crate) fn compose_ball(ctx: &Context<LzReceive>, guid: [u8; 32], ball: U256) -> Result<()> {
+        let Some(composer) = ctx.accounts.store.composer else {
+            return Ok(());
+        };
+        let compose_end = Self::send_accounts_start(&ctx.accounts.store);
+        require_gte!(
+            ctx.remaining_accounts.len(),
+            compose_end,
+            MyOAppError::NotEnoughRemainingAccounts
+        );
+        let id_seed = Store::id_seed(ctx.accounts.store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[ctx.accounts.store.bump]];
+        oapp::endpoint_cpi::send_compose(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            &ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..compose_end],
+            seeds,
+            SendComposeParams {
+                to: composer,
+                guid,
+                index: 0,
+                message: uint256_msg_codec::encode_ball_compose(&ball.to_be_bytes(), &guid),
+            },
+        )?;
+        Ok(())
+    }
 
+    /// The inbound `ball` to apply under the Store's bounds: itself when inside them,
+    /// otherwise the nearest bound (emitting `BallClamped`), or None under
+    /// `BoundPolicy::Reject` (emitting `BallOutOfBoundsDropped`), in which case the message
+    /// is cleared but not applied. lz_receive_types clamps the same way for the compose
+    /// accounts.
+    pub(crate) fn bound_ball(store: &Store, params: &LzReceiveParams, ball: U256) -> Option<U256> {
+        let bounded = store.clamp_ball(ball);
+        if bounded == ball {
+            return Some(ball);
+        }
+        if store.bound_policy == BoundPolicy::Reject {
+            emit!(crate::events::BallOutOfBoundsDropped {
+                src_eid: params.src_eid,
+                guid: params.guid,
+                received_ball: ball.to_be_bytes().to_vec(),
+            });
+            return None;
+        }
+        emit!(crate::events::BallClamped {
+            src_eid: params.src_eid,
+            guid: params.guid,
+            received_ball: ball.to_be_bytes().to_vec(),
+            applied_ball: bounded.to_be_bytes().to_vec(),
+        });
+        Some(bounded)
+    }
+
+    /// Write the inbound ball to the store and emit `BallReceived`.
+    pub(crate) fn receive_ball(
+        store: &mut Store,
+        new_ball: U256,
+        params: &LzReceiveParams,
+        memo: String,
+        origin_caller: Option<[u8; 32]>,
+        seq: u64,
+        inbound_count: u64,
+    ) -> Result<()> {
+        let old_ball = uint256_msg_codec::decode_u256(&store.ball)?;
+        store.set_ball(new_ball.to_be_bytes());
+        store.last_src_eid = params.src_eid;
+        store.last_nonce = params.nonce;
+        store.last_guid = params.guid;
+        store.last_received_at = Clock::get()?.unix_timestamp;
+
+        // Emit event tracking the ball value
+        emit!(crate::events::BallReceived {
+            old_ball: old_ball.to_be_bytes().to_vec(),
+            new_ball: new_ball.to_be_bytes().to_vec(),
+            old_ball_str: uint256_msg_codec::ball_to_string(old_ball, store.signed_ball),
+            new_ball_str: uint256_msg_codec::ball_to_string(new_ball, store.signed_ball),
+            new_ball_u64: uint256_msg_codec::ball_as_u64(&new_ball.to_be_bytes()).ok(),
+            src_eid: params.src_eid,
+            memo,
+            origin_caller,
+            seq,
+            nonce: params.nonce,
+            guid: params.guid,
+            sender: params.sender,
+            received_at: store.last_received_at,
+            inbound_count,
+            total_received: store.total_received,
+            mode: store.mode,
+            previous_touch: store.previous_touch(),
+        });
         Ok(())
     }
+
+    /// Compare a reply against the ball last sent to the peer, which the peer decrements by
+    /// `step` before answering, and return whether the message is applied. Mismatches emit
+    /// `BallMismatch`; for strict peers the message is then rejected, which leaves it cleared
+    /// but unapplied, since failing after Clear would leave it undeliverable. Nothing is
+    /// checked when no ball is outstanding (the first message) or the admin reset the ball
+    /// since it was sent. A matching reply lands the ping in flight.
+    pub(crate) fn check_consistency(
+        peer: &mut PeerConfig,
+        store: &mut Store,
+        src_eid: u32,
+        ball: U256,
+        step: U256,
+    ) -> bool {
+        let Some(last_sent) = peer.last_sent_ball.take() else {
+            return true;
+        };
+        if peer.last_sent_epoch != store.ball_epoch {
+            return true;
+        }
+        let last_sent = U256::from_be_bytes(last_sent);
+        let expected = uint256_msg_codec::decrement_ball_by(last_sent, step, store.signed_ball);
+        if ball != expected {
+            emit!(crate::events::BallMismatch {
+                src_eid,
+                expected_ball: expected.to_be_bytes().to_vec(),
+                received_ball: ball.to_be_bytes().to_vec(),
+                rejected: peer.strict_consistency,
+            });
+            return !peer.strict_consistency;
+        }
+        store.in_flight = store.in_flight.saturating_sub(1);
+        true
+    }
+
+    /// Record the peer's sequence number, emitting `SequenceGap` when it does not follow the
+    /// last one seen. Gaps and regressions are only reported; the message is still processed.
+    pub(crate) fn track_seq(peer: &mut PeerConfig, src_eid: u32, seq: u64) {
+        // Messages without a sequence number leave the tracking untouched
+        if seq == 0 {
+            return;
+        }
+        if let Some(expected_seq) = Self::seq_gap(peer.last_seen_seq, seq) {
+            emit!(crate::events::SequenceGap { src_eid, expected_seq, received_seq: seq });
+        }
+        peer.last_seen_seq = seq;
+    }
+
+    /// The sequence number expected after `last_seen_seq`, when `seq` isn't it. The first
+    /// sequenced message from a peer has nothing to compare against.
+    fn seq_gap(last_seen_seq: u64, seq: u64) -> Option<u64> {
+        let expected_seq = Store::seq_after(last_seen_seq);
+        (last_seen_seq != 0 && seq != expected_seq).then_some(expected_seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    /// Stand-ins for remaining accounts; the split only looks at their positions.
+    fn accounts(len: usize) -> Vec<usize> {
+        (0..len).collect()
+    }
+
+    #[test]
+    fn zero_accounts_are_not_enough() {
+        let err =
+            LzReceive::split_remaining_accounts(&accounts(0), Clear::MIN_ACCOUNTS_LEN).unwrap_err();
+        assert_eq!(
+            ProgramError::from(err),
+            ProgramError::from(anchor_lang::error::Error::from(
+                MyOAppError::NotEnoughRemainingAccounts
+            ))
+        );
+    }
+
+    #[test]
+    fn one_account_short_of_clear_is_not_enough() {
+        let accounts = accounts(Clear::MIN_ACCOUNTS_LEN - 1);
+        assert!(LzReceive::split_remaining_accounts(&accounts, Clear::MIN_ACCOUNTS_LEN).is_err());
+    }
+
+    #[test]
+    fn clear_accounts_only_leave_no_send_accounts() {
+        let accounts = accounts(Clear::MIN_ACCOUNTS_LEN);
+        let (clear, send) =
+            LzReceive::split_remaining_accounts(&accounts, Clear::MIN_ACCOUNTS_LEN).unwrap();
+        assert_eq!(clear, &accounts[..]);
+        // Too few for Endpoint::send, so the return is parked rather than sent
+        assert!(send.is_empty());
+        assert!(send.len() < EndpointSend::MIN_ACCOUNTS_LEN);
+    }
+
+    #[test]
+    fn send_accounts_follow_the_clear_and_compose_accounts() {
+        let send_start = Clear::MIN_ACCOUNTS_LEN + SendCompose::MIN_ACCOUNTS_LEN;
+        let accounts = accounts(send_start + EndpointSend::MIN_ACCOUNTS_LEN);
+        let (clear, send) = LzReceive::split_remaining_accounts(&accounts, send_start).unwrap();
+        assert_eq!(clear, &accounts[..Clear::MIN_ACCOUNTS_LEN]);
+        assert_eq!(send, &accounts[send_start..]);
+        // Compose accounts alone leave none for the send
+        let accounts = &accounts[..send_start];
+        let (_, send) = LzReceive::split_remaining_accounts(accounts, send_start).unwrap();
+        assert!(send.is_empty());
+    }
+
+    #[test]
+    fn first_sequenced_message_is_no_gap() {
+        assert_eq!(LzReceive::seq_gap(0, 1), None);
+        // Nor is a peer's first message after this program was redeployed mid-stream
+        assert_eq!(LzReceive::seq_gap(0, 500), None);
+    }
+
+    #[test]
+    fn sequence_gaps_and_regressions_are_reported() {
+        assert_eq!(LzReceive::seq_gap(5, 6), None);
+        assert_eq!(LzReceive::seq_gap(5, 8), Some(6));
+        assert_eq!(LzReceive::seq_gap(5, 5), Some(6));
+        assert_eq!(LzReceive::seq_gap(5, 2), Some(6));
+    }
+
+    #[test]
+    fn sequence_wraps_past_u64_max_to_one() {
+        assert_eq!(LzReceive::seq_gap(u64::MAX, 1), None);
+        assert_eq!(LzReceive::seq_gap(u64::MAX - 1, u64::MAX), None);
+    }
+
+    /// A Store bounded to 10..=100 under `bound_policy`.
+    fn bounded_store(bound_policy: BoundPolicy) -> Store {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        store.min_ball = U256::from(10u128).to_be_bytes();
+        store.max_ball = U256::from(100u128).to_be_bytes();
+        store.bound_policy = bound_policy;
+        store
+    }
+
+    fn params() -> LzReceiveParams {
+        LzReceiveParams {
+            src_eid: 30101,
+            sender: [1; 32],
+            nonce: 1,
+            guid: [2; 32],
+            message: Vec::new(),
+            extra_data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ball_above_max_is_clamped() {
+        let store = bounded_store(BoundPolicy::Clamp);
+        let ball = LzReceive::bound_ball(&store, &params(), U256::from(500u128)).unwrap();
+        assert_eq!(ball, U256::from(100u128));
+        let ball = LzReceive::bound_ball(&store, &params(), U256::from(5u128)).unwrap();
+        assert_eq!(ball, U256::from(10u128));
+    }
+
+    #[test]
+    fn ball_above_max_is_rejected() {
+        let store = bounded_store(BoundPolicy::Reject);
+        assert!(LzReceive::bound_ball(&store, &params(), U256::from(500u128)).is_none());
+        assert!(LzReceive::bound_ball(&store, &params(), U256::from(5u128)).is_none());
+    }
+
+    #[test]
+    fn balls_within_or_without_bounds_are_applied_as_is() {
+        let store = bounded_store(BoundPolicy::Reject);
+        for ball in [10u128, 55, 100] {
+            let bounded = LzReceive::bound_ball(&store, &params(), U256::from(ball)).unwrap();
+            assert_eq!(bounded, U256::from(ball));
+        }
+        // All-zero and all-ones bounds mean unbounded
+        let mut store = store;
+        store.min_ball = [0; 32];
+        store.max_ball = [0xff; 32];
+        let ball = LzReceive::bound_ball(&store, &params(), U256::from(500u128)).unwrap();
+        assert_eq!(ball, U256::from(500u128));
+    }
+
+    #[test]
+    fn matching_answer_lands_the_ping_in_flight() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let mut peer = PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap();
+        store.start_flight().unwrap();
+        store.start_flight().unwrap();
+
+        // The peer answers 100 with 99
+        peer.last_sent_ball = Some(U256::from(100u128).to_be_bytes());
+        let answer = U256::from(99u128);
+        assert!(LzReceive::check_consistency(&mut peer, &mut store, 30101, answer, U256::ONE));
+        assert_eq!(store.in_flight, 1);
+
+        // A mismatch, or an answer with nothing outstanding, leaves the count alone
+        peer.last_sent_ball = Some(U256::from(100u128).to_be_bytes());
+        let wrong = answer - U256::ONE;
+        assert!(LzReceive::check_consistency(&mut peer, &mut store, 30101, wrong, U256::ONE));
+        assert!(LzReceive::check_consistency(&mut peer, &mut store, 30101, answer, U256::ONE));
+        assert_eq!(store.in_flight, 1);
+    }
+
+    #[test]
+    fn strict_peer_mismatch_is_rejected_without_failing() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let mut peer = PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap();
+        peer.strict_consistency = true;
+        peer.last_sent_ball = Some(U256::from(100u128).to_be_bytes());
+        let wrong = U256::from(90u128);
+        assert!(!LzReceive::check_consistency(&mut peer, &mut store, 30101, wrong, U256::ONE));
+        // The outstanding ball is used up, so the next reply isn't checked against it
+        assert!(LzReceive::check_consistency(&mut peer, &mut store, 30101, wrong, U256::ONE));
+    }
 }
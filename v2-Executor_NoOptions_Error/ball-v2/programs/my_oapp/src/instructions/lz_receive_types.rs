@@ -1,5 +1,6 @@
-use crate::{consts::*, *};
-use oapp::endpoint_cpi::{get_accounts_for_clear, LzAccount};
+use crate::{consts::*, uint256_msg_codec::DecodedMessage, *};
+use ethnum::U256;
+use oapp::endpoint_cpi::{get_accounts_for_clear, get_accounts_for_send_compose, LzAccount};
 use oapp::endpoint::ID as ENDPOINT_ID;
 use oapp::LzReceiveParams;
 
@@ -12,11 +13,15 @@ use oapp::LzReceiveParams;
 /// cross-program invocation will fail.
 ///
 /// For ABA pattern, accounts needed for Endpoint::send CPI (including endpoint)
-/// are provided as additional remaining_accounts after the clear accounts.
+/// are appended after the clear accounts, from the list cached in `ReturnAccounts`.
 #[derive(Accounts)]
 pub struct LzReceiveTypes<'info> {
-    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
     pub store: Account<'info, Store>,
+    /// CHECK: ReturnAccounts PDA. Only read when it exists, since it is created by the first
+    /// `set_return_accounts` call.
+    #[account(seeds = [RETURN_ACCOUNTS_SEED, &store.key().to_bytes()], bump)]
+    pub return_accounts: UncheckedAccount<'info>,
 }
 
 impl LzReceiveTypes<'_> {
@@ -33,12 +38,51 @@ impl LzReceiveTypes<'_> {
         let peer_seeds = [PEER_SEED, &store.to_bytes(), &params.src_eid.to_be_bytes()];
         let (peer, _) = Pubkey::find_program_address(&peer_seeds, ctx.program_id);
 
+        // 3. The PendingReturn PDA for this message, in case the return leg has to be parked.
+        let pending_seeds = [PENDING_RETURN_SEED, &store.to_bytes(), &params.guid];
+        let (pending_return, _) = Pubkey::find_program_address(&pending_seeds, ctx.program_id);
+
+        // 4. The Receipt PDA for this message, written when `Store.write_receipts` is set.
+        let receipt_seeds = [RECEIPT_SEED, &store.to_bytes(), &params.guid];
+        let (receipt, _) = Pubkey::find_program_address(&receipt_seeds, ctx.program_id);
+
+        // 5. The FeeQuoteCache PDA for the source eid, read (when it exists) to price the
+        // return leg.
+        let cache_seeds = [FEE_QUOTE_CACHE_SEED, &store.to_bytes(), &params.src_eid.to_be_bytes()];
+        let (fee_quote_cache, _) = Pubkey::find_program_address(&cache_seeds, ctx.program_id);
+
+        // 6. The BallHistory PDA, written (when it exists) with every ball applied or returned.
+        let history_seeds = [BALL_HISTORY_SEED, &store.to_bytes()];
+        let (ball_history, _) = Pubkey::find_program_address(&history_seeds, ctx.program_id);
+
+        // 7. The UserBall PDA of the message's origin caller, written (when it exists) in
+        // place of the Store's ball. Messages without one get the PDA of the all-zero key,
+        // which can never be created.
+        let user = Self::decode(&params.message)
+            .and_then(|aba_msg| aba_msg.known_origin_caller())
+            .unwrap_or_default();
+        let user_ball = UserBall::address(&store, &user);
+
         // Accounts used directly by `lz_receive`
         let mut accounts = vec![
             // store (mutable)
             LzAccount { pubkey: store, is_signer: false, is_writable: true },
-            // peer (read-only)
-            LzAccount { pubkey: peer, is_signer: false, is_writable: false },
+            // peer (mutable, tracks the last sequence number seen)
+            LzAccount { pubkey: peer, is_signer: false, is_writable: true },
+            // payer for a parked return (signer placeholder the Executor fills in with itself)
+            LzAccount { pubkey: Pubkey::default(), is_signer: true, is_writable: true },
+            // pending_return (only created when the return leg has to be parked)
+            LzAccount { pubkey: pending_return, is_signer: false, is_writable: true },
+            // receipt (only created while receipts are enabled)
+            LzAccount { pubkey: receipt, is_signer: false, is_writable: true },
+            // fee_quote_cache (only read, when `refresh_quote` has created it)
+            LzAccount { pubkey: fee_quote_cache, is_signer: false, is_writable: false },
+            // ball_history (only written, when `init_history` has created it)
+            LzAccount { pubkey: ball_history, is_signer: false, is_writable: true },
+            // user_ball (only written, when `init_user_ball` has created it)
+            LzAccount { pubkey: user_ball, is_signer: false, is_writable: true },
+            // system_program
+            LzAccount { pubkey: System::id(), is_signer: false, is_writable: false },
         ];
 
         // Append the additional accounts required for `Endpoint::clear`
@@ -51,11 +95,166 @@ impl LzReceiveTypes<'_> {
         );
         accounts.extend(accounts_for_clear);
 
-        // Note: For ABA pattern, accounts needed for Endpoint::send CPI should be provided
-        // as additional remaining_accounts after the clear accounts. These accounts depend
-        // on the message library and destination chain configuration, and are typically
-        // fetched off-chain using the endpoint SDK's getSendIXAccountMetaForCPI method.
+        // Append the accounts for `Endpoint::send_compose` when a composer is configured.
+        // They are always present in that case, since `lz_receive` locates the send accounts
+        // behind them; only the PDA for the composed message depends on the ball.
+        if let Some(composer) = ctx.accounts.store.composer {
+            let ball = Self::decode(&params.message)
+                .map(|aba_msg| aba_msg.ball)
+                .or_else(|| Self::last_batch_ball(&params.message))
+                .unwrap_or_default();
+            // lz_receive composes the ball it applies, after the Store's bounds
+            let ball = ctx.accounts.store.clamp_ball(U256::from_be_bytes(ball)).to_be_bytes();
+            accounts.extend(get_accounts_for_send_compose(
+                ENDPOINT_ID,
+                &store,
+                &composer,
+                &params.guid,
+                0,
+                &uint256_msg_codec::encode_ball_compose(&ball, &params.guid),
+            ));
+        }
+
+        // 8. For ABA pattern, the accounts needed for the return Endpoint::send CPI follow
+        // the clear accounts; `lz_receive` slices them from
+        // remaining_accounts[Clear::MIN_ACCOUNTS_LEN..].
+        // They depend on the message library and destination chain configuration, so the
+        // admin caches them per peer with `set_return_accounts` (from the endpoint SDK's
+        // getSendIXAccountMetaForCPI method).
+        // They are left out for messages that never trigger a return: vanilla and batch
+        // messages, GAME_OVER messages, one-way ABA messages, (unsigned) balls that are
+        // already zero and counters outside counter mode. When they
+        // are missing for a message that does need a return, the return is parked as a
+        // PendingReturn. In split-return mode (`Store.split_return`) they are never needed
+        // here; they are passed to `dispatch_return` instead, which can follow in the same
+        // transaction.
+        if Self::needs_return(&ctx.accounts.store, &params.message) {
+            if let Some(send_accounts) =
+                Self::cached_send_accounts(&ctx.accounts.return_accounts, params.src_eid)?
+            {
+                accounts.extend(send_accounts);
+            }
+        }
 
         Ok(accounts)
     }
+
+    /// Whether `lz_receive` will try to send a return leg for this message. Messages that
+    /// fail to decode are rejected by `lz_receive` anyway.
+    fn needs_return(store: &Store, message: &[u8]) -> bool {
+        !store.split_return && Self::may_return(store, message)
+    }
+
+    /// Whether `lz_receive` answers this message with a return leg, sent or parked.
+    pub(crate) fn may_return(store: &Store, message: &[u8]) -> bool {
+        let Some(aba_msg) = Self::decode(message) else {
+            return false;
+        };
+        match aba_msg.msg_type {
+            uint256_msg_codec::ABA_TYPE => {
+                (store.signed_ball || store.clamp_ball(aba_msg.ball_u256()) != U256::ZERO)
+                    && aba_msg.no_return() == Ok(false)
+            }
+            // Every counter is answered while counter mode is on
+            uint256_msg_codec::COUNTER_TYPE => store.mode == GameMode::Counter,
+            _ => false,
+        }
+    }
+
+    /// Decode a message like `lz_receive` does, without the peer config. Vanilla messages
+    /// come back with `VANILLA_TYPE` and counters with `COUNTER_TYPE`; batches and
+    /// undecodable messages as `None`.
+    fn decode(message: &[u8]) -> Option<uint256_msg_codec::AbaMessageRef<'_>> {
+        let (_, message) = uint256_msg_codec::split_tag(message);
+        // The peer config isn't available here, so fall back to the compact layout when the
+        // full one doesn't decode
+        match uint256_msg_codec::decode_any(message) {
+            Ok(DecodedMessage::Aba(aba_msg)) => Some(aba_msg),
+            Ok(DecodedMessage::Vanilla(ball)) => Some(uint256_msg_codec::AbaMessageRef {
+                ball,
+                msg_type: uint256_msg_codec::VANILLA_TYPE,
+                ..Default::default()
+            }),
+            Ok(DecodedMessage::Counter(ball)) => Some(uint256_msg_codec::AbaMessageRef {
+                ball,
+                msg_type: uint256_msg_codec::COUNTER_TYPE,
+                ..Default::default()
+            }),
+            Ok(DecodedMessage::Batch(_)) => None,
+            Err(_) => uint256_msg_codec::decode_aba_compact_ref(message).ok(),
+        }
+    }
+
+    /// The value a batch message leaves in the ball (its last entry).
+    fn last_batch_ball(message: &[u8]) -> Option<[u8; 32]> {
+        let (_, message) = uint256_msg_codec::split_tag(message);
+        match uint256_msg_codec::decode_any(message) {
+            Ok(DecodedMessage::Batch(values)) => values.last().copied(),
+            _ => None,
+        }
+    }
+
+    /// The send accounts cached for `eid`, if the ReturnAccounts PDA exists and has them.
+    fn cached_send_accounts(
+        return_accounts: &UncheckedAccount,
+        eid: u32,
+    ) -> Result<Option<Vec<LzAccount>>> {
+        if return_accounts.owner != &crate::ID || return_accounts.data_is_empty() {
+            return Ok(None);
+        }
+        let data = return_accounts.try_borrow_data()?;
+        let cache = ReturnAccounts::try_deserialize(&mut &data[..])?;
+        Ok(cache.get(eid).map(<[LzAccount]>::to_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uint256_msg_codec::{AbaMessage, Extension};
+
+    fn store() -> Store {
+        Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0)
+    }
+
+    fn ping(extensions: Vec<Extension>) -> Vec<u8> {
+        let message = AbaMessage {
+            ball: U256::from(5u128).to_be_bytes(),
+            msg_type: uint256_msg_codec::ABA_TYPE,
+            extensions,
+            ..Default::default()
+        };
+        message.encode().unwrap()
+    }
+
+    #[test]
+    fn one_way_pings_need_no_send_accounts() {
+        let store = store();
+        let one_way = ping(vec![Extension::no_return()]);
+        assert!(!LzReceiveTypes::may_return(&store, &one_way));
+        assert!(!LzReceiveTypes::needs_return(&store, &one_way));
+    }
+
+    #[test]
+    fn pings_without_the_flag_need_send_accounts() {
+        let store = store();
+        let ttl_only = ping(vec![Extension::ttl(1, 60)]);
+        assert!(LzReceiveTypes::may_return(&store, &ttl_only));
+        assert!(LzReceiveTypes::needs_return(&store, &ttl_only));
+
+        // Legacy pings have no extension area and are always answered
+        let legacy = uint256_msg_codec::encode_aba(
+            &U256::from(5u128).to_be_bytes(),
+            uint256_msg_codec::ABA_TYPE,
+            &[],
+        )
+        .unwrap();
+        assert!(LzReceiveTypes::needs_return(&store, &legacy));
+
+        // With split returns the send accounts are left to `send_return`
+        let mut split = store;
+        split.split_return = true;
+        assert!(LzReceiveTypes::may_return(&split, &legacy));
+        assert!(!LzReceiveTypes::needs_return(&split, &legacy));
+    }
 }
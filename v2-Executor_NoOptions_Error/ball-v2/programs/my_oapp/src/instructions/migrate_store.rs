@@ -0,0 +1,59 @@
+use crate::*;
+use anchor_lang::prelude::*;
+
+// Grows a Store created by an older build to the current `Store::SIZE` and upgrades it to
+// `Store::VERSION`. New fields are appended at the end of `Store` and the added bytes are
+// zero-filled; `Store::upgrade` then sets the ones whose starting value isn't zero. Safe to
+// call more than once.
+
+#[derive(Accounts)]
+pub struct MigrateStore<'info> {
+    #[account(mut)]
+    /// Admin of the OApp store; pays for the extra rent
+    pub admin: Signer<'info>,
+    /// CHECK: An older Store may not deserialize as the current one, so it is handled as
+    /// raw bytes. The owner is checked here and the discriminator and admin in `apply`; only
+    /// `init_store` creates program-owned accounts with the Store discriminator, whatever
+    /// their store id, so no seeds are needed.
+    #[account(mut, owner = crate::ID)]
+    pub store: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl MigrateStore<'_> {
+    pub fn apply(ctx: &mut Context<MigrateStore>) -> Result<()> {
+        let store = ctx.accounts.store.to_account_info();
+        Self::check_admin(&store, &ctx.accounts.admin.key())?;
+        if store.data_len() < Store::SIZE {
+            grow_account(&ctx.accounts.admin, &ctx.accounts.system_program, &store, Store::SIZE)?;
+        }
+
+        // The Store now deserializes with the current layout
+        let mut data = store.try_borrow_mut_data()?;
+        let mut state = Store::try_deserialize(&mut &data[..])?;
+        if state.version < Store::VERSION {
+            msg!("Upgrading Store from version {} to {}", state.version, Store::VERSION);
+            state.upgrade();
+        }
+        // Older builds left the baseline at zero, or short of the last fee paid, which made
+        // the Store's spare balance look like a native drop; measure from the balance now
+        state.fee_baseline = store.lamports();
+        state.try_serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    /// Check that the raw Store account is a Store whose admin is `admin`. Also used by
+    /// `extend_store`.
+    pub(crate) fn check_admin(store: &AccountInfo, admin: &Pubkey) -> Result<()> {
+        // `admin` is the first field after the discriminator in every Store version
+        let data = store.try_borrow_data()?;
+        require!(data.len() >= 8 + 32, ErrorCode::AccountDidNotDeserialize);
+        require!(data[..8] == *Store::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        require_keys_eq!(
+            Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+            *admin,
+            ErrorCode::ConstraintAddress
+        );
+        Ok(())
+    }
+}
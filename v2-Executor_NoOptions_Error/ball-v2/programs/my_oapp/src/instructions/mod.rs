@@ -4,6 +4,33 @@ pub mod lz_receive;
 pub mod lz_receive_types;
 pub mod quote_send;
 pub mod set_peer_config;
+pub mod set_store_config;
+pub mod retry_return;
+pub mod dispatch_return;
+pub mod set_return_accounts;
+pub mod migrate_store;
+pub mod close_receipt;
+pub mod send_with_value;
+pub mod send_multi;
+pub mod set_send_allowlist;
+pub mod send_raw;
+pub mod prepare_send;
+pub mod set_allowed_receivers;
+pub mod refresh_quote;
+pub mod preview_send;
+pub mod ping_many;
+pub mod send_ping;
+pub mod get_stats;
+pub mod init_history;
+pub mod transfer_admin;
+pub mod pause_peer;
+pub mod extend_store;
+pub mod init_user_ball;
+pub mod send_user_ping;
+pub mod set_metadata;
+pub mod reset_in_flight;
+pub mod drain_pending_return;
+pub mod set_ball;
 
 
 pub use send::*;
@@ -12,3 +39,30 @@ pub use lz_receive::*;
 pub use lz_receive_types::*;
 pub use quote_send::*;
 pub use set_peer_config::*;
+pub use set_store_config::*;
+pub use retry_return::*;
+pub use dispatch_return::*;
+pub use set_return_accounts::*;
+pub use migrate_store::*;
+pub use close_receipt::*;
+pub use send_with_value::*;
+pub use send_multi::*;
+pub use set_send_allowlist::*;
+pub use send_raw::*;
+pub use prepare_send::*;
+pub use set_allowed_receivers::*;
+pub use refresh_quote::*;
+pub use preview_send::*;
+pub use ping_many::*;
+pub use send_ping::*;
+pub use get_stats::*;
+pub use init_history::*;
+pub use transfer_admin::*;
+pub use pause_peer::*;
+pub use extend_store::*;
+pub use init_user_ball::*;
+pub use send_user_ping::*;
+pub use set_metadata::*;
+pub use reset_in_flight::*;
+pub use drain_pending_return::*;
+pub use set_ball::*;
@@ -0,0 +1,42 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+
+// Emergency brake for the admin or the guardian: stops lz_receive from accepting a peer's
+// messages, which stay retriable. Only the admin can unpause, through
+// `PeerConfigParam::PausedInbound(false)`.
+
+#[derive(Accounts)]
+#[instruction(params: PausePeerParams)]
+pub struct PausePeer<'info> {
+    /// The Store's admin or guardian
+    pub authority: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PausePeerParams {
+    pub remote_eid: u32,
+}
+
+impl PausePeer<'_> {
+    pub fn apply(ctx: &mut Context<PausePeer>, params: &PausePeerParams) -> Result<()> {
+        let store = &ctx.accounts.store;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == store.admin
+                || (store.guardian != Pubkey::default() && authority == store.guardian),
+            MyOAppError::NotAdminOrGuardian
+        );
+        ctx.accounts.peer.paused_inbound = true;
+        emit!(crate::events::PeerPaused { remote_eid: params.remote_eid, paused_by: authority });
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID, MessagingReceipt,
+};
+
+// Sends `count` consecutive pings to one peer in a single transaction, for load-testing the
+// DVNs and the Executor. Every ping goes through `send_ball` with the same remaining
+// accounts: the Endpoint takes each send as its own message, handing out the next outbound
+// nonce (the nonce PDA is writable and updated in place) and a new guid, so repeating the
+// CPI within one transaction is fine. A peer with a send cooldown only takes one ping per
+// interval, so the second ping fails the instruction unless `min_send_interval_slots` is 0.
+
+#[derive(Accounts)]
+#[instruction(params: PingManyParams)]
+pub struct PingMany<'info> {
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instructions and hands out sequence numbers
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    /// Signer checked against the Store's send permission
+    pub sender: Signer<'info>,
+    #[account(seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()], bump = send_allowlist.bump)]
+    /// Signers allowed to send; only needed under SendPermission::Allowlist
+    pub send_allowlist: Option<Account<'info, SendAllowlist>>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PingManyParams {
+    pub dst_eid: u32,
+    pub count: u8, // Pings to send, 1 to MAX_PING_MANY
+    pub return_options: Vec<u8>, // Options for each return message (B→A)
+    pub options: Vec<u8>, // Additional options for each ping (A→B)
+    // Fee for each ping; AUTO_NATIVE_FEE quotes each one, with the first
+    // `quote_accounts_len` remaining accounts as the Endpoint::quote accounts
+    pub native_fee: u64,
+    pub quote_accounts_len: u16,
+    pub max_total_native_fee: u64, // Native fee all pings together may cost
+}
+
+impl PingMany<'_> {
+    /// Returns the Endpoint's receipt for each ping, in send order.
+    pub fn apply(
+        ctx: &mut Context<PingMany>,
+        params: &PingManyParams,
+    ) -> Result<Vec<MessagingReceipt>> {
+        require!(params.count != 0 && params.count <= MAX_PING_MANY, MyOAppError::TooManyPings);
+        let store = &ctx.accounts.store;
+        let allowlist = ctx.accounts.send_allowlist.as_deref();
+        store.require_send_permitted(ctx.accounts.sender.key, allowlist)?;
+        require!(store.mode == GameMode::Counter || !store.game_over, MyOAppError::GameOver);
+
+        // Each ping carries the value after the previous one, as if the peer had answered in
+        // between. A fee budget running out fails the instruction, reverting every ping.
+        let mut ball = uint256_msg_codec::decode_u256(&store.ball)?;
+        let mut spent: u64 = 0;
+        let mut receipts = Vec::with_capacity(params.count as usize);
+        for _ in 0..params.count {
+            let (current, new_ball, step) = Send::next_ball_after(&ctx.accounts.store, ball, None)?;
+            let remaining = params.max_total_native_fee.saturating_sub(spent);
+            if params.native_fee != AUTO_NATIVE_FEE {
+                require_gte!(remaining, params.native_fee, MyOAppError::FeeBudgetExceeded);
+            }
+            let send_params = SendMessageParams {
+                dst_eid: params.dst_eid,
+                return_options: params.return_options.clone(),
+                options: params.options.clone(),
+                native_fee: params.native_fee,
+                lz_token_fee: 0,
+                memo: None,
+                step: None,
+                ttl: 0,
+                quote_accounts_len: params.quote_accounts_len,
+                // A quoted fee above what is left of the budget fails before it is paid
+                max_fee: Some(remaining),
+                client_order_id: 0,
+                receiver_override: None,
+                option_items: None,
+            };
+            let receipt = Send::send_ball(
+                &mut ctx.accounts.store,
+                &mut ctx.accounts.peer,
+                ctx.remaining_accounts,
+                &send_params,
+                current,
+                new_ball,
+                step,
+            )?;
+            spent = spent.saturating_add(receipt.fee.native_fee);
+            require_gte!(params.max_total_native_fee, spent, MyOAppError::FeeBudgetExceeded);
+            receipts.push(receipt);
+            ball = new_ball;
+        }
+
+        // Only one ball can be outstanding per peer, and the replies to all but the last
+        // ping wouldn't match it, so none is checked
+        ctx.accounts.peer.last_sent_ball = None;
+        Ok(receipts)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    state::SendLibraryConfig, ENDPOINT_SEED, ID as ENDPOINT_ID, MESSAGE_LIB_SEED, NONCE_SEED,
+    SEND_LIBRARY_CONFIG_SEED,
+};
+use oapp::endpoint_cpi::LzAccount;
+
+// Seed of the Endpoint's Anchor event-CPI authority
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// `prepare_send` derives the Endpoint::send accounts a `send` to `dst_eid` needs, so clients
+/// don't have to maintain the list by hand. They are returned in the order `send` expects
+/// in remaining_accounts (without a leading quote list for AUTO_NATIVE_FEE):
+/// endpoint program, sender (Store), send library program, send library config, default
+/// send library config, send library info, endpoint settings, nonce, event authority and
+/// the endpoint program again.
+///
+/// The send library's own accounts (for the ULN: its settings, executor and DVN configs,
+/// price feed, treasury...) depend on that library's on-chain configuration and must still
+/// be appended, e.g. from the endpoint SDK.
+#[derive(Accounts)]
+#[instruction(params: PrepareSendParams)]
+pub struct PrepareSend<'info> {
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    /// Configuration for the destination chain; its address is part of the nonce seeds
+    pub peer: Account<'info, PeerConfig>,
+    #[account(
+        seeds = [SEND_LIBRARY_CONFIG_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = send_library_config.bump,
+        seeds::program = ENDPOINT_ID
+    )]
+    pub send_library_config: Account<'info, SendLibraryConfig>,
+    #[account(
+        seeds = [SEND_LIBRARY_CONFIG_SEED, &params.dst_eid.to_be_bytes()],
+        bump = default_send_library_config.bump,
+        seeds::program = ENDPOINT_ID
+    )]
+    pub default_send_library_config: Account<'info, SendLibraryConfig>,
+    /// CHECK: the send library the Store uses for `dst_eid` (its own or the default one),
+    /// checked in `apply`; its owner is the send library program
+    pub send_library: UncheckedAccount<'info>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PrepareSendParams {
+    pub dst_eid: u32,
+}
+
+impl PrepareSend<'_> {
+    pub fn apply(ctx: &Context<PrepareSend>, params: &PrepareSendParams) -> Result<Vec<LzAccount>> {
+        let store = ctx.accounts.store.key();
+        let eid = params.dst_eid.to_be_bytes();
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &ENDPOINT_ID).0;
+
+        // The Store's own library unless it is unset, as the Endpoint resolves it
+        let message_lib = if ctx.accounts.send_library_config.message_lib == Pubkey::default() {
+            ctx.accounts.default_send_library_config.message_lib
+        } else {
+            ctx.accounts.send_library_config.message_lib
+        };
+        require_keys_eq!(
+            ctx.accounts.send_library.key(),
+            message_lib,
+            MyOAppError::InvalidSendAccounts
+        );
+
+        let receiver = ctx.accounts.peer.peer_address;
+        let account = |pubkey: Pubkey, is_writable: bool| LzAccount {
+            pubkey,
+            is_signer: false,
+            is_writable,
+        };
+        Ok(vec![
+            account(ENDPOINT_ID, false),
+            // sender; the Store signs the CPI itself
+            account(store, false),
+            account(*ctx.accounts.send_library.owner, false),
+            account(ctx.accounts.send_library_config.key(), false),
+            account(ctx.accounts.default_send_library_config.key(), false),
+            account(pda(&[MESSAGE_LIB_SEED, message_lib.as_ref()]), false),
+            account(pda(&[ENDPOINT_SEED]), false),
+            account(pda(&[NONCE_SEED, store.as_ref(), &eid, &receiver[..]]), true),
+            account(pda(&[EVENT_AUTHORITY_SEED]), false),
+            account(ENDPOINT_ID, false),
+        ])
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+
+// Read-only dry run of `send`: the message and options it would hand to Endpoint::send,
+// without the CPI. Meant to be simulated when debugging encoding against the EVM side.
+
+#[derive(Accounts)]
+#[instruction(params: SendMessageParams)]
+pub struct PreviewSend<'info> {
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+}
+
+/// What `send` would put on the wire, as returned by `preview_send`.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendPreview {
+    pub message: Vec<u8>,
+    pub options: Vec<u8>,
+    pub message_len: u32,
+    pub options_len: u32,
+}
+
+impl PreviewSend<'_> {
+    pub fn apply(ctx: &Context<PreviewSend>, params: &SendMessageParams) -> Result<SendPreview> {
+        // The same ball math, sequence number and encoding as Send::apply and send_ball; only
+        // a TTL's origin timestamp can differ from a later send. Permissions, cooldowns and
+        // client order ids are left to the real send.
+        let store = &ctx.accounts.store;
+        let peer = &ctx.accounts.peer;
+        require!(store.mode == GameMode::Counter || !store.game_over, MyOAppError::GameOver);
+        let (_, new_ball, step) = Send::next_ball(store, params.step)?;
+        let seq = if Send::uses_seq(store, peer) { store.peek_seq() } else { 0 };
+        let message = Send::encode_message(store, peer, params, new_ball, step, seq)?;
+        let options = Send::send_options(store, peer, params)?;
+        Ok(SendPreview {
+            message_len: message.len() as u32,
+            options_len: options.len() as u32,
+            message,
+            options,
+        })
+    }
+}
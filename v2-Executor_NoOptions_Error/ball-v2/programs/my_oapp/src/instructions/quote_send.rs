@@ -8,7 +8,7 @@ use oapp::endpoint::{
 #[derive(Accounts)]
 #[instruction(params: QuoteSendParams)]
 pub struct QuoteSend<'info> {
-    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
     pub store: Account<'info, Store>,
     #[account(
     seeds = [
@@ -30,31 +30,289 @@ pub struct QuoteSendParams {
     pub return_options: Vec<u8>, // Options for the return message (B→A)
     pub options: Vec<u8>, // Additional options for the initial send (A→B)
     pub pay_in_lz_token: bool,
+    // The same as in SendMessageParams, so the quoted message matches the sent one
+    pub memo: Option<String>,
+    pub step: Option<[u8; 32]>,
+    pub ttl: u64,
+    pub quote_mode: QuoteMode, // Which options the outbound quote uses
+}
+
+/// Options an outbound quote is priced with.
+#[derive(Clone, Default, AnchorSerialize, AnchorDeserialize)]
+pub enum QuoteMode {
+    /// The peer's enforced options combined with `options`, exactly as `send` does; just
+    /// `options` while the peer has no enforced options
+    #[default]
+    Combined,
+    /// Only `options`, ignoring the enforced ones
+    RawOnly,
+    /// These options in place of the enforced ones, combined with `options`, to price a
+    /// configuration before the admin sets it
+    Hypothetical(Vec<u8>),
+}
+
+/// Both legs of an ABA round trip and their sum, as returned by `quote_send_full`.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RoundTripFee {
+    pub outbound: MessagingFee,
+    pub return_leg: MessagingFee,
+    pub total: MessagingFee,
+}
+
+/// The fee in both payment modes, as returned by `quote_send_both`. `lz_token` is `None`
+/// when the Endpoint has no LZ token configured.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PaymentModeFees {
+    pub native: MessagingFee,
+    pub lz_token: Option<MessagingFee>,
 }
 
 impl<'info> QuoteSend<'info> {
     pub fn apply(ctx: &Context<QuoteSend>, params: &QuoteSendParams) -> Result<MessagingFee> {
-        // Encode ABA message for quoting
-        let ball = ctx.accounts.store.ball;
-        let ball_ethnum = U256::from_be_bytes(ball);
-        let new_ball = ball_ethnum.saturating_sub(U256::ONE).to_be_bytes();
-        
-        // Encode ABA message with return options
-        let message = uint256_msg_codec::encode_aba(&new_ball, &params.return_options);
-
-        // Ask the Endpoint how much a send would cost
+        let (message, ..) =
+            Self::outbound_message(&ctx.accounts.store, &ctx.accounts.peer, params)?;
+        let options = Self::options(ctx, params)?;
+        Self::quote(ctx, params, message, options)
+    }
+
+    /// Quote the same message paying in native only and paying in LZ token; `pay_in_lz_token`
+    /// in `params` is ignored.
+    pub fn apply_both(
+        ctx: &Context<QuoteSend>,
+        params: &QuoteSendParams,
+    ) -> Result<PaymentModeFees> {
+        let (message, ..) =
+            Self::outbound_message(&ctx.accounts.store, &ctx.accounts.peer, params)?;
+        let options = Self::options(ctx, params)?;
+        let native_params = QuoteSendParams { pay_in_lz_token: false, ..params.clone() };
+        let native = Self::quote(ctx, &native_params, message.clone(), options.clone())?;
+        // A failed CPI aborts the whole instruction, so check the Endpoint up front instead
+        // of letting the LZ token quote fail
+        let lz_token = if ctx.accounts.endpoint.lz_token_mint.is_some() {
+            let lz_token_params = QuoteSendParams { pay_in_lz_token: true, ..params.clone() };
+            Some(Self::quote(ctx, &lz_token_params, message, options)?)
+        } else {
+            None
+        };
+        Ok(PaymentModeFees { native, lz_token })
+    }
+
+    /// Quote the outbound ping and the return this store sends back to `dst_eid` when it
+    /// serves one. Both legs share sender, destination and receiver, so the same quote
+    /// accounts in `remaining_accounts` are used for each CPI.
+    pub fn apply_full(ctx: &Context<QuoteSend>, params: &QuoteSendParams) -> Result<RoundTripFee> {
+        let store = &ctx.accounts.store;
+        let peer = &ctx.accounts.peer;
+        let (message, new_ball, step, seq) = Self::outbound_message(store, peer, params)?;
+        let options = Self::options(ctx, params)?;
+        let outbound = Self::quote(ctx, params, message, options)?;
+
+        // Mirror the return lz_receive would build for that ping. The origin guid is not
+        // known yet, but it is a fixed-size word, so the quoted length is the same.
+        let message = if store.mode == GameMode::Counter {
+            uint256_msg_codec::encode_counter(&uint256_msg_codec::increment_counter(
+                &new_ball.to_be_bytes(),
+            ))
+        } else {
+            let return_ball = store
+                .underflow_policy
+                .decrement(new_ball, U256::from_be_bytes(step), store.signed_ball)?
+                .to_be_bytes();
+            let return_type = if !store.signed_ball && return_ball == [0u8; 32] {
+                uint256_msg_codec::GAME_OVER_TYPE
+            } else {
+                uint256_msg_codec::PONG_TYPE
+            };
+            if peer.compact {
+                uint256_msg_codec::encode_aba_compact(
+                    uint256_msg_codec::ball_as_u128(&return_ball)?,
+                    return_type,
+                    &[],
+                )?
+            } else {
+                uint256_msg_codec::AbaMessage {
+                    ball: return_ball,
+                    msg_type: return_type,
+                    hop_count: 1,
+                    seq: seq.wrapping_add(1),
+                    step,
+                    ..Default::default()
+                }
+                .encode()?
+            }
+        };
+        let message = uint256_msg_codec::tag_if(message, store.require_magic);
+        let options = crate::handlers::return_options(peer, &[])?;
+        let return_leg = Self::quote(ctx, params, message, options)?;
+
+        let total = MessagingFee {
+            native_fee: outbound.native_fee.saturating_add(return_leg.native_fee),
+            lz_token_fee: outbound.lz_token_fee.saturating_add(return_leg.lz_token_fee),
+        };
+        Ok(RoundTripFee { outbound, return_leg, total })
+    }
+
+    /// Build exactly the message `send` would for the same params, including the sequence
+    /// number it would take, so the quote covers the bytes actually sent.
+    fn outbound_message(
+        store: &Store,
+        peer: &PeerConfig,
+        params: &QuoteSendParams,
+    ) -> Result<(Vec<u8>, U256, [u8; 32], u64)> {
+        let send_params = SendMessageParams {
+            dst_eid: params.dst_eid,
+            return_options: params.return_options.clone(),
+            options: params.options.clone(),
+            native_fee: 0,
+            lz_token_fee: 0,
+            memo: params.memo.clone(),
+            step: params.step,
+            ttl: params.ttl,
+            quote_accounts_len: 0,
+            max_fee: None,
+            client_order_id: 0,
+            receiver_override: None,
+            option_items: None,
+        };
+        let (_, new_ball, step) = Send::next_ball(store, params.step)?;
+        let seq = if Send::uses_seq(store, peer) { store.peek_seq() } else { 0 };
+        let message = Send::encode_message(store, peer, &send_params, new_ball, step, seq)?;
+        Ok((message, new_ball, step, seq))
+    }
+
+    /// The outbound options `params.quote_mode` asks for.
+    fn options(ctx: &Context<QuoteSend>, params: &QuoteSendParams) -> Result<Vec<u8>> {
+        let reject_conflicts = ctx.accounts.store.reject_conflicting_options;
+        match &params.quote_mode {
+            QuoteMode::Combined => ctx.accounts.peer.enforced_options.combine_options(
+                &None::<Vec<u8>>,
+                &params.options,
+                reject_conflicts,
+            ),
+            QuoteMode::RawOnly => Ok(params.options.clone()),
+            QuoteMode::Hypothetical(enforced) => Ok(options_codec::merge_type3(
+                enforced,
+                &params.options,
+                reject_conflicts,
+            )?),
+        }
+    }
+
+    /// Ask the Endpoint how much sending `message` with `options` would cost.
+    fn quote(
+        ctx: &Context<QuoteSend>,
+        params: &QuoteSendParams,
+        message: Vec<u8>,
+        options: Vec<u8>,
+    ) -> Result<MessagingFee> {
         let quote_params = QuoteParams {
             sender: ctx.accounts.store.key(),
             dst_eid: params.dst_eid,
             receiver: params.receiver,
             message,
             pay_in_lz_token: params.pay_in_lz_token,
-            options: ctx
-                .accounts
-                .peer
-                .enforced_options
-                .combine_options(&None::<Vec<u8>>, &params.options)?,
+            options,
         };
         oapp::endpoint_cpi::quote(ENDPOINT_ID, ctx.remaining_accounts, quote_params)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    fn peer(compact: bool) -> PeerConfig {
+        // An all-zero account reads as a peer with nothing configured
+        let mut peer = PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap();
+        peer.compact = compact;
+        peer
+    }
+
+    fn quote_params(step: Option<[u8; 32]>, memo: Option<String>) -> QuoteSendParams {
+        QuoteSendParams {
+            dst_eid: 30101,
+            receiver: [1; 32],
+            return_options: vec![0, 3],
+            options: Vec::new(),
+            pay_in_lz_token: false,
+            memo,
+            step,
+            ttl: 0,
+            quote_mode: QuoteMode::Combined,
+        }
+    }
+
+    /// The message `send` puts on the wire for the quoted params, taking its sequence number
+    fn sent_message(store: &mut Store, peer: &PeerConfig, params: &QuoteSendParams) -> Vec<u8> {
+        let send_params = SendMessageParams {
+            dst_eid: params.dst_eid,
+            return_options: params.return_options.clone(),
+            options: params.options.clone(),
+            native_fee: AUTO_NATIVE_FEE,
+            lz_token_fee: 0,
+            memo: params.memo.clone(),
+            step: params.step,
+            ttl: params.ttl,
+            quote_accounts_len: 0,
+            max_fee: None,
+            client_order_id: 7,
+            receiver_override: None,
+            option_items: None,
+        };
+        let (_, new_ball, step) = Send::next_ball(store, send_params.step).unwrap();
+        Send::encode_next_message(store, peer, &send_params, new_ball, step).unwrap().0
+    }
+
+    #[test]
+    fn quoted_message_is_the_sent_message() {
+        let step = U256::from(25u128).to_be_bytes();
+        for (compact, memo) in [(false, Some("hi".to_string())), (true, None)] {
+            let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+            let peer = peer(compact);
+            for params in [quote_params(None, memo.clone()), quote_params(Some(step), memo)] {
+                let (quoted, new_ball, ..) =
+                    QuoteSend::outbound_message(&store, &peer, &params).unwrap();
+                assert_eq!(sent_message(&mut store, &peer, &params), quoted);
+                if !compact {
+                    let decoded = uint256_msg_codec::decode_aba(&quoted).unwrap();
+                    assert_eq!(decoded.step, params.step.unwrap_or_default());
+                    assert_eq!(decoded.ball, new_ball.to_be_bytes());
+                }
+            }
+            // Each full-width send took a sequence number; compact sends carry none
+            assert_eq!(store.peek_seq(), if compact { 1 } else { 3 });
+        }
+    }
+
+    #[test]
+    fn step_is_subtracted_from_the_ball() {
+        let store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let params = quote_params(Some(U256::from(25u128).to_be_bytes()), None);
+        let (_, new_ball, ..) = QuoteSend::outbound_message(&store, &peer(false), &params).unwrap();
+        assert_eq!(new_ball, U256::from(Store::INITIAL_BALL - 25));
+    }
+
+    #[test]
+    fn zero_step_is_rejected() {
+        let store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let params = quote_params(Some([0; 32]), None);
+        let err = QuoteSend::outbound_message(&store, &peer(false), &params).unwrap_err();
+        assert_eq!(
+            ProgramError::from(err),
+            ProgramError::from(anchor_lang::error::Error::from(MyOAppError::ZeroStep))
+        );
+    }
+
+    #[test]
+    fn depleted_ball_is_not_quoted() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        store.ball = [0; 32];
+        let err = QuoteSend::outbound_message(&store, &peer(false), &quote_params(None, None))
+            .unwrap_err();
+        assert_eq!(
+            ProgramError::from(err),
+            ProgramError::from(anchor_lang::error::Error::from(MyOAppError::BallDepleted))
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{instructions::QuoteParams, ID as ENDPOINT_ID};
+
+// Permissionless crank that quotes a return leg to one eid and caches the fee for lz_receive.
+// The Endpoint::quote accounts are passed as remaining_accounts.
+
+#[derive(Accounts)]
+#[instruction(params: RefreshQuoteParams)]
+pub struct RefreshQuote<'info> {
+    #[account(mut)]
+    /// Pays the cache's rent on the first refresh
+    pub payer: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    #[account(
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    /// Peer the returns go to; supplies the receiver and the return options
+    pub peer: Account<'info, PeerConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = FeeQuoteCache::SIZE,
+        seeds = [FEE_QUOTE_CACHE_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump
+    )]
+    pub fee_quote_cache: Account<'info, FeeQuoteCache>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RefreshQuoteParams {
+    pub dst_eid: u32,
+}
+
+impl RefreshQuote<'_> {
+    pub fn apply(ctx: &mut Context<RefreshQuote>, params: &RefreshQuoteParams) -> Result<()> {
+        let store = &ctx.accounts.store;
+        let peer = &ctx.accounts.peer;
+        // A vanilla message carrying the current ball stands in for the return; the options
+        // are the ones a return without sender-supplied return options gets
+        let message =
+            uint256_msg_codec::tag_if(uint256_msg_codec::encode(&store.ball), store.require_magic);
+        let fee = oapp::endpoint_cpi::quote(
+            ENDPOINT_ID,
+            ctx.remaining_accounts,
+            QuoteParams {
+                sender: store.key(),
+                dst_eid: params.dst_eid,
+                receiver: peer.return_address(),
+                message,
+                options: crate::handlers::return_options(peer, &[])?,
+                pay_in_lz_token: false,
+            },
+        )?;
+
+        let cache = &mut ctx.accounts.fee_quote_cache;
+        cache.bump = ctx.bumps.fee_quote_cache;
+        cache.dst_eid = params.dst_eid;
+        cache.native_fee = fee.native_fee;
+        cache.quoted_slot = Clock::get()?.slot;
+        cache.max_age_slots = store.fee_quote_max_age_slots;
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Clears Store.in_flight for pings whose answer will never be matched (lost, answered
+// after a NewGame, or overwritten by a later send to the same peer), so a single-flight
+// Store can send again.
+
+#[derive(Accounts)]
+pub struct ResetInFlight<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+}
+
+impl ResetInFlight<'_> {
+    pub fn apply(ctx: &mut Context<ResetInFlight>) -> Result<()> {
+        msg!("Resetting {} in-flight pings", ctx.accounts.store.in_flight);
+        ctx.accounts.store.in_flight = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::MyOAppError;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use std::collections::BTreeSet;
+
+    fn store_account(store: &Store) -> TestAccount {
+        let key = Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(0)], &crate::ID).0;
+        let mut data = Vec::new();
+        store.try_serialize(&mut data).unwrap();
+        TestAccount::new(key, crate::ID, false, data)
+    }
+
+    /// Check the accounts, reset and write the Store back, as the instruction does.
+    fn reset(store: &mut TestAccount, signer: Pubkey) -> Result<()> {
+        let mut signer = TestAccount::signer(signer);
+        let infos = [signer.info(), store.info()];
+        let mut accounts = ResetInFlight::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )?;
+        ResetInFlight::apply(&mut Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))?;
+        accounts.exit(&crate::ID)
+    }
+
+    fn assert_error(result: Result<()>, expected: anchor_lang::error::Error) {
+        assert_eq!(ProgramError::from(result.unwrap_err()), ProgramError::from(expected));
+    }
+
+    #[test]
+    fn single_flight_store_refuses_a_second_ping_until_reset() {
+        let admin = Pubkey::new_unique();
+        let bump = Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(0)], &crate::ID).1;
+        let mut store = Store::new(admin, bump, Pubkey::new_unique(), 0);
+        store.single_flight = true;
+        store.start_flight().unwrap();
+        assert_error(store.start_flight(), MyOAppError::PingInFlight.into());
+        assert_eq!(store.in_flight, 1);
+
+        let mut account = store_account(&store);
+        // Only the admin may reset
+        assert_error(
+            reset(&mut account, Pubkey::new_unique()),
+            ErrorCode::ConstraintAddress.into(),
+        );
+        reset(&mut account, admin).unwrap();
+        let mut store = Store::try_deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!(store.in_flight, 0);
+        store.start_flight().unwrap();
+    }
+
+    #[test]
+    fn without_single_flight_pings_pile_up() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        for _ in 0..3 {
+            store.start_flight().unwrap();
+        }
+        assert_eq!(store.in_flight, 3);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::ID as ENDPOINT_ID;
+
+#[derive(Accounts)]
+#[instruction(params: RetryReturnParams)]
+pub struct RetryReturn<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store; pays the fee passed in `params`
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction and counts open PendingReturns
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [PENDING_RETURN_SEED, &store.key().to_bytes(), &params.guid],
+        bump = pending_return.bump
+    )]
+    /// Return leg parked by lz_receive, closed once it has been sent
+    pub pending_return: Account<'info, PendingReturn>,
+    #[account(
+        seeds = [PEER_SEED, &store.key().to_bytes(), &pending_return.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    /// Configuration for the chain the return is addressed to
+    pub peer: Account<'info, PeerConfig>,
+    /// CHECK: only receives the PendingReturn rent back; must be the account that paid it
+    #[account(mut, address = pending_return.payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct RetryReturnParams {
+    pub guid: [u8; 32], // Guid of the inbound message whose return was parked
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
+
+impl RetryReturn<'_> {
+    pub fn apply(ctx: &mut Context<RetryReturn>, params: &RetryReturnParams) -> Result<()> {
+        let id_seed = Store::id_seed(ctx.accounts.store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[ctx.accounts.store.bump]];
+
+        // The message and options were fixed when the return was parked; the receiver is
+        // read from the peer config so a corrected peer address is picked up.
+        let pending = &ctx.accounts.pending_return;
+        let send_params = pending.send_params(
+            ctx.accounts.peer.return_address(),
+            params.native_fee,
+            params.lz_token_fee,
+        );
+        // remaining_accounts are the fresh Endpoint::send accounts
+        oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            ctx.accounts.store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+
+        emit!(crate::events::ReturnRetried { dst_eid: pending.dst_eid, guid: params.guid });
+        ctx.accounts.store.close_pending_return();
+        ctx.accounts.store.touch(pending.dst_eid, BallDirection::Sent)?;
+        Ok(())
+    }
+}
@@ -1,14 +1,25 @@
-use crate::{consts::*, *};
+use crate::{consts::*, errors::MyOAppError, options_builder::OptionItem, *};
 use anchor_lang::prelude::*;
 use ethnum::U256;
 use oapp::endpoint::{
-    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+    cpi::accounts::Quote as EndpointQuote,
+    instructions::{QuoteParams, SendParams},
+    state::EndpointSettings,
+    ConstructCPIContext,
+    ENDPOINT_SEED,
+    ID as ENDPOINT_ID,
+    MessagingReceipt,
 };
 
+// Token programs an LZ token fee may be paid through
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGkPFXZ3WPXbz6vZ29h4ty2u");
+const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS5EPFLC1PHnBqCXEpPxuEb");
+
 #[derive(Accounts)]
 #[instruction(params: SendMessageParams)]
 pub struct Send<'info> {
     #[account(
+        mut,
         seeds = [
             PEER_SEED,
             &store.key().to_bytes(),
@@ -17,13 +28,43 @@ pub struct Send<'info> {
         bump = peer.bump
     )]
     /// Configuration for the destination chain. Holds the peer address and any
-    /// enforced messaging options.
+    /// enforced messaging options, and records the sent ball to check the reply against.
     pub peer: Account<'info, PeerConfig>,
-    #[account(seeds = [STORE_SEED], bump = store.bump)]
-    /// OApp Store PDA that signs the send instruction
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction and hands out sequence numbers
     pub store: Account<'info, Store>,
     #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
     pub endpoint: Account<'info, EndpointSettings>,
+    /// Signer checked against the Store's send permission
+    pub sender: Signer<'info>,
+    #[account(seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()], bump = send_allowlist.bump)]
+    /// Signers allowed to send; only needed under SendPermission::Allowlist
+    pub send_allowlist: Option<Account<'info, SendAllowlist>>,
+    #[account(
+        seeds = [
+            ALLOWED_RECEIVERS_SEED,
+            &store.key().to_bytes(),
+            &params.dst_eid.to_be_bytes()
+        ],
+        bump = allowed_receivers.bump
+    )]
+    /// Receivers `receiver_override` may name for this eid; only needed with an override
+    pub allowed_receivers: Option<Account<'info, AllowedReceivers>>,
+    /// CHECK: The Store's BallHistory; the sent ball is recorded when it is passed and
+    /// `init_history` has created it
+    #[account(mut, seeds = [BALL_HISTORY_SEED, &store.key().to_bytes()], bump)]
+    pub ball_history: Option<UncheckedAccount<'info>>,
+    // Only needed when paying `lz_token_fee`. The send library debits the fee from
+    // `lz_token_source` with `sender` as authority, so these must also appear among the
+    // Endpoint::send accounts in remaining_accounts (the endpoint SDK includes them when
+    // asked to pay in LZ token).
+    /// CHECK: the Endpoint's LZ token mint, checked in `apply`
+    pub lz_token_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: `sender`'s token account for that mint, checked in `apply`
+    #[account(mut)]
+    pub lz_token_source: Option<UncheckedAccount<'info>>,
+    /// CHECK: the SPL token or Token-2022 program owning `lz_token_source`
+    pub token_program: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
@@ -31,55 +72,395 @@ pub struct SendMessageParams {
     pub dst_eid: u32,
     pub return_options: Vec<u8>, // Options for the return message (B→A)
     pub options: Vec<u8>, // Additional options for the initial send (A→B)
-    pub native_fee: u64,
+    pub native_fee: u64, // AUTO_NATIVE_FEE to quote the fee inside the instruction
     pub lz_token_fee: u64,
+    pub memo: Option<String>, // Optional note for the destination (at most MAX_MEMO_LEN bytes)
+    pub step: Option<[u8; 32]>, // Per-hop decrement as a big-endian uint256; defaults to 1
+    pub ttl: u64, // Seconds after sending the peer drops the message unapplied (0 = no expiry)
+    // With AUTO_NATIVE_FEE: how many leading remaining accounts are the Endpoint::quote
+    // accounts, and the most the quoted fee may be (None = no limit)
+    pub quote_accounts_len: u16,
+    pub max_fee: Option<u64>,
+    // Caller's id for this send, echoed in BallSent; a repeat of one of the peer's recent
+    // ids is rejected so a retried transaction can't send twice (0 = no deduplication)
+    pub client_order_id: u64,
+    // Receiver to send to instead of the peer address; must be in the eid's AllowedReceivers
+    pub receiver_override: Option<[u8; 32]>,
+    // The additional options as structured items the program encodes, in place of `options`
+    // (which must then be empty)
+    pub option_items: Option<Vec<OptionItem>>,
 }
 
 impl<'info> Send<'info> {
-    pub fn apply(ctx: &mut Context<Send>, params: &SendMessageParams) -> Result<()> {
-        // Prepare the seeds for the OApp Store PDA, which is used to sign the CPI call to the Endpoint program.
-        let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+    pub fn apply(ctx: &mut Context<Send>, params: &SendMessageParams) -> Result<MessagingReceipt> {
+        // Counters never end, so only the ball game can be over
+        let store = &ctx.accounts.store;
+        let allowlist = ctx.accounts.send_allowlist.as_deref();
+        store.require_send_permitted(ctx.accounts.sender.key, allowlist)?;
+        require!(store.mode == GameMode::Counter || !store.game_over, MyOAppError::GameOver);
+        if params.lz_token_fee > 0 {
+            Self::check_lz_token_accounts(ctx)?;
+        }
+        if let Some(receiver) = &params.receiver_override {
+            let allowed = ctx.accounts.allowed_receivers.as_deref();
+            require!(
+                allowed.is_some_and(|allowed| allowed.contains(receiver)),
+                MyOAppError::ReceiverNotAllowed
+            );
+        }
+        let (ball, new_ball, step) = Self::next_ball(store, params.step)?;
+        ctx.accounts.store.start_flight()?;
 
-        let ball = ctx.accounts.store.ball;
-        let ball_ethnum = U256::from_be_bytes(ball);
-        let new_ball_ethnum = ball_ethnum.saturating_sub(U256::ONE);
-        let new_ball = new_ball_ethnum.to_be_bytes();
-        
-        // Encode ABA message with return options
-        let message = uint256_msg_codec::encode_aba(&new_ball, &params.return_options);
+        let receipt = Self::send_ball(
+            &mut ctx.accounts.store,
+            &mut ctx.accounts.peer,
+            ctx.remaining_accounts,
+            params,
+            ball,
+            new_ball,
+            step,
+        )?;
+        if let Some(ball_history) = &ctx.accounts.ball_history {
+            let entry = BallHistoryEntry {
+                ball: new_ball.to_be_bytes(),
+                eid: params.dst_eid,
+                direction: BallDirection::Sent,
+                slot: Clock::get()?.slot,
+            };
+            BallHistory::record(ball_history, &entry)?;
+        }
+        Ok(receipt)
+    }
 
-        // Emit event tracking the ball value
-        emit!(crate::events::BallSent {
-            current_ball: ball.to_vec(),
-            new_ball: new_ball.to_vec(),
-            current_ball_str: ball_ethnum.to_string(),
-            new_ball_str: new_ball_ethnum.to_string(),
-            dst_eid: params.dst_eid,
-        });
+    /// Check the accounts an LZ token fee is paid with: `sender`'s token account for the
+    /// Endpoint's LZ token mint, under a token program, all passed on to Endpoint::send.
+    fn check_lz_token_accounts(ctx: &Context<Send>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let lz_token_mint =
+            accounts.endpoint.lz_token_mint.ok_or(MyOAppError::LzTokenUnavailable)?;
+        let (Some(mint), Some(source), Some(token_program)) =
+            (&accounts.lz_token_mint, &accounts.lz_token_source, &accounts.token_program)
+        else {
+            return err!(MyOAppError::InvalidLzTokenAccounts);
+        };
+        require_keys_eq!(mint.key(), lz_token_mint, MyOAppError::InvalidLzTokenAccounts);
+        require!(
+            token_program.key() == TOKEN_PROGRAM_ID || token_program.key() == TOKEN_2022_PROGRAM_ID,
+            MyOAppError::InvalidLzTokenAccounts
+        );
+        require_keys_eq!(*source.owner, token_program.key(), MyOAppError::InvalidLzTokenAccounts);
+        // Token accounts start with the mint and the owner (both program flavours)
+        let data = source.try_borrow_data()?;
+        require!(
+            data.len() >= 64
+                && data[..32] == lz_token_mint.to_bytes()
+                && data[32..64] == accounts.sender.key().to_bytes(),
+            MyOAppError::InvalidLzTokenAccounts
+        );
+        // The send library only debits what it finds in the accounts it is given
+        for key in [mint.key(), source.key(), token_program.key()] {
+            require!(
+                ctx.remaining_accounts.iter().any(|account| account.key() == key),
+                MyOAppError::InvalidLzTokenAccounts
+            );
+        }
+        Ok(())
+    }
 
-        // Prepare the SendParams for the Endpoint::send CPI call.
+    /// The Store's current value, the value the next send carries and the step it is sent
+    /// with (1 unless `step` says otherwise). Counters go up by one; balls go down by the
+    /// step under the underflow policy. Shared with `quote_send`.
+    pub(crate) fn next_ball(
+        store: &Store,
+        step: Option<[u8; 32]>,
+    ) -> Result<(U256, U256, [u8; 32])> {
+        let ball = uint256_msg_codec::decode_u256(&store.ball)?;
+        Self::next_ball_after(store, ball, step)
+    }
+
+    /// `next_ball` for `ball` in place of the Store's value, so `ping_many` can send several
+    /// values in a row.
+    pub(crate) fn next_ball_after(
+        store: &Store,
+        ball: U256,
+        step: Option<[u8; 32]>,
+    ) -> Result<(U256, U256, [u8; 32])> {
+        if store.mode == GameMode::Counter {
+            let next = uint256_msg_codec::increment_counter(&ball.to_be_bytes());
+            return Ok((ball, U256::from_be_bytes(next), U256::ONE.to_be_bytes()));
+        }
+        let step = step.unwrap_or_else(|| U256::ONE.to_be_bytes());
+        require!(step != [0u8; 32], MyOAppError::ZeroStep);
+        // Checked before the Endpoint CPI so a depleted ball costs no fee
+        store.require_sendable(ball, U256::from_be_bytes(step))?;
+        let new_ball =
+            store.underflow_policy.decrement(ball, U256::from_be_bytes(step), store.signed_ball)?;
+        require!(!store.below_min_ball(new_ball), MyOAppError::BallBelowMinimum);
+        Ok((ball, new_ball, step))
+    }
+
+    /// The message `send` puts on the wire for `new_ball`, with sequence number `seq` (0 for
+    /// counters and compact peers, which carry none). `quote_send` builds the same bytes, so
+    /// the quote is for exactly what is sent; only a TTL's origin timestamp can differ.
+    pub(crate) fn encode_message(
+        store: &Store,
+        peer: &PeerConfig,
+        params: &SendMessageParams,
+        new_ball: U256,
+        step: [u8; 32],
+        seq: u64,
+    ) -> Result<Vec<u8>> {
+        // Counters carry nothing but their value. Compact peers only receive the ball, type
+        // and return options, so no sequence number is used up and no TTL is carried; the
+        // ball must fit in 16 bytes. Neither can carry a memo, so one is rejected rather than
+        // dropped.
+        let has_memo = params.memo.as_deref().is_some_and(|memo| !memo.is_empty());
+        require!(
+            !has_memo || (store.mode != GameMode::Counter && !peer.compact),
+            MyOAppError::MemoNotCarried
+        );
+        let message = if store.mode == GameMode::Counter {
+            uint256_msg_codec::encode_counter(&new_ball.to_be_bytes())
+        } else if peer.compact {
+            uint256_msg_codec::encode_aba_compact(
+                uint256_msg_codec::ball_as_u128(&new_ball.to_be_bytes())?,
+                uint256_msg_codec::ABA_TYPE,
+                &params.return_options,
+            )?
+        } else {
+            // Encode ABA message with return options, the optional memo and TTL
+            let mut extensions = Vec::new();
+            if params.ttl != 0 {
+                let sent_at = u64::try_from(Clock::get()?.unix_timestamp).unwrap_or(0);
+                extensions.push(uint256_msg_codec::Extension::ttl(sent_at, params.ttl));
+            }
+            uint256_msg_codec::AbaMessage {
+                ball: new_ball.to_be_bytes(),
+                msg_type: uint256_msg_codec::ABA_TYPE,
+                return_options: params.return_options.clone(),
+                memo: params.memo.clone().map(String::into_bytes).unwrap_or_default(),
+                seq,
+                extensions,
+                step,
+                ..Default::default()
+            }
+            .encode()?
+        };
+        Ok(uint256_msg_codec::tag_if(message, store.require_magic))
+    }
+
+    /// `encode_message` with the sequence number the send uses up, if any, taken from the
+    /// Store. Returns the message and that number. `quote_send` peeks at the same number
+    /// instead of taking it.
+    pub(crate) fn encode_next_message(
+        store: &mut Store,
+        peer: &PeerConfig,
+        params: &SendMessageParams,
+        new_ball: U256,
+        step: [u8; 32],
+    ) -> Result<(Vec<u8>, u64)> {
+        let seq = if Self::uses_seq(store, peer) { store.take_seq() } else { 0 };
+        Ok((Self::encode_message(store, peer, params, new_ball, step, seq)?, seq))
+    }
+
+    /// The options `send` passes to the Endpoint: the peer's enforced options combined with
+    /// `params.options`, or with the options built from `params.option_items`. Shared with
+    /// `preview_send`.
+    pub(crate) fn send_options(
+        store: &Store,
+        peer: &PeerConfig,
+        params: &SendMessageParams,
+    ) -> Result<Vec<u8>> {
         // For ABA pattern, options should include ExecutorLzReceiveOption with return gas
         // The options are typically built off-chain using the SDK, but we combine with enforced options here
+        // A gas option given both ways is merged (or rejected) rather than sent twice.
+        let extra_options = match &params.option_items {
+            Some(items) => {
+                require!(params.options.is_empty(), MyOAppError::OptionsGivenTwice);
+                options_builder::combine(items)
+            }
+            None => params.options.clone(),
+        };
+        peer.enforced_options.combine_options(
+            &None::<Vec<u8>>,
+            &extra_options,
+            store.reject_conflicting_options,
+        )
+    }
+
+    /// Whether a send to `peer` uses up one of the Store's sequence numbers.
+    pub(crate) fn uses_seq(store: &Store, peer: &PeerConfig) -> bool {
+        store.mode != GameMode::Counter && !peer.compact
+    }
+
+    /// Encode `new_ball` for the peer, record it as sent and send it through the Endpoint.
+    /// `ball` is the Store's ball before the send, for the event. A `receiver_override` must
+    /// already have been checked against the eid's AllowedReceivers. Shared with
+    /// `send_with_value`.
+    pub(crate) fn send_ball(
+        store: &mut Account<Store>,
+        peer: &mut Account<PeerConfig>,
+        remaining_accounts: &[AccountInfo],
+        params: &SendMessageParams,
+        ball: U256,
+        new_ball: U256,
+        step: [u8; 32],
+    ) -> Result<MessagingReceipt> {
+        // Prepare the seeds for the OApp Store PDA, which is used to sign the CPI call to the Endpoint program.
+        let id_seed = Store::id_seed(store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[store.bump]];
+        let signed = store.signed_ball;
+        let receiver = params.receiver_override.unwrap_or(peer.peer_address);
+
+        peer.start_send(Clock::get()?.slot)?;
+        peer.record_client_order(params.client_order_id)?;
+        let (message, seq) = Self::encode_next_message(store, peer, params, new_ball, step)?;
+        peer.last_sent_ball = Some(new_ball.to_be_bytes());
+        peer.last_sent_epoch = store.ball_epoch;
+        peer.outbound_count = peer.outbound_count.wrapping_add(1);
+        store.total_sent = store.total_sent.wrapping_add(1);
+        store.touch(params.dst_eid, BallDirection::Sent)?;
+
+        let options = Self::send_options(store, peer, params)?;
+
+        // With the AUTO_NATIVE_FEE sentinel the fee is quoted here, for the exact message and
+        // options, so it can't drift from a separate quote_send. The quote accounts come
+        // first in remaining_accounts, followed by the send accounts.
+        let (native_fee, send_accounts) = if params.native_fee == AUTO_NATIVE_FEE {
+            let quote_len = params.quote_accounts_len as usize;
+            require_gte!(
+                quote_len,
+                EndpointQuote::MIN_ACCOUNTS_LEN,
+                MyOAppError::NotEnoughRemainingAccounts
+            );
+            require_gte!(
+                remaining_accounts.len(),
+                quote_len,
+                MyOAppError::NotEnoughRemainingAccounts
+            );
+            let (quote_accounts, send_accounts) = remaining_accounts.split_at(quote_len);
+            let fee = oapp::endpoint_cpi::quote(
+                ENDPOINT_ID,
+                quote_accounts,
+                QuoteParams {
+                    sender: store.key(),
+                    dst_eid: params.dst_eid,
+                    receiver,
+                    message: message.clone(),
+                    options: options.clone(),
+                    // Paying part of the fee in LZ token changes the native part
+                    pay_in_lz_token: params.lz_token_fee > 0,
+                },
+            )?;
+            if let Some(max_fee) = params.max_fee {
+                require_gte!(max_fee, fee.native_fee, MyOAppError::FeeAboveMax);
+            }
+            (fee.native_fee, send_accounts)
+        } else {
+            (params.native_fee, remaining_accounts)
+        };
+
+        // Prepare the SendParams for the Endpoint::send CPI call.
         let send_params = SendParams {
             dst_eid: params.dst_eid,
-            receiver: ctx.accounts.peer.peer_address,
+            receiver,
             message,
-            options: ctx
-                .accounts
-                .peer
-                .enforced_options
-                .combine_options(&None::<Vec<u8>>, &params.options)?,
-            native_fee: params.native_fee,
+            options,
+            native_fee,
             lz_token_fee: params.lz_token_fee,
         };
         // Call the Endpoint::send CPI to send the message.
-        oapp::endpoint_cpi::send(
-            ENDPOINT_ID,
-            ctx.accounts.store.key(),
-            ctx.remaining_accounts,
-            seeds,
-            send_params,
-        )?;
-        Ok(())
+        let receipt =
+            oapp::endpoint_cpi::send(ENDPOINT_ID, store.key(), send_accounts, seeds, send_params)?;
+
+        // Emit event tracking the ball value, with the guid and nonce the Endpoint assigned
+        emit!(crate::events::BallSent {
+            current_ball: ball.to_be_bytes().to_vec(),
+            new_ball: new_ball.to_be_bytes().to_vec(),
+            current_ball_str: uint256_msg_codec::ball_to_string(ball, signed),
+            new_ball_str: uint256_msg_codec::ball_to_string(new_ball, signed),
+            new_ball_u64: uint256_msg_codec::ball_as_u64(&new_ball.to_be_bytes()).ok(),
+            dst_eid: params.dst_eid,
+            seq,
+            outbound_count: peer.outbound_count,
+            total_sent: store.total_sent,
+            mode: store.mode,
+            client_order_id: params.client_order_id,
+            memo: params.memo.clone().unwrap_or_default(),
+            receiver,
+            guid: receipt.guid,
+            nonce: receipt.nonce,
+            previous_touch: store.previous_touch(),
+        });
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    fn store(ball: u128, underflow_policy: UnderflowPolicy) -> Store {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        store.ball = U256::from(ball).to_be_bytes();
+        store.underflow_policy = underflow_policy;
+        store
+    }
+
+    fn step(step: u128) -> Option<[u8; 32]> {
+        Some(U256::from(step).to_be_bytes())
+    }
+
+    fn assert_err<T>(result: Result<T>, expected: MyOAppError) {
+        assert_eq!(
+            ProgramError::from(result.err().expect("the send is rejected")),
+            ProgramError::from(anchor_lang::error::Error::from(expected))
+        );
+    }
+
+    #[test]
+    fn send_leg_follows_the_underflow_policy() {
+        // A step past zero saturates, is rejected before any CPI, or wraps
+        let (_, new_ball, _) =
+            Send::next_ball(&store(1, UnderflowPolicy::Saturate), step(3)).unwrap();
+        assert_eq!(new_ball, U256::ZERO);
+        assert_err(
+            Send::next_ball(&store(1, UnderflowPolicy::Error), step(3)),
+            MyOAppError::BallDepleted,
+        );
+        let (_, new_ball, _) = Send::next_ball(&store(1, UnderflowPolicy::Wrap), step(3)).unwrap();
+        assert_eq!(new_ball, U256::MAX - U256::ONE);
+        // Wrap keeps going from zero
+        let (_, new_ball, _) = Send::next_ball(&store(0, UnderflowPolicy::Wrap), None).unwrap();
+        assert_eq!(new_ball, U256::MAX);
+    }
+
+    #[test]
+    fn ball_of_one_is_sent_as_zero() {
+        let (ball, new_ball, _) =
+            Send::next_ball(&store(1, UnderflowPolicy::Saturate), None).unwrap();
+        assert_eq!((ball, new_ball), (U256::ONE, U256::ZERO));
+    }
+
+    #[test]
+    fn depleted_ball_is_not_sent() {
+        for policy in [UnderflowPolicy::Saturate, UnderflowPolicy::Error] {
+            assert_err(Send::next_ball(&store(0, policy), None), MyOAppError::BallDepleted);
+        }
+        // Under Error a step larger than the ball is refused up front as well
+        assert_err(
+            Send::next_ball(&store(2, UnderflowPolicy::Error), step(3)),
+            MyOAppError::BallDepleted,
+        );
+        Send::next_ball(&store(3, UnderflowPolicy::Error), step(3)).unwrap();
+    }
+
+    #[test]
+    fn ball_below_min_is_not_sent() {
+        let mut store = store(11, UnderflowPolicy::Saturate);
+        store.min_ball = U256::from(10u128).to_be_bytes();
+        Send::next_ball(&store, None).unwrap();
+        assert_err(Send::next_ball(&store, step(2)), MyOAppError::BallBelowMinimum);
     }
 }
@@ -0,0 +1,115 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID, MessagingReceipt,
+};
+
+#[derive(Accounts)]
+pub struct SendMulti<'info> {
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instructions and hands out sequence numbers
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    /// Signer checked against the Store's send permission
+    pub sender: Signer<'info>,
+    #[account(seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()], bump = send_allowlist.bump)]
+    /// Signers allowed to send; only needed under SendPermission::Allowlist
+    pub send_allowlist: Option<Account<'info, SendAllowlist>>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendMultiParams {
+    pub destinations: Vec<SendMultiDestination>, // At most MAX_SEND_MULTI_DESTINATIONS
+    pub return_options: Vec<u8>, // Options for the return messages (B→A)
+    pub memo: Option<String>, // Optional note for the destinations (at most MAX_MEMO_LEN bytes)
+    pub step: Option<[u8; 32]>, // Per-hop decrement as a big-endian uint256; defaults to 1
+    pub ttl: u64, // Seconds after sending the peers drop the message unapplied (0 = no expiry)
+}
+
+/// One destination of a `send_multi`. Its accounts are
+/// `remaining_accounts[accounts_offset..accounts_offset + accounts_len]`: the destination's
+/// PeerConfig PDA followed by the Endpoint::send accounts for it.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendMultiDestination {
+    pub dst_eid: u32,
+    pub options: Vec<u8>, // Additional options for this send (A→B)
+    pub native_fee: u64, // Explicit fee; AUTO_NATIVE_FEE is not supported here
+    pub lz_token_fee: u64,
+    pub accounts_offset: u16,
+    pub accounts_len: u16,
+}
+
+impl SendMulti<'_> {
+    /// Returns the Endpoint's receipt for each destination, in the order given.
+    pub fn apply(
+        ctx: &mut Context<SendMulti>,
+        params: &SendMultiParams,
+    ) -> Result<Vec<MessagingReceipt>> {
+        require!(
+            params.destinations.len() <= MAX_SEND_MULTI_DESTINATIONS,
+            MyOAppError::TooManyDestinations
+        );
+        // A peer loaded twice would have one of its updates overwritten by the other
+        for (i, destination) in params.destinations.iter().enumerate() {
+            require!(
+                params.destinations[..i].iter().all(|other| other.dst_eid != destination.dst_eid),
+                MyOAppError::DuplicateDestination
+            );
+        }
+
+        // Every destination gets the same ball, as if each were its own `send`. Any failing
+        // send reverts the whole instruction, so either all destinations are sent or none.
+        let store = &ctx.accounts.store;
+        let allowlist = ctx.accounts.send_allowlist.as_deref();
+        store.require_send_permitted(ctx.accounts.sender.key, allowlist)?;
+        require!(store.mode == GameMode::Counter || !store.game_over, MyOAppError::GameOver);
+        let (ball, new_ball, step) = Send::next_ball(store, params.step)?;
+        let store_key = store.key().to_bytes();
+        let mut receipts = Vec::with_capacity(params.destinations.len());
+
+        for destination in &params.destinations {
+            let start = destination.accounts_offset as usize;
+            let accounts = start
+                .checked_add(destination.accounts_len as usize)
+                .and_then(|end| ctx.remaining_accounts.get(start..end))
+                .ok_or(MyOAppError::NotEnoughRemainingAccounts)?;
+            let (peer_info, send_accounts) =
+                accounts.split_first().ok_or(MyOAppError::NotEnoughRemainingAccounts)?;
+            let (expected_peer, _) = Pubkey::find_program_address(
+                &[PEER_SEED, &store_key, &destination.dst_eid.to_be_bytes()],
+                ctx.program_id,
+            );
+            require_keys_eq!(peer_info.key(), expected_peer, MyOAppError::InvalidPeerAccount);
+            let mut peer = Account::<PeerConfig>::try_from(peer_info)?;
+
+            let send_params = SendMessageParams {
+                dst_eid: destination.dst_eid,
+                return_options: params.return_options.clone(),
+                options: destination.options.clone(),
+                native_fee: destination.native_fee,
+                lz_token_fee: destination.lz_token_fee,
+                memo: params.memo.clone(),
+                step: params.step,
+                ttl: params.ttl,
+                quote_accounts_len: 0,
+                max_fee: None,
+                client_order_id: 0,
+                receiver_override: None,
+                option_items: None,
+            };
+            receipts.push(Send::send_ball(
+                &mut ctx.accounts.store,
+                &mut peer,
+                send_accounts,
+                &send_params,
+                ball,
+                new_ball,
+                step,
+            )?);
+            // Peers outside the Accounts struct aren't written back by Anchor
+            peer.exit(ctx.program_id)?;
+        }
+        Ok(receipts)
+    }
+}
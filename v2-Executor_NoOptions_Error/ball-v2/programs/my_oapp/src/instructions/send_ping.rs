@@ -0,0 +1,90 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID, MessagingReceipt,
+};
+
+// `send` with nothing but the destination: the options and return options are the peer's
+// ping defaults and the fee comes from the eid's FeeQuoteCache. The send accounts are the
+// remaining_accounts, as for `send`.
+
+#[derive(Accounts)]
+#[instruction(params: SendPingParams)]
+pub struct SendPing<'info> {
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction and hands out sequence numbers
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    /// Signer checked against the Store's send permission
+    pub sender: Signer<'info>,
+    #[account(seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()], bump = send_allowlist.bump)]
+    /// Signers allowed to send; only needed under SendPermission::Allowlist
+    pub send_allowlist: Option<Account<'info, SendAllowlist>>,
+    #[account(
+        seeds = [FEE_QUOTE_CACHE_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = fee_quote_cache.bump
+    )]
+    /// Kept fresh by `refresh_quote`; prices the ping
+    pub fee_quote_cache: Account<'info, FeeQuoteCache>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendPingParams {
+    pub dst_eid: u32,
+}
+
+impl SendPing<'_> {
+    pub fn apply(ctx: &mut Context<SendPing>, params: &SendPingParams) -> Result<MessagingReceipt> {
+        let store = &ctx.accounts.store;
+        let peer = &ctx.accounts.peer;
+        let allowlist = ctx.accounts.send_allowlist.as_deref();
+        store.require_send_permitted(ctx.accounts.sender.key, allowlist)?;
+        require!(store.mode == GameMode::Counter || !store.game_over, MyOAppError::GameOver);
+        // Without any send options the ping would carry no executor gas
+        require!(
+            !peer.default_options.is_empty() || !peer.enforced_options.send.is_empty(),
+            MyOAppError::PingDefaultsNotSet
+        );
+        // The cache quotes the same pathway. The fee is the most the Endpoint may charge, so
+        // the safety multiplier only raises the cap; the send library takes the actual fee.
+        let quoted_fee = ctx
+            .accounts
+            .fee_quote_cache
+            .fresh_fee(Clock::get()?.slot)
+            .ok_or(MyOAppError::QuoteCacheStale)?;
+        let (ball, new_ball, step) = Send::next_ball(store, None)?;
+
+        let send_params = SendMessageParams {
+            dst_eid: params.dst_eid,
+            return_options: peer.default_return_options.clone(),
+            options: peer.default_options.clone(),
+            native_fee: quoted_fee.saturating_mul(RETURN_FEE_MULTIPLIER),
+            lz_token_fee: 0,
+            memo: None,
+            step: None,
+            ttl: 0,
+            quote_accounts_len: 0,
+            max_fee: None,
+            client_order_id: 0,
+            receiver_override: None,
+            option_items: None,
+        };
+        ctx.accounts.store.start_flight()?;
+        Send::send_ball(
+            &mut ctx.accounts.store,
+            &mut ctx.accounts.peer,
+            ctx.remaining_accounts,
+            &send_params,
+            ball,
+            new_ball,
+            step,
+        )
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint::{
+    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+    MessagingReceipt,
+};
+
+// Sends caller-encoded bytes to a peer, e.g. to exercise the EVM contract's decoder
+// without a program upgrade. The Store's ball, sequence numbers and peer send bookkeeping
+// are left alone. Admin only, whatever the Store's send permission.
+
+#[derive(Accounts)]
+#[instruction(params: SendRawParams)]
+pub struct SendRaw<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    /// Configuration for the destination chain
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendRawParams {
+    pub dst_eid: u32,
+    pub message: Vec<u8>, // Sent as is (at most MAX_MESSAGE_LEN bytes)
+    pub options: Vec<u8>, // Combined with the peer's enforced options, as for send
+    pub native_fee: u64,
+    pub lz_token_fee: u64,
+}
+
+impl SendRaw<'_> {
+    pub fn apply(ctx: &mut Context<SendRaw>, params: &SendRawParams) -> Result<MessagingReceipt> {
+        require!(params.message.len() <= MAX_MESSAGE_LEN, MyOAppError::MessageTooLarge);
+        let store = &ctx.accounts.store;
+        let options = ctx.accounts.peer.enforced_options.combine_options(
+            &None::<Vec<u8>>,
+            &params.options,
+            store.reject_conflicting_options,
+        )?;
+        let send_params = SendParams {
+            dst_eid: params.dst_eid,
+            receiver: ctx.accounts.peer.peer_address,
+            message: params.message.clone(),
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: params.lz_token_fee,
+        };
+        let id_seed = Store::id_seed(store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[store.bump]];
+        let receipt = oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+        emit!(crate::events::RawMessageSent {
+            dst_eid: params.dst_eid,
+            message_len: params.message.len() as u32,
+            guid: receipt.guid,
+            nonce: receipt.nonce,
+        });
+        Ok(receipt)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use ethnum::U256;
+use oapp::endpoint::{
+    instructions::SendParams, state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID,
+    MessagingReceipt,
+};
+
+// `send` for the signer's own UserBall: the ping carries their key as the ABA origin caller,
+// so the peer's answer is applied to the UserBall instead of Store.ball. The send accounts
+// are the remaining_accounts, as for `send`.
+
+#[derive(Accounts)]
+#[instruction(params: SendUserPingParams)]
+pub struct SendUserPing<'info> {
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction and hands out sequence numbers
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+    /// Owner of the UserBall, checked against the Store's send permission
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_BALL_SEED, &store.key().to_bytes(), &user.key().to_bytes()],
+        bump = user_ball.bump
+    )]
+    pub user_ball: Account<'info, UserBall>,
+    #[account(seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()], bump = send_allowlist.bump)]
+    /// Signers allowed to send; only needed under SendPermission::Allowlist
+    pub send_allowlist: Option<Account<'info, SendAllowlist>>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendUserPingParams {
+    pub dst_eid: u32,
+    pub return_options: Vec<u8>, // Options for the return message (B→A)
+    pub options: Vec<u8>, // Additional options for the initial send (A→B)
+    pub native_fee: u64,
+}
+
+impl SendUserPing<'_> {
+    pub fn apply(
+        ctx: &mut Context<SendUserPing>,
+        params: &SendUserPingParams,
+    ) -> Result<MessagingReceipt> {
+        let store = &mut ctx.accounts.store;
+        let peer = &mut ctx.accounts.peer;
+        let allowlist = ctx.accounts.send_allowlist.as_deref();
+        store.require_send_permitted(ctx.accounts.user.key, allowlist)?;
+        // Counters and compact messages have no origin caller to route the answer by
+        require!(store.mode == GameMode::Ball && !peer.compact, MyOAppError::UserBallUnsupported);
+        let ball = U256::from_be_bytes(ctx.accounts.user_ball.ball);
+        let (ball, new_ball, step) = Send::next_ball_after(store, ball, None)?;
+
+        // The Store's outstanding ball (peer.last_sent_ball) is left alone: it belongs to
+        // the Store's game and the answer to this ping isn't checked against it
+        peer.start_send(Clock::get()?.slot)?;
+        let seq = store.take_seq();
+        let user = ctx.accounts.user.key();
+        let message = uint256_msg_codec::AbaMessage {
+            ball: new_ball.to_be_bytes(),
+            msg_type: uint256_msg_codec::ABA_TYPE,
+            return_options: params.return_options.clone(),
+            origin_caller: user.to_bytes(),
+            seq,
+            step,
+            ..Default::default()
+        }
+        .encode()?;
+        let message = uint256_msg_codec::tag_if(message, store.require_magic);
+        let options = peer.enforced_options.combine_options(
+            &None::<Vec<u8>>,
+            &params.options,
+            store.reject_conflicting_options,
+        )?;
+        peer.outbound_count = peer.outbound_count.wrapping_add(1);
+        store.total_sent = store.total_sent.wrapping_add(1);
+        store.touch(params.dst_eid, BallDirection::Sent)?;
+
+        let id_seed = Store::id_seed(store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[store.bump]];
+        let send_params = SendParams {
+            dst_eid: params.dst_eid,
+            receiver: peer.peer_address,
+            message,
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: 0,
+        };
+        let receipt = oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+
+        let user_ball = &mut ctx.accounts.user_ball;
+        user_ball.sent_count = user_ball.sent_count.wrapping_add(1);
+        emit!(crate::events::UserBallSent {
+            user,
+            current_ball: ball.to_be_bytes().to_vec(),
+            new_ball: new_ball.to_be_bytes().to_vec(),
+            dst_eid: params.dst_eid,
+            seq,
+            sent_count: user_ball.sent_count,
+            guid: receipt.guid,
+            nonce: receipt.nonce,
+        });
+        Ok(receipt)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use ethnum::U256;
+use oapp::endpoint::{
+    state::EndpointSettings, ENDPOINT_SEED, ID as ENDPOINT_ID, MessagingReceipt,
+};
+
+#[derive(Accounts)]
+#[instruction(params: SendWithValueParams)]
+pub struct SendWithValue<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PEER_SEED,
+            &store.key().to_bytes(),
+            &params.send.dst_eid.to_be_bytes()
+        ],
+        bump = peer.bump
+    )]
+    /// Configuration for the destination chain
+    pub peer: Account<'info, PeerConfig>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the send instruction and takes the new ball
+    pub store: Account<'info, Store>,
+    #[account(seeds = [ENDPOINT_SEED], bump = endpoint.bump, seeds::program = ENDPOINT_ID)]
+    pub endpoint: Account<'info, EndpointSettings>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SendWithValueParams {
+    pub ball: [u8; 32], // Ball to send, as a big-endian uint256 (two's complement when signed)
+    pub send: SendMessageParams,
+}
+
+impl SendWithValue<'_> {
+    pub fn apply(
+        ctx: &mut Context<SendWithValue>,
+        params: &SendWithValueParams,
+    ) -> Result<MessagingReceipt> {
+        // The ball is sent as given instead of being decremented, and becomes the Store's
+        // ball. This works after the game ended too; the game_over flag is left to NewGame.
+        let ball = uint256_msg_codec::decode_u256(&ctx.accounts.store.ball)?;
+        let new_ball = U256::from_be_bytes(params.ball);
+        let step = params.send.step.unwrap_or_else(|| U256::ONE.to_be_bytes());
+        require!(step != [0u8; 32], MyOAppError::ZeroStep);
+        // There is no AllowedReceivers account to check an override against
+        require!(params.send.receiver_override.is_none(), MyOAppError::ReceiverNotAllowed);
+        ctx.accounts.store.set_ball(params.ball);
+        ctx.accounts.store.start_flight()?;
+
+        Send::send_ball(
+            &mut ctx.accounts.store,
+            &mut ctx.accounts.peer,
+            ctx.remaining_accounts,
+            &params.send,
+            ball,
+            new_ball,
+            step,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    fn send_params() -> SendMessageParams {
+        SendMessageParams {
+            dst_eid: 30101,
+            return_options: vec![0, 3],
+            options: Vec::new(),
+            native_fee: 0,
+            lz_token_fee: 0,
+            memo: None,
+            step: None,
+            ttl: 0,
+            quote_accounts_len: 0,
+            max_fee: None,
+            client_order_id: 0,
+            receiver_override: None,
+            option_items: None,
+        }
+    }
+
+    fn peer(compact: bool) -> PeerConfig {
+        // An all-zero account reads as a peer with nothing configured
+        let mut peer = PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap();
+        peer.compact = compact;
+        peer
+    }
+
+    #[test]
+    fn any_value_is_sent_as_given() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let above_current = U256::from(Store::INITIAL_BALL) + U256::ONE;
+        for value in [U256::ZERO, U256::MAX, above_current] {
+            let message = Send::encode_message(
+                &store,
+                &peer(false),
+                &send_params(),
+                value,
+                U256::ONE.to_be_bytes(),
+                1,
+            )
+            .unwrap();
+            let decoded = uint256_msg_codec::decode_aba(&message).unwrap();
+            assert_eq!(decoded.ball, value.to_be_bytes());
+            assert_eq!(decoded.msg_type, uint256_msg_codec::ABA_TYPE);
+            store.set_ball(value.to_be_bytes());
+            assert_eq!(store.ball, value.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn compact_peers_only_get_values_that_fit() {
+        let store = Store::new(Pubkey::new_unique(), 255, ENDPOINT_ID, 0);
+        let step = U256::ONE.to_be_bytes();
+        let above_current = U256::from(Store::INITIAL_BALL) + U256::ONE;
+        assert!(Send::encode_message(&store, &peer(true), &send_params(), above_current, step, 0)
+            .is_ok());
+        let err = Send::encode_message(&store, &peer(true), &send_params(), U256::MAX, step, 0)
+            .unwrap_err();
+        assert_eq!(
+            ProgramError::from(err),
+            ProgramError::from(anchor_lang::error::Error::from(MyOAppError::ValueTooLarge))
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Edits the override receivers sends to one eid may target; the PDA is created on first use.
+
+#[derive(Accounts)]
+#[instruction(params: SetAllowedReceiversParams)]
+pub struct SetAllowedReceivers<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AllowedReceivers::SIZE,
+        seeds = [
+            ALLOWED_RECEIVERS_SEED,
+            &store.key().to_bytes(),
+            &params.remote_eid.to_be_bytes()
+        ],
+        bump
+    )]
+    /// Override receivers allowed for `remote_eid`
+    pub allowed_receivers: Account<'info, AllowedReceivers>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetAllowedReceiversParams {
+    pub remote_eid: u32,
+    pub receiver: [u8; 32],
+}
+
+impl SetAllowedReceivers<'_> {
+    pub fn add(
+        ctx: &mut Context<SetAllowedReceivers>,
+        params: &SetAllowedReceiversParams,
+    ) -> Result<()> {
+        ctx.accounts.allowed_receivers.bump = ctx.bumps.allowed_receivers;
+        ctx.accounts.allowed_receivers.add(params.receiver)
+    }
+
+    pub fn remove(
+        ctx: &mut Context<SetAllowedReceivers>,
+        params: &SetAllowedReceiversParams,
+    ) -> Result<()> {
+        ctx.accounts.allowed_receivers.bump = ctx.bumps.allowed_receivers;
+        ctx.accounts.allowed_receivers.remove(&params.receiver);
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use ethnum::U256;
+use oapp::endpoint::{instructions::SendParams, ID as ENDPOINT_ID};
+
+// Corrects the ball when the chains have drifted apart, e.g. after a message was skipped at
+// the Endpoint. Replies to balls sent before the correction are no longer checked against
+// it. With `sync` the new value is also sent to one peer as a vanilla message, which every
+// peer applies as a plain ball update without answering; the send accounts are the
+// remaining_accounts.
+
+#[derive(Accounts)]
+#[instruction(params: SetBallParams)]
+pub struct SetBall<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// OApp Store PDA that signs the sync send
+    pub store: Account<'info, Store>,
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &params.dst_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    /// Peer the value is synced to; only needed with `sync`
+    pub peer: Option<Account<'info, PeerConfig>>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetBallParams {
+    pub value: [u8; 32], // Big-endian uint256 (int256 for signed balls)
+    pub sync: bool,      // Also send the value to the peer for dst_eid
+    // Only used with `sync`
+    pub dst_eid: u32,
+    pub options: Vec<u8>,
+    pub native_fee: u64,
+}
+
+impl SetBall<'_> {
+    pub fn apply(ctx: &mut Context<SetBall>, params: &SetBallParams) -> Result<()> {
+        let store = &mut ctx.accounts.store;
+        // An explicit value outside the bounds is refused rather than clamped
+        let value = U256::from_be_bytes(params.value);
+        require!(store.clamp_ball(value) == value, MyOAppError::BallOutOfBounds);
+        let old_ball = store.ball;
+        store.set_ball(params.value);
+        store.ball_epoch = store.ball_epoch.wrapping_add(1);
+
+        let sync_guid = if params.sync { Some(Self::sync(ctx, params)?) } else { None };
+        emit!(crate::events::BallAdminSet {
+            admin: ctx.accounts.admin.key(),
+            old_ball: old_ball.to_vec(),
+            new_ball: params.value.to_vec(),
+            synced_eid: params.sync.then_some(params.dst_eid),
+            sync_guid,
+        });
+        Ok(())
+    }
+
+    /// Send the new value to the peer for `params.dst_eid`. Returns the sync message's guid.
+    fn sync(ctx: &mut Context<SetBall>, params: &SetBallParams) -> Result<[u8; 32]> {
+        let store = &mut ctx.accounts.store;
+        let peer = ctx.accounts.peer.as_mut().ok_or(MyOAppError::SyncPeerMissing)?;
+        // A peer the admin or guardian paused isn't synced until it is unpaused
+        require!(!peer.paused_inbound, MyOAppError::PeerPaused);
+        // Compact peers only decode the compact ABA layout
+        require!(!peer.compact, MyOAppError::SyncNotCarried);
+        let message = uint256_msg_codec::tag_if(
+            uint256_msg_codec::encode(&params.value),
+            store.require_magic,
+        );
+        let options = peer.enforced_options.combine_options(
+            &None::<Vec<u8>>,
+            &params.options,
+            store.reject_conflicting_options,
+        )?;
+
+        let id_seed = Store::id_seed(store.id);
+        let seeds: &[&[u8]] = &[STORE_SEED, &id_seed, &[store.bump]];
+        let send_params = SendParams {
+            dst_eid: params.dst_eid,
+            receiver: peer.peer_address,
+            message,
+            options,
+            native_fee: params.native_fee,
+            lz_token_fee: 0,
+        };
+        let receipt = oapp::endpoint_cpi::send(
+            ENDPOINT_ID,
+            store.key(),
+            ctx.remaining_accounts,
+            seeds,
+            send_params,
+        )?;
+        peer.outbound_count = peer.outbound_count.wrapping_add(1);
+        store.total_sent = store.total_sent.wrapping_add(1);
+        store.touch(params.dst_eid, BallDirection::Sent)?;
+        Ok(receipt.guid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use std::collections::BTreeSet;
+
+    const DST_EID: u32 = 30101;
+
+    fn store_account(store: &Store) -> TestAccount {
+        let key = Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(0)], &crate::ID).0;
+        let mut data = Vec::new();
+        store.try_serialize(&mut data).unwrap();
+        TestAccount::new(key, crate::ID, false, data)
+    }
+
+    fn peer_account(store: &TestAccount, paused_inbound: bool, compact: bool) -> TestAccount {
+        let (key, bump) = Pubkey::find_program_address(
+            &[PEER_SEED, &store.key.to_bytes(), &DST_EID.to_be_bytes()],
+            &crate::ID,
+        );
+        // An all-zero account reads as a peer with nothing configured
+        let mut peer = PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap();
+        peer.bump = bump;
+        peer.paused_inbound = paused_inbound;
+        peer.compact = compact;
+        let mut data = Vec::new();
+        peer.try_serialize(&mut data).unwrap();
+        TestAccount::new(key, crate::ID, false, data)
+    }
+
+    fn params(value: u64, sync: bool) -> SetBallParams {
+        SetBallParams {
+            value: U256::from(value).to_be_bytes(),
+            sync,
+            dst_eid: DST_EID,
+            options: Vec::new(),
+            native_fee: 0,
+        }
+    }
+
+    /// Check the accounts, set the ball and write the Store back, as the instruction does.
+    /// The peer slot is left empty unless `peer` is given.
+    fn set_ball(
+        store: &mut TestAccount,
+        peer: Option<&mut TestAccount>,
+        signer: Pubkey,
+        params: &SetBallParams,
+    ) -> Result<()> {
+        let mut signer = TestAccount::signer(signer);
+        // An optional account passed as the program id reads as absent
+        let mut absent = TestAccount::new(crate::ID, crate::ID, false, Vec::new());
+        let peer = peer.unwrap_or(&mut absent);
+        let infos = [signer.info(), store.info(), peer.info()];
+        let mut data = Vec::new();
+        params.serialize(&mut data).unwrap();
+        let mut accounts = SetBall::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &data,
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )?;
+        SetBall::apply(
+            &mut Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            params,
+        )?;
+        accounts.exit(&crate::ID)
+    }
+
+    fn assert_error(result: Result<()>, expected: anchor_lang::error::Error) {
+        assert_eq!(ProgramError::from(result.unwrap_err()), ProgramError::from(expected));
+    }
+
+    fn new_store(admin: Pubkey) -> Store {
+        let bump = Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(0)], &crate::ID).1;
+        Store::new(admin, bump, Pubkey::new_unique(), 0)
+    }
+
+    fn stored(account: &TestAccount) -> Store {
+        Store::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    #[test]
+    fn admin_sets_the_ball_without_sync() {
+        let admin = Pubkey::new_unique();
+        let mut store = store_account(&new_store(admin));
+        assert_error(
+            set_ball(&mut store, None, Pubkey::new_unique(), &params(7, false)),
+            ErrorCode::ConstraintAddress.into(),
+        );
+        set_ball(&mut store, None, admin, &params(7, false)).unwrap();
+        let store = stored(&store);
+        assert_eq!(store.ball, U256::from(7u64).to_be_bytes());
+        assert_eq!(store.ball_epoch, 1);
+    }
+
+    #[test]
+    fn ball_outside_the_bounds_is_refused() {
+        let admin = Pubkey::new_unique();
+        let mut bounded = new_store(admin);
+        bounded.min_ball = U256::from(10u64).to_be_bytes();
+        bounded.max_ball = U256::from(20u64).to_be_bytes();
+        let mut store = store_account(&bounded);
+        for value in [9, 21] {
+            assert_error(
+                set_ball(&mut store, None, admin, &params(value, false)),
+                MyOAppError::BallOutOfBounds.into(),
+            );
+        }
+        // Both bounds are inclusive
+        for value in [10, 20] {
+            set_ball(&mut store, None, admin, &params(value, false)).unwrap();
+            assert_eq!(stored(&store).ball, U256::from(value).to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn paused_peer_is_not_synced() {
+        let admin = Pubkey::new_unique();
+        let mut store = store_account(&new_store(admin));
+        let mut peer = peer_account(&store, true, false);
+        assert_error(
+            set_ball(&mut store, Some(&mut peer), admin, &params(7, true)),
+            MyOAppError::PeerPaused.into(),
+        );
+        // The pause only holds back the sync; the local correction still goes through
+        set_ball(&mut store, Some(&mut peer), admin, &params(7, false)).unwrap();
+        assert_eq!(stored(&store).ball, U256::from(7u64).to_be_bytes());
+    }
+
+    #[test]
+    fn sync_needs_a_peer_that_decodes_vanilla_balls() {
+        let admin = Pubkey::new_unique();
+        let mut store = store_account(&new_store(admin));
+        assert_error(
+            set_ball(&mut store, None, admin, &params(7, true)),
+            MyOAppError::SyncPeerMissing.into(),
+        );
+        let mut compact = peer_account(&store, false, true);
+        assert_error(
+            set_ball(&mut store, Some(&mut compact), admin, &params(7, true)),
+            MyOAppError::SyncNotCarried.into(),
+        );
+    }
+}
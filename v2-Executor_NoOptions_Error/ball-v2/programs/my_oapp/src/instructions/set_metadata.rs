@@ -0,0 +1,37 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Renames the deployment and points it at new metadata, e.g. to tell devnet, testnet and
+// mainnet-beta Stores apart on-chain.
+
+#[derive(Accounts)]
+pub struct SetMetadata<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetMetadataParams {
+    pub label: [u8; STORE_LABEL_LEN], // Zero-padded UTF-8
+    pub metadata_uri: [u8; METADATA_URI_LEN], // Zero-padded UTF-8
+}
+
+impl SetMetadata<'_> {
+    pub fn apply(ctx: &mut Context<SetMetadata>, params: &SetMetadataParams) -> Result<()> {
+        let store = &mut ctx.accounts.store;
+        let old_label = Store::padded_str(&store.label);
+        let old_metadata_uri = Store::padded_str(&store.metadata_uri);
+        store.set_metadata(params.label, params.metadata_uri)?;
+        emit!(crate::events::MetadataUpdated {
+            old_label,
+            new_label: Store::padded_str(&store.label),
+            old_metadata_uri,
+            new_metadata_uri: Store::padded_str(&store.metadata_uri),
+        });
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@ pub struct SetPeerConfig<'info> {
     )]
     /// Peer configuration PDA for a specific remote chain
     pub peer: Account<'info, PeerConfig>,
-    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
     /// Store PDA of this OApp
     pub store: Account<'info, Store>,
     pub system_program: Program<'info, System>,
@@ -40,6 +40,39 @@ pub enum PeerConfigParam {
         send: Vec<u8>,
         send_and_call: Vec<u8>,
     },
+    /// Enforce specific options for the return leg lz_receive sends to this peer, which
+    /// usually needs different executor gas than a ping (empty = use the send options)
+    ReturnEnforcedOptions(Vec<u8>),
+    /// Exchange compact 16-byte balls with this peer
+    Compact(bool),
+    /// Only accept inbound nonces from this peer in strict order
+    EnforceOrdered(bool),
+    /// Stop accepting inbound messages from this peer; sending to it is unaffected
+    PausedInbound(bool),
+    /// Reject replies whose ball isn't the last sent ball decremented by the step
+    StrictConsistency(bool),
+    /// Send return legs to this address instead of the peer address (None = peer address)
+    ReturnReceiver(Option<[u8; 32]>),
+    /// Have every return leg drop `amount` of native gas token to `receiver` on the peer
+    /// chain (amount 0 = no drop)
+    ReturnNativeDrop {
+        amount: u128,
+        receiver: [u8; 32],
+    },
+    /// Zero the inbound and outbound message counters
+    ResetCounters,
+    /// Also accept inbound messages from this address; sends still go to the peer address
+    AddSender([u8; 32]),
+    /// Stop accepting inbound messages from this address
+    RemoveSender([u8; 32]),
+    /// Require this many slots between sends to this peer (0 = no cooldown)
+    MinSendInterval(u64),
+    /// Options and return options `send_ping` sends to this peer with (both type-3, may be
+    /// empty when the enforced options cover the ping)
+    PingDefaults {
+        options: Vec<u8>,
+        return_options: Vec<u8>,
+    },
 }
 
 impl SetPeerConfig<'_> {
@@ -55,6 +88,49 @@ impl SetPeerConfig<'_> {
                 oapp::options::assert_type_3(&send_and_call)?;
                 ctx.accounts.peer.enforced_options.send_and_call = send_and_call;
             },
+            PeerConfigParam::ReturnEnforcedOptions(return_send) => {
+                // Bounded by the room PeerConfig::SIZE leaves for them
+                options_codec::validate_type3(&return_send, ENFORCED_OPTIONS_SEND_MAX_LEN)?;
+                ctx.accounts.peer.enforced_options.return_send = return_send;
+            },
+            PeerConfigParam::Compact(compact) => {
+                ctx.accounts.peer.compact = compact;
+            },
+            PeerConfigParam::EnforceOrdered(enforce_ordered) => {
+                ctx.accounts.peer.enforce_ordered = enforce_ordered;
+            },
+            PeerConfigParam::PausedInbound(paused_inbound) => {
+                ctx.accounts.peer.paused_inbound = paused_inbound;
+            },
+            PeerConfigParam::StrictConsistency(strict_consistency) => {
+                ctx.accounts.peer.strict_consistency = strict_consistency;
+            },
+            PeerConfigParam::ReturnReceiver(return_receiver) => {
+                ctx.accounts.peer.return_receiver = return_receiver;
+            },
+            PeerConfigParam::ReturnNativeDrop { amount, receiver } => {
+                ctx.accounts.peer.return_drop_amount = amount;
+                ctx.accounts.peer.return_drop_receiver = receiver;
+            },
+            PeerConfigParam::ResetCounters => {
+                ctx.accounts.peer.inbound_count = 0;
+                ctx.accounts.peer.outbound_count = 0;
+            },
+            PeerConfigParam::AddSender(sender) => {
+                ctx.accounts.peer.add_sender(sender)?;
+            },
+            PeerConfigParam::RemoveSender(sender) => {
+                ctx.accounts.peer.remove_sender(&sender)?;
+            },
+            PeerConfigParam::MinSendInterval(slots) => {
+                ctx.accounts.peer.min_send_interval_slots = slots;
+            },
+            PeerConfigParam::PingDefaults { options, return_options } => {
+                options_codec::validate_type3(&options, ENFORCED_OPTIONS_SEND_MAX_LEN)?;
+                options_codec::validate_type3(&return_options, MAX_RETURN_OPTIONS_LEN)?;
+                ctx.accounts.peer.default_options = options;
+                ctx.accounts.peer.default_return_options = return_options;
+            },
         }
         // Store the PDA bump for later validation
         ctx.accounts.peer.bump = ctx.bumps.peer;
@@ -0,0 +1,92 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use oapp::endpoint_cpi::LzAccount;
+
+// Caches the Endpoint::send accounts for a peer's return leg, as produced off-chain by the
+// endpoint SDK's getSendIXAccountMetaForCPI, so lz_receive_types can hand them to the Executor.
+// Re-run it whenever the send library or its configuration for the peer changes.
+
+#[derive(Accounts)]
+pub struct SetReturnAccounts<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 1 + 4,
+        seeds = [RETURN_ACCOUNTS_SEED, &store.key().to_bytes()],
+        bump
+    )]
+    /// Send accounts for every peer, read by lz_receive_types
+    pub return_accounts: Account<'info, ReturnAccounts>,
+    /// CHECK: Handled as raw bytes, since one created by an older build lacks the
+    /// `return_accounts` field; it is grown to fit and the field written.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [LZ_RECEIVE_TYPES_SEED, &store.key().to_bytes()],
+        bump
+    )]
+    pub lz_receive_types_accounts: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetReturnAccountsParams {
+    pub remote_eid: u32,
+    pub accounts: Vec<LzAccount>, // Empty to stop returning send accounts for this peer
+}
+
+impl SetReturnAccounts<'_> {
+    pub fn apply(
+        ctx: &mut Context<SetReturnAccounts>,
+        params: &SetReturnAccountsParams,
+    ) -> Result<()> {
+        require!(
+            params.accounts.len() <= MAX_RETURN_SEND_ACCOUNTS,
+            MyOAppError::TooManyReturnAccounts
+        );
+        ctx.accounts.return_accounts.bump = ctx.bumps.return_accounts;
+        ctx.accounts.return_accounts.set(params.remote_eid, params.accounts.clone());
+
+        // Grow the account to fit the new list; it is never shrunk
+        let info = ctx.accounts.return_accounts.to_account_info();
+        let space = ctx.accounts.return_accounts.space();
+        if space > info.data_len() {
+            let shortfall =
+                Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+            if shortfall > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.admin.to_account_info(),
+                            to: info.clone(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+            info.realloc(space, false)?;
+        }
+
+        // Point lz_receive_types at this PDA, which an upgraded deployment may not do yet
+        let types_info = ctx.accounts.lz_receive_types_accounts.to_account_info();
+        if types_info.data_len() < LzReceiveTypesAccounts::SIZE {
+            grow_account(
+                &ctx.accounts.admin,
+                &ctx.accounts.system_program,
+                &types_info,
+                LzReceiveTypesAccounts::SIZE,
+            )?;
+        }
+        LzReceiveTypesAccounts::write_return_accounts(
+            &types_info,
+            &ctx.accounts.return_accounts.key(),
+        )
+    }
+}
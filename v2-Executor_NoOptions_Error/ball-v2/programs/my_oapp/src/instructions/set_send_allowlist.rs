@@ -0,0 +1,46 @@
+use crate::{consts::*, *};
+use anchor_lang::prelude::*;
+
+// Edits the signers allowed to send while `Store.send_permission` is `Allowlist`; the PDA is
+// created on first use.
+
+#[derive(Accounts)]
+pub struct SetSendAllowlist<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = SendAllowlist::SIZE,
+        seeds = [SEND_ALLOWLIST_SEED, &store.key().to_bytes()],
+        bump
+    )]
+    /// Signers allowed to send under `SendPermission::Allowlist`
+    pub send_allowlist: Account<'info, SendAllowlist>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetSendAllowlistParams {
+    pub sender: Pubkey,
+}
+
+impl SetSendAllowlist<'_> {
+    pub fn add(ctx: &mut Context<SetSendAllowlist>, params: &SetSendAllowlistParams) -> Result<()> {
+        ctx.accounts.send_allowlist.bump = ctx.bumps.send_allowlist;
+        ctx.accounts.send_allowlist.add(params.sender)
+    }
+
+    pub fn remove(
+        ctx: &mut Context<SetSendAllowlist>,
+        params: &SetSendAllowlistParams,
+    ) -> Result<()> {
+        ctx.accounts.send_allowlist.bump = ctx.bumps.send_allowlist;
+        ctx.accounts.send_allowlist.remove(&params.sender);
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+use ethnum::U256;
+
+// Store-wide settings that are not tied to a specific remote chain
+
+#[derive(Accounts)]
+pub struct SetStoreConfig<'info> {
+    #[account(address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetStoreConfigParams {
+    pub config: StoreConfigParam,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub enum StoreConfigParam {
+    /// Stop sending the return leg once an inbound message carries this hop count (0 = unlimited)
+    MaxHopCount(u16),
+    /// Treat the ball as a signed int256 whose decrement may cross zero
+    SignedBall(bool),
+    /// Only accept (and only send) messages carrying the codec's MAGIC prefix
+    RequireMagic(bool),
+    /// Reset the ball to its initial value after a game has ended
+    NewGame,
+    /// Set how many more returns lz_receive may send automatically (None = unlimited)
+    MaxAutoReturns(Option<u64>),
+    /// Leave the return send to a separate `dispatch_return` instruction
+    SplitReturn(bool),
+    /// Choose how decrements that would leave the ball's range are handled
+    UnderflowPolicy(UnderflowPolicy),
+    /// Seconds of clock skew tolerated when checking an inbound message's TTL
+    TtlGrace(u64),
+    /// Queue received balls for this local composer program (None = no compose)
+    Composer(Option<Pubkey>),
+    /// Record a Receipt PDA for every message lz_receive processes
+    WriteReceipts(bool),
+    /// Switch between the ball and counter games, resetting the value (initial ball, or a
+    /// zero counter) as NewGame does
+    Mode(GameMode),
+    /// Reject send options that duplicate an executor option (e.g. a second lzReceive gas
+    /// option) instead of merging them by summing gas and value
+    RejectConflictingOptions(bool),
+    /// Restrict who may call send and send_multi (quotes stay permissionless)
+    SendPermission(SendPermission),
+    /// Slots a refreshed FeeQuoteCache quote is used for (0 = always use the estimate);
+    /// applies from each cache's next refresh
+    FeeQuoteMaxAge(u64),
+    /// Key allowed to pause peers with `pause_peer` alongside the admin (default = none)
+    Guardian(Pubkey),
+    /// Keep balls within `min_ball..=max_ball` (big-endian; all zeros or all ones = unset).
+    /// Sends below the minimum fail; inbound balls outside are clamped or rejected.
+    BallBounds { min_ball: [u8; 32], max_ball: [u8; 32], policy: BoundPolicy },
+    /// Refuse send, send_with_value and send_ping while a ping is in flight
+    SingleFlight(bool),
+    /// Refuse messages lz_receive would answer while this many PendingReturns are open
+    /// (0 = no cap); they stay deliverable once some are retried or drained
+    MaxPendingReturns(u16),
+}
+
+impl SetStoreConfig<'_> {
+    pub fn apply(ctx: &mut Context<SetStoreConfig>, params: &SetStoreConfigParams) -> Result<()> {
+        match params.config.clone() {
+            StoreConfigParam::MaxHopCount(max_hop_count) => {
+                ctx.accounts.store.max_hop_count = max_hop_count;
+            },
+            StoreConfigParam::SignedBall(signed_ball) => {
+                ctx.accounts.store.signed_ball = signed_ball;
+            },
+            StoreConfigParam::RequireMagic(require_magic) => {
+                ctx.accounts.store.require_magic = require_magic;
+            },
+            StoreConfigParam::NewGame => {
+                ctx.accounts.store.require_version(1)?;
+                let initial_ball = ctx.accounts.store.initial_ball;
+                ctx.accounts.store.set_ball(initial_ball);
+                ctx.accounts.store.game_over = false;
+                ctx.accounts.store.ball_epoch = ctx.accounts.store.ball_epoch.wrapping_add(1);
+            },
+            StoreConfigParam::MaxAutoReturns(max_auto_returns) => {
+                ctx.accounts.store.max_auto_returns = max_auto_returns;
+            },
+            StoreConfigParam::SplitReturn(split_return) => {
+                ctx.accounts.store.split_return = split_return;
+            },
+            StoreConfigParam::UnderflowPolicy(underflow_policy) => {
+                ctx.accounts.store.underflow_policy = underflow_policy;
+            },
+            StoreConfigParam::TtlGrace(ttl_grace_secs) => {
+                ctx.accounts.store.ttl_grace_secs = ttl_grace_secs;
+            },
+            StoreConfigParam::Composer(composer) => {
+                ctx.accounts.store.composer = composer;
+            },
+            StoreConfigParam::WriteReceipts(write_receipts) => {
+                ctx.accounts.store.write_receipts = write_receipts;
+            },
+            StoreConfigParam::Mode(mode) => {
+                let store = &mut ctx.accounts.store;
+                store.require_version(1)?;
+                let value = match mode {
+                    GameMode::Ball => U256::from_be_bytes(store.initial_ball),
+                    GameMode::Counter => U256::ZERO,
+                };
+                store.mode = mode;
+                store.set_ball(value.to_be_bytes());
+                store.game_over = false;
+                store.ball_epoch = store.ball_epoch.wrapping_add(1);
+            },
+            StoreConfigParam::RejectConflictingOptions(reject) => {
+                ctx.accounts.store.reject_conflicting_options = reject;
+            },
+            StoreConfigParam::SendPermission(send_permission) => {
+                ctx.accounts.store.send_permission = send_permission;
+            },
+            StoreConfigParam::FeeQuoteMaxAge(max_age_slots) => {
+                ctx.accounts.store.fee_quote_max_age_slots = max_age_slots;
+            },
+            StoreConfigParam::Guardian(guardian) => {
+                ctx.accounts.store.guardian = guardian;
+            },
+            StoreConfigParam::BallBounds { min_ball, max_ball, policy } => {
+                let store = &mut ctx.accounts.store;
+                store.min_ball = min_ball;
+                store.max_ball = max_ball;
+                store.bound_policy = policy;
+                require!(!store.ball_bounds_crossed(), MyOAppError::InvalidBallBounds);
+            },
+            StoreConfigParam::SingleFlight(single_flight) => {
+                ctx.accounts.store.single_flight = single_flight;
+            },
+            StoreConfigParam::MaxPendingReturns(max_pending_returns) => {
+                ctx.accounts.store.max_pending_returns = max_pending_returns;
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use std::collections::BTreeSet;
+
+    /// A Store account for game `id`, at that id's PDA.
+    fn store_account(id: u64, admin: Pubkey) -> TestAccount {
+        let (key, bump) =
+            Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(id)], &crate::ID);
+        let mut store = Store::new(admin, bump, Pubkey::new_unique(), 0);
+        store.id = id;
+        let mut data = Vec::new();
+        store.try_serialize(&mut data).unwrap();
+        TestAccount::new(key, crate::ID, false, data)
+    }
+
+    fn read_store(account: &TestAccount) -> Store {
+        Store::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    /// Check the accounts, apply `config` and write the Store back, as the instruction does.
+    fn configure(store: &mut TestAccount, admin: Pubkey, config: StoreConfigParam) -> Result<()> {
+        let mut signer = TestAccount::signer(admin);
+        let infos = [signer.info(), store.info()];
+        let mut accounts = SetStoreConfig::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )?;
+        let params = SetStoreConfigParams { config };
+        SetStoreConfig::apply(
+            &mut Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            &params,
+        )?;
+        accounts.exit(&crate::ID)
+    }
+
+    fn assert_error(result: Result<()>, expected: ErrorCode) {
+        let err = result.err().expect("set_store_config fails");
+        let expected = anchor_lang::error::Error::from(expected);
+        assert_eq!(ProgramError::from(err), ProgramError::from(expected));
+    }
+
+    #[test]
+    fn two_games_are_configured_independently() {
+        let (admin_a, admin_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut game_a, mut game_b) = (store_account(1, admin_a), store_account(2, admin_b));
+        assert_ne!(game_a.key, game_b.key);
+
+        configure(&mut game_a, admin_a, StoreConfigParam::MaxHopCount(3)).unwrap();
+        configure(&mut game_b, admin_b, StoreConfigParam::MaxHopCount(7)).unwrap();
+        configure(&mut game_a, admin_a, StoreConfigParam::SplitReturn(true)).unwrap();
+
+        let (a, b) = (read_store(&game_a), read_store(&game_b));
+        assert_eq!((a.id, a.admin, a.max_hop_count, a.split_return), (1, admin_a, 3, true));
+        assert_eq!((b.id, b.admin, b.max_hop_count, b.split_return), (2, admin_b, 7, false));
+    }
+
+    #[test]
+    fn one_games_admin_cannot_configure_another() {
+        let (admin_a, admin_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut game_b = store_account(2, admin_b);
+        assert_error(
+            configure(&mut game_b, admin_a, StoreConfigParam::MaxHopCount(3)),
+            ErrorCode::ConstraintAddress,
+        );
+        assert_eq!(read_store(&game_b).max_hop_count, 0);
+    }
+
+    #[test]
+    fn a_store_only_passes_at_its_own_ids_address() {
+        let admin = Pubkey::new_unique();
+        // Game 1's data at game 2's address
+        let mut moved = store_account(1, admin);
+        moved.key = store_account(2, admin).key;
+        assert_error(
+            configure(&mut moved, admin, StoreConfigParam::MaxHopCount(3)),
+            ErrorCode::ConstraintSeeds,
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{consts::*, errors::MyOAppError, *};
+use anchor_lang::prelude::*;
+
+// Two-step admin transfer: the admin proposes a key, which only becomes admin by signing
+// `accept_admin`, so a mistyped key can't take over the Store. Until then every admin-gated
+// instruction keeps taking the current admin.
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account(address = store.admin)]
+    /// Current admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The Store's pending admin
+    pub pending_admin: Signer<'info>,
+    #[account(mut, seeds = [STORE_SEED, &Store::id_seed(store.id)], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ProposeAdminParams {
+    pub new_admin: Pubkey,
+}
+
+impl TransferAdmin<'_> {
+    /// Record `new_admin` as the pending admin, replacing any earlier proposal.
+    pub fn propose(ctx: &mut Context<TransferAdmin>, params: &ProposeAdminParams) -> Result<()> {
+        ctx.accounts.store.pending_admin = Some(params.new_admin);
+        emit!(crate::events::AdminProposed {
+            admin: ctx.accounts.store.admin,
+            pending_admin: params.new_admin,
+        });
+        Ok(())
+    }
+
+    /// Drop the pending proposal, if any.
+    pub fn cancel(ctx: &mut Context<TransferAdmin>) -> Result<()> {
+        if let Some(pending_admin) = ctx.accounts.store.pending_admin.take() {
+            emit!(crate::events::AdminTransferCancelled {
+                admin: ctx.accounts.store.admin,
+                pending_admin,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl AcceptAdmin<'_> {
+    pub fn apply(ctx: &mut Context<AcceptAdmin>) -> Result<()> {
+        let store = &mut ctx.accounts.store;
+        require!(
+            store.pending_admin == Some(ctx.accounts.pending_admin.key()),
+            MyOAppError::NotPendingAdmin
+        );
+        let previous_admin = store.admin;
+        store.admin = ctx.accounts.pending_admin.key();
+        store.pending_admin = None;
+        emit!(crate::events::AdminAccepted { previous_admin, admin: store.admin });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    /// The Store and signer accounts both instructions take.
+    fn test_accounts(store: &Store, signer: Pubkey) -> Result<(TestAccount, TestAccount)> {
+        let mut data = Vec::new();
+        store.try_serialize(&mut data)?;
+        let store = TestAccount::new(Pubkey::new_unique(), crate::ID, false, data);
+        Ok((store, TestAccount::signer(signer)))
+    }
+
+    /// Run `propose` (with `params`) or `cancel` (without) signed by `signer` on `store`, and
+    /// return the Store it leaves behind.
+    fn transfer_admin(
+        store: &Store,
+        signer: Pubkey,
+        params: Option<&ProposeAdminParams>,
+    ) -> Result<Store> {
+        let (mut store, mut signer) = test_accounts(store, signer)?;
+        let (store_info, signer_info) = (store.info(), signer.info());
+        let mut accounts = TransferAdmin {
+            admin: Signer::try_from(&signer_info)?,
+            store: Account::try_from(&store_info)?,
+        };
+        let mut ctx = Context::new(&crate::ID, &mut accounts, &[], Default::default());
+        match params {
+            Some(params) => TransferAdmin::propose(&mut ctx, params)?,
+            None => TransferAdmin::cancel(&mut ctx)?,
+        }
+        Ok(accounts.store.into_inner())
+    }
+
+    /// Run `accept_admin` signed by `signer` on `store`, and return the Store it leaves behind.
+    fn accept_admin(store: &Store, signer: Pubkey) -> Result<Store> {
+        let (mut store, mut signer) = test_accounts(store, signer)?;
+        let (store_info, signer_info) = (store.info(), signer.info());
+        let mut accounts = AcceptAdmin {
+            pending_admin: Signer::try_from(&signer_info)?,
+            store: Account::try_from(&store_info)?,
+        };
+        AcceptAdmin::apply(&mut Context::new(&crate::ID, &mut accounts, &[], Default::default()))?;
+        Ok(accounts.store.into_inner())
+    }
+
+    fn proposal(new_admin: Pubkey) -> ProposeAdminParams {
+        ProposeAdminParams { new_admin }
+    }
+
+    fn assert_not_pending_admin(result: Result<Store>) {
+        let err = result.err().expect("accept_admin fails");
+        let expected = anchor_lang::error::Error::from(MyOAppError::NotPendingAdmin);
+        assert_eq!(ProgramError::from(err), ProgramError::from(expected));
+    }
+
+    #[test]
+    fn propose_then_accept() {
+        let (admin, new_admin) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let store = Store::new(admin, 255, Pubkey::new_unique(), 0);
+
+        let store = transfer_admin(&store, admin, Some(&proposal(new_admin))).unwrap();
+        // The current admin stays in charge until the proposal is accepted
+        assert_eq!(store.admin, admin);
+        assert_eq!(store.pending_admin, Some(new_admin));
+
+        let store = accept_admin(&store, new_admin).unwrap();
+        assert_eq!(store.admin, new_admin);
+        assert_eq!(store.pending_admin, None);
+    }
+
+    #[test]
+    fn propose_then_cancel() {
+        let (admin, new_admin) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let store = Store::new(admin, 255, Pubkey::new_unique(), 0);
+
+        let store = transfer_admin(&store, admin, Some(&proposal(new_admin))).unwrap();
+        let store = transfer_admin(&store, admin, None).unwrap();
+        assert_eq!(store.admin, admin);
+        assert_eq!(store.pending_admin, None);
+        // The withdrawn key can no longer take over
+        assert_not_pending_admin(accept_admin(&store, new_admin));
+    }
+
+    #[test]
+    fn unauthorized_accept_fails() {
+        let (admin, new_admin) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let store = Store::new(admin, 255, Pubkey::new_unique(), 0);
+
+        // Nothing proposed yet
+        assert_not_pending_admin(accept_admin(&store, new_admin));
+
+        // Proposed to someone else; the current admin can't accept on their behalf either
+        let store = transfer_admin(&store, admin, Some(&proposal(new_admin))).unwrap();
+        for signer in [Pubkey::new_unique(), admin] {
+            assert_not_pending_admin(accept_admin(&store, signer));
+        }
+        assert_eq!(store.admin, admin);
+        assert_eq!(store.pending_admin, Some(new_admin));
+    }
+}
@@ -1,25 +1,48 @@
+// Everything except the codec (and the constants it shares) needs the Solana program
+// dependencies. Off-chain tooling can depend on this crate with `default-features = false`.
+#[cfg(feature = "onchain")]
 mod errors;
+#[cfg(feature = "onchain")]
 mod events;
+#[cfg(feature = "onchain")]
+mod handlers;
+#[cfg(feature = "onchain")]
 mod instructions;
+#[cfg(feature = "onchain")]
 mod state;
-mod uint256_msg_codec;
-mod consts;
+#[cfg(all(test, feature = "onchain"))]
+pub(crate) mod test_utils;
+pub mod uint256_msg_codec;
+pub mod options_codec;
+pub mod options_builder;
+pub mod consts;
 
+#[cfg(feature = "onchain")]
 use anchor_lang::prelude::*;
+#[cfg(feature = "onchain")]
 use instructions::*;
-use oapp::{endpoint::MessagingFee, endpoint_cpi::LzAccount, LzReceiveParams};
+#[cfg(feature = "onchain")]
+use oapp::{
+    endpoint::{MessagingFee, MessagingReceipt},
+    endpoint_cpi::LzAccount,
+    LzReceiveParams,
+};
+#[cfg(feature = "onchain")]
 use solana_helper::program_id_from_env;
+#[cfg(feature = "onchain")]
 use state::*;
 
 // to build in verifiable mode and using environment variable (what the README instructs), run:
 // anchor build -v -e MYOAPP_ID=<OAPP_PROGRAM_ID>
 // to build in normal mode and using environment, run:
 // MYOAPP_ID=$PROGRAM_ID anchor build
+#[cfg(feature = "onchain")]
 declare_id!(anchor_lang::solana_program::pubkey::Pubkey::new_from_array(program_id_from_env!(
     "MYOAPP_ID",
     "41NCdrEvXhQ4mZgyJkmqYxL6A1uEmnraGj31UJ6PsXd3" // It's not necessary to change the ID here if you are building using environment variable
 )));
 
+#[cfg(feature = "onchain")]
 #[program]
 pub mod my_oapp {
     use super::*;
@@ -38,17 +61,264 @@ pub mod my_oapp {
         SetPeerConfig::apply(&mut ctx, &params)
     }
 
+    // admin instruction to update store-wide settings such as the hop limit.
+    pub fn set_store_config(
+        mut ctx: Context<SetStoreConfig>,
+        params: SetStoreConfigParams,
+    ) -> Result<()> {
+        SetStoreConfig::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to grow a Store created by an older build to the current layout.
+    pub fn migrate_store(mut ctx: Context<MigrateStore>) -> Result<()> {
+        MigrateStore::apply(&mut ctx)
+    }
+
+    // admin instruction growing the Store to `new_size` bytes, zero-filling the new tail.
+    pub fn extend_store(mut ctx: Context<ExtendStore>, params: ExtendAccountParams) -> Result<()> {
+        ExtendStore::apply(&mut ctx, &params)
+    }
+
+    // admin instruction growing the LzReceiveTypesAccounts PDA to `new_size` bytes.
+    pub fn extend_lz_receive_types(
+        mut ctx: Context<ExtendLzReceiveTypes>,
+        params: ExtendAccountParams,
+    ) -> Result<()> {
+        ExtendLzReceiveTypes::apply(&mut ctx, &params)
+    }
+
+    // admin instruction growing a PeerConfig created by an older build to the current size.
+    pub fn extend_peer_config(
+        mut ctx: Context<ExtendPeerConfig>,
+        _params: ExtendPeerConfigParams,
+    ) -> Result<()> {
+        ExtendPeerConfig::apply(&mut ctx)
+    }
+
+    // admin instruction to cache the Endpoint::send accounts lz_receive_types returns for a peer.
+    pub fn set_return_accounts(
+        mut ctx: Context<SetReturnAccounts>,
+        params: SetReturnAccountsParams,
+    ) -> Result<()> {
+        SetReturnAccounts::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to allow a signer to send under SendPermission::Allowlist.
+    pub fn add_allowed_sender(
+        mut ctx: Context<SetSendAllowlist>,
+        params: SetSendAllowlistParams,
+    ) -> Result<()> {
+        SetSendAllowlist::add(&mut ctx, &params)
+    }
+
+    // admin instruction to stop allowing a signer to send under SendPermission::Allowlist.
+    pub fn remove_allowed_sender(
+        mut ctx: Context<SetSendAllowlist>,
+        params: SetSendAllowlistParams,
+    ) -> Result<()> {
+        SetSendAllowlist::remove(&mut ctx, &params)
+    }
+
+    // admin instruction to let sends to an eid target this receiver instead of the peer.
+    pub fn add_allowed_receiver(
+        mut ctx: Context<SetAllowedReceivers>,
+        params: SetAllowedReceiversParams,
+    ) -> Result<()> {
+        SetAllowedReceivers::add(&mut ctx, &params)
+    }
+
+    // admin instruction to stop letting sends to an eid target this receiver.
+    pub fn remove_allowed_receiver(
+        mut ctx: Context<SetAllowedReceivers>,
+        params: SetAllowedReceiversParams,
+    ) -> Result<()> {
+        SetAllowedReceivers::remove(&mut ctx, &params)
+    }
+
+    // admin instruction correcting the ball, optionally syncing the value to one peer.
+    pub fn set_ball(mut ctx: Context<SetBall>, params: SetBallParams) -> Result<()> {
+        SetBall::apply(&mut ctx, &params)
+    }
+
+    // admin instruction replacing the Store's label and metadata URI.
+    pub fn set_metadata(mut ctx: Context<SetMetadata>, params: SetMetadataParams) -> Result<()> {
+        SetMetadata::apply(&mut ctx, &params)
+    }
+
+    // admin instruction closing a parked return without sending it.
+    pub fn drain_pending_return(
+        mut ctx: Context<DrainPendingReturn>,
+        params: DrainPendingReturnParams,
+    ) -> Result<()> {
+        DrainPendingReturn::apply(&mut ctx, &params)
+    }
+
+    // admin instruction clearing the in-flight ping count after answers that never matched.
+    pub fn reset_in_flight(mut ctx: Context<ResetInFlight>) -> Result<()> {
+        ResetInFlight::apply(&mut ctx)
+    }
+
+    // admin or guardian instruction pausing a peer's inbound messages; the admin unpauses
+    // with set_peer_config.
+    pub fn pause_peer(mut ctx: Context<PausePeer>, params: PausePeerParams) -> Result<()> {
+        PausePeer::apply(&mut ctx, &params)
+    }
+
+    // admin instruction proposing a new admin, who takes over by signing accept_admin.
+    pub fn propose_admin(
+        mut ctx: Context<TransferAdmin>,
+        params: ProposeAdminParams,
+    ) -> Result<()> {
+        TransferAdmin::propose(&mut ctx, &params)
+    }
+
+    // admin instruction withdrawing a pending admin proposal.
+    pub fn cancel_admin_transfer(mut ctx: Context<TransferAdmin>) -> Result<()> {
+        TransferAdmin::cancel(&mut ctx)
+    }
+
+    // instruction for the pending admin to accept the proposal and become admin.
+    pub fn accept_admin(mut ctx: Context<AcceptAdmin>) -> Result<()> {
+        AcceptAdmin::apply(&mut ctx)
+    }
+
+    // admin instruction creating the ball history ring that send and lz_receive record into.
+    pub fn init_history(mut ctx: Context<InitHistory>) -> Result<()> {
+        InitHistory::apply(&mut ctx)
+    }
+
+    // admin instruction to send a return leg that lz_receive parked as a PendingReturn.
+    pub fn retry_return(mut ctx: Context<RetryReturn>, params: RetryReturnParams) -> Result<()> {
+        RetryReturn::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to send an explicit ball value instead of the decremented Store ball.
+    pub fn send_with_value(
+        mut ctx: Context<SendWithValue>,
+        params: SendWithValueParams,
+    ) -> Result<MessagingReceipt> {
+        SendWithValue::apply(&mut ctx, &params)
+    }
+
+    // admin instruction to send pre-encoded bytes to a peer without touching the ball.
+    pub fn send_raw(mut ctx: Context<SendRaw>, params: SendRawParams) -> Result<MessagingReceipt> {
+        SendRaw::apply(&mut ctx, &params)
+    }
+
+    // admin (or rent payer) instruction to close a Receipt lz_receive wrote and reclaim its rent.
+    pub fn close_receipt(mut ctx: Context<CloseReceipt>, params: CloseReceiptParams) -> Result<()> {
+        CloseReceipt::apply(&mut ctx, &params)
+    }
+
     // ============================== Public ==============================
     // public instruction returning the estimated MessagingFee for sending a message.
     pub fn quote_send(ctx: Context<QuoteSend>, params: QuoteSendParams) -> Result<MessagingFee> {
         QuoteSend::apply(&ctx, &params)
     }
 
+    // public instruction quoting both legs of an ABA round trip: the outbound ping and the
+    // return this store would send back to `dst_eid`. `remaining_accounts` are the Endpoint
+    // quote accounts for `dst_eid`; both quotes share them.
+    pub fn quote_send_full(
+        ctx: Context<QuoteSend>,
+        params: QuoteSendParams,
+    ) -> Result<RoundTripFee> {
+        QuoteSend::apply_full(&ctx, &params)
+    }
+
+    // public instruction quoting the same send paying in native and in LZ token, so clients
+    // can compare payment modes in one call.
+    pub fn quote_send_both(
+        ctx: Context<QuoteSend>,
+        params: QuoteSendParams,
+    ) -> Result<PaymentModeFees> {
+        QuoteSend::apply_both(&ctx, &params)
+    }
+
+    // public instruction returning the Endpoint::send accounts a send to `dst_eid` needs.
+    pub fn prepare_send(
+        ctx: Context<PrepareSend>,
+        params: PrepareSendParams,
+    ) -> Result<Vec<LzAccount>> {
+        PrepareSend::apply(&ctx, &params)
+    }
+
+    // public instruction returning the Store's ball, mode, sent/received totals, recent remote
+    // eids and metadata.
+    pub fn get_stats(ctx: Context<GetStats>) -> Result<StoreStats> {
+        GetStats::apply(&ctx)
+    }
+
+    // public instruction returning the message and options `send` would pass to the Endpoint
+    // for the same params, without sending anything. Meant to be simulated.
+    pub fn preview_send(
+        ctx: Context<PreviewSend>,
+        params: SendMessageParams,
+    ) -> Result<SendPreview> {
+        PreviewSend::apply(&ctx, &params)
+    }
+
     // public instruction to send a message to a cross-chain peer.
-    pub fn send(mut ctx: Context<Send>, params: SendMessageParams) -> Result<()> {
+    pub fn send(mut ctx: Context<Send>, params: SendMessageParams) -> Result<MessagingReceipt> {
         Send::apply(&mut ctx, &params)
     }
 
+    // public instruction to send the same ball to several cross-chain peers at once.
+    pub fn send_multi(
+        mut ctx: Context<SendMulti>,
+        params: SendMultiParams,
+    ) -> Result<Vec<MessagingReceipt>> {
+        SendMulti::apply(&mut ctx, &params)
+    }
+
+    // public instruction sending a ping with the peer's stored defaults, priced from the
+    // eid's FeeQuoteCache.
+    pub fn send_ping(
+        mut ctx: Context<SendPing>,
+        params: SendPingParams,
+    ) -> Result<MessagingReceipt> {
+        SendPing::apply(&mut ctx, &params)
+    }
+
+    // public instruction creating the signer's own UserBall for `send_user_ping`.
+    pub fn init_user_ball(mut ctx: Context<InitUserBall>) -> Result<()> {
+        InitUserBall::apply(&mut ctx)
+    }
+
+    // public instruction sending the signer's UserBall, with their key as the origin caller
+    // the answer is routed back by.
+    pub fn send_user_ping(
+        mut ctx: Context<SendUserPing>,
+        params: SendUserPingParams,
+    ) -> Result<MessagingReceipt> {
+        SendUserPing::apply(&mut ctx, &params)
+    }
+
+    // public instruction sending `count` consecutive pings to one peer, for load tests. The
+    // remaining accounts (quote accounts first with AUTO_NATIVE_FEE) are reused by every ping.
+    pub fn ping_many(
+        mut ctx: Context<PingMany>,
+        params: PingManyParams,
+    ) -> Result<Vec<MessagingReceipt>> {
+        PingMany::apply(&mut ctx, &params)
+    }
+
+    // public instruction sending a return recorded by lz_receive in split-return mode; the
+    // caller pays the fee.
+    pub fn dispatch_return(
+        mut ctx: Context<DispatchReturn>,
+        params: DispatchReturnParams,
+    ) -> Result<()> {
+        DispatchReturn::apply(&mut ctx, &params)
+    }
+
+    // public instruction (a crank) re-quoting the return leg to `dst_eid` into the
+    // FeeQuoteCache lz_receive prices returns with. `remaining_accounts` are the Endpoint
+    // quote accounts for `dst_eid`.
+    pub fn refresh_quote(mut ctx: Context<RefreshQuote>, params: RefreshQuoteParams) -> Result<()> {
+        RefreshQuote::apply(&mut ctx, &params)
+    }
+
     // handler for processing incoming cross-chain messages and executing the LzReceive logic
     pub fn lz_receive(mut ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
         LzReceive::apply(&mut ctx, &params)
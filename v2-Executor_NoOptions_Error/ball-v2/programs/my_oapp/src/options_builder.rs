@@ -0,0 +1,74 @@
+// Builds LayerZero type-3 options from structured items, so callers don't have to assemble
+// the bytes off-chain. The encoding matches the TypeScript SDK's `Options` builder: amounts
+// are big-endian u128s, and an lzReceive or compose value of 0 is left out.
+// Anchor-free like `options_codec`; with the `onchain` feature `OptionItem` can be passed as
+// an instruction argument.
+
+use crate::options_codec::{
+    append_worker_option, executor_native_drop_option, executor_option,
+    EXECUTOR_COMPOSE_OPTION, EXECUTOR_LZ_RECEIVE_OPTION, EXECUTOR_ORDERED_EXECUTION_OPTION,
+};
+
+/// One executor option, as `combine` turns it into bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "onchain",
+    derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize)
+)]
+pub enum OptionItem {
+    /// Gas, and msg.value, for the destination's lzReceive
+    LzReceive { gas: u128, value: u128 },
+    /// `amount` of the destination's gas token sent to `receiver` on delivery
+    NativeDrop { amount: u128, receiver: [u8; 32] },
+    /// Gas, and msg.value, for the compose call at `index`
+    Compose { index: u16, gas: u128, value: u128 },
+    /// Deliver in nonce order
+    OrderedExecution,
+}
+
+/// Build an executor lzReceive option with `gas` and, when non-zero, `value`. Returns the
+/// worker option without the type-3 header, ready for `append_worker_option`.
+pub fn lz_receive_option(gas: u128, value: u128) -> Vec<u8> {
+    executor_option(EXECUTOR_LZ_RECEIVE_OPTION, &gas_and_value(gas, value))
+}
+
+/// Build an executor native-drop option: `amount` sent to `receiver` (an EVM address is
+/// left-padded). Without the type-3 header.
+pub fn native_drop_option(amount: u128, receiver: &[u8; 32]) -> Vec<u8> {
+    executor_native_drop_option(amount, receiver)
+}
+
+/// Build an executor compose option for compose `index` with `gas` and, when non-zero,
+/// `value`. Without the type-3 header.
+pub fn compose_option(index: u16, gas: u128, value: u128) -> Vec<u8> {
+    let mut data = index.to_be_bytes().to_vec();
+    data.extend(gas_and_value(gas, value));
+    executor_option(EXECUTOR_COMPOSE_OPTION, &data)
+}
+
+/// Build an executor ordered-execution option. Without the type-3 header.
+pub fn ordered_execution_option() -> Vec<u8> {
+    executor_option(EXECUTOR_ORDERED_EXECUTION_OPTION, &[])
+}
+
+/// Type-3 options holding `items` in order. No items give empty options, which request
+/// nothing beyond the enforced ones.
+pub fn combine(items: &[OptionItem]) -> Vec<u8> {
+    items.iter().fold(Vec::new(), |options, item| {
+        let worker_option = match item {
+            OptionItem::LzReceive { gas, value } => lz_receive_option(*gas, *value),
+            OptionItem::NativeDrop { amount, receiver } => native_drop_option(*amount, receiver),
+            OptionItem::Compose { index, gas, value } => compose_option(*index, *gas, *value),
+            OptionItem::OrderedExecution => ordered_execution_option(),
+        };
+        append_worker_option(options, &worker_option)
+    })
+}
+
+fn gas_and_value(gas: u128, value: u128) -> Vec<u8> {
+    let mut data = gas.to_be_bytes().to_vec();
+    if value != 0 {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data
+}
@@ -0,0 +1,233 @@
+// Structural checks for LayerZero type-3 options, the format `return_options` arrive in:
+// a 2-byte big-endian type header (3) followed by worker options, each a 1-byte worker id,
+// a 2-byte big-endian size and `size` bytes starting with the option type.
+// Like the message codec this module is anchor-free; with the `onchain` feature its error
+// converts into `MyOAppError::InvalidReturnOptions`.
+
+pub const TYPE_3: u16 = 3;
+// Worker ids that may appear in type-3 options
+pub const EXECUTOR_WORKER_ID: u8 = 1;
+pub const DVN_WORKER_ID: u8 = 2;
+// Worker id plus the 2-byte size
+const WORKER_HEADER_LEN: usize = 3;
+// Executor option types
+pub const EXECUTOR_LZ_RECEIVE_OPTION: u8 = 1; // gas and optional msg.value for lzReceive
+// Executor option asking the executor to send native gas token to an address on delivery
+pub const EXECUTOR_NATIVE_DROP_OPTION: u8 = 2;
+pub const EXECUTOR_COMPOSE_OPTION: u8 = 3; // compose index, gas and optional value
+pub const EXECUTOR_ORDERED_EXECUTION_OPTION: u8 = 4;
+
+/// Why a set of options was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionsError {
+    TooLong, // Options exceed the caller's cap
+    InvalidHeader, // Missing or non-type-3 header
+    Truncated, // A worker option declares more bytes than remain
+    EmptyOption, // A worker option without even an option type byte
+    UnknownWorker, // Worker id other than the executor or a DVN
+    Conflicting, // Duplicated executor option under the reject policy, or its merge overflows
+    MalformedOption, // An executor option's data does not fit its option type
+}
+
+/// Check that `options` are well-formed type-3 options of at most `max_len` bytes.
+/// Empty options are accepted: they request nothing beyond the enforced options.
+///
+/// # Arguments
+/// * `options` - The options bytes, e.g. an inbound message's return options
+/// * `max_len` - Largest accepted length
+pub fn validate_type3(options: &[u8], max_len: usize) -> Result<(), OptionsError> {
+    if options.len() > max_len {
+        return Err(OptionsError::TooLong);
+    }
+    if options.is_empty() {
+        return Ok(());
+    }
+    if options.len() < 2 || u16::from_be_bytes([options[0], options[1]]) != TYPE_3 {
+        return Err(OptionsError::InvalidHeader);
+    }
+    let mut rest = &options[2..];
+    while !rest.is_empty() {
+        if rest.len() < WORKER_HEADER_LEN {
+            return Err(OptionsError::Truncated);
+        }
+        let worker_id = rest[0];
+        let size = u16::from_be_bytes([rest[1], rest[2]]) as usize;
+        if worker_id != EXECUTOR_WORKER_ID && worker_id != DVN_WORKER_ID {
+            return Err(OptionsError::UnknownWorker);
+        }
+        if size == 0 {
+            return Err(OptionsError::EmptyOption);
+        }
+        rest = rest[WORKER_HEADER_LEN..].get(size..).ok_or(OptionsError::Truncated)?;
+    }
+    Ok(())
+}
+
+/// Build an executor native-drop option: `amount` of the destination's gas token sent to
+/// `receiver` (an EVM address is left-padded). Returns the worker option without the
+/// type-3 header, ready for `append_worker_option`.
+pub fn executor_native_drop_option(amount: u128, receiver: &[u8; 32]) -> Vec<u8> {
+    let mut data = amount.to_be_bytes().to_vec();
+    data.extend_from_slice(receiver);
+    executor_option(EXECUTOR_NATIVE_DROP_OPTION, &data)
+}
+
+/// Build an executor worker option of `option_type` carrying `data`, without the type-3
+/// header.
+pub(crate) fn executor_option(option_type: u8, data: &[u8]) -> Vec<u8> {
+    let size = (1 + data.len()) as u16;
+    let mut option = Vec::with_capacity(WORKER_HEADER_LEN + size as usize);
+    option.push(EXECUTOR_WORKER_ID);
+    option.extend_from_slice(&size.to_be_bytes());
+    option.push(option_type);
+    option.extend_from_slice(data);
+    option
+}
+
+/// Split type-3 `options` into their worker options, each including its worker header.
+/// Empty options have none.
+pub fn worker_options(options: &[u8]) -> Result<Vec<&[u8]>, OptionsError> {
+    if options.is_empty() {
+        return Ok(Vec::new());
+    }
+    if options.len() < 2 || u16::from_be_bytes([options[0], options[1]]) != TYPE_3 {
+        return Err(OptionsError::InvalidHeader);
+    }
+    let mut worker_options = Vec::new();
+    let mut rest = &options[2..];
+    while !rest.is_empty() {
+        if rest.len() < WORKER_HEADER_LEN {
+            return Err(OptionsError::Truncated);
+        }
+        let size = u16::from_be_bytes([rest[1], rest[2]]) as usize;
+        if size == 0 {
+            return Err(OptionsError::EmptyOption);
+        }
+        let end = WORKER_HEADER_LEN + size;
+        worker_options.push(rest.get(..end).ok_or(OptionsError::Truncated)?);
+        rest = &rest[end..];
+    }
+    Ok(worker_options)
+}
+
+/// Combine enforced and caller options like the OApp library does (enforced first), except
+/// that an executor option occurring more than once is merged into one by summing its gas
+/// and value, or rejected with `Conflicting` when `reject_conflicts` is set. Options the
+/// executor can't receive twice meaningfully are lzReceive, native drops to the same
+/// receiver, composes with the same index and ordered execution; everything else, DVN
+/// options included, is kept as is.
+///
+/// # Arguments
+/// * `enforced` - The peer's enforced options
+/// * `extra` - The options the caller passed
+/// * `reject_conflicts` - Fail instead of merging duplicated executor options
+pub fn merge_type3(
+    enforced: &[u8],
+    extra: &[u8],
+    reject_conflicts: bool,
+) -> Result<Vec<u8>, OptionsError> {
+    if enforced.is_empty() && extra.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut merged: Vec<Vec<u8>> = Vec::new();
+    for option in worker_options(enforced)?.into_iter().chain(worker_options(extra)?) {
+        let key = merge_key(option);
+        let duplicate = key.and_then(|key| merged.iter().position(|m| merge_key(m) == Some(key)));
+        match duplicate {
+            Some(_) if reject_conflicts => return Err(OptionsError::Conflicting),
+            Some(index) => merged[index] = merge_executor_option(&merged[index], option)?,
+            None => merged.push(option.to_vec()),
+        }
+    }
+    let mut options = TYPE_3.to_be_bytes().to_vec();
+    for option in merged {
+        options.extend_from_slice(&option);
+    }
+    Ok(options)
+}
+
+/// What makes two executor options the same for merging: the option type plus the drop
+/// receiver or compose index. `None` for options that are never merged.
+fn merge_key(option: &[u8]) -> Option<(u8, &[u8])> {
+    if option[0] != EXECUTOR_WORKER_ID {
+        return None;
+    }
+    let (option_type, data) = (option[WORKER_HEADER_LEN], &option[WORKER_HEADER_LEN + 1..]);
+    match option_type {
+        EXECUTOR_LZ_RECEIVE_OPTION | EXECUTOR_ORDERED_EXECUTION_OPTION => Some((option_type, &[])),
+        EXECUTOR_NATIVE_DROP_OPTION => data.get(16..).map(|receiver| (option_type, receiver)),
+        EXECUTOR_COMPOSE_OPTION => data.get(..2).map(|index| (option_type, index)),
+        _ => None,
+    }
+}
+
+/// Merge two executor options with the same `merge_key`.
+fn merge_executor_option(first: &[u8], second: &[u8]) -> Result<Vec<u8>, OptionsError> {
+    let option_type = first[WORKER_HEADER_LEN];
+    let (a, b) = (&first[WORKER_HEADER_LEN + 1..], &second[WORKER_HEADER_LEN + 1..]);
+    let data = match option_type {
+        EXECUTOR_LZ_RECEIVE_OPTION => sum_amounts(a, b, 2)?,
+        EXECUTOR_NATIVE_DROP_OPTION => {
+            // Same receiver, so only the amounts add up
+            let mut data = sum_amounts(&a[..16], &b[..16], 1)?;
+            data.extend_from_slice(&a[16..]);
+            data
+        }
+        EXECUTOR_COMPOSE_OPTION => {
+            let mut data = a[..2].to_vec();
+            data.extend(sum_amounts(&a[2..], &b[2..], 2)?);
+            data
+        }
+        // Ordered execution carries nothing to add up
+        _ => a.to_vec(),
+    };
+    Ok(executor_option(option_type, &data))
+}
+
+/// Add up two lists of one to `max` big-endian u128 amounts; a missing trailing amount
+/// (e.g. an lzReceive option without value) counts as zero.
+fn sum_amounts(a: &[u8], b: &[u8], max: usize) -> Result<Vec<u8>, OptionsError> {
+    let count = a.len().max(b.len()) / 16;
+    let well_formed = |amounts: &[u8]| !amounts.is_empty() && amounts.len() % 16 == 0;
+    if !well_formed(a) || !well_formed(b) || count > max {
+        return Err(OptionsError::MalformedOption);
+    }
+    let amount = |amounts: &[u8], i: usize| {
+        amounts.get(i * 16..(i + 1) * 16).map_or(0, |word| {
+            u128::from_be_bytes(word.try_into().expect("16-byte amount"))
+        })
+    };
+    let mut sums = Vec::with_capacity(count * 16);
+    for i in 0..count {
+        let sum = amount(a, i).checked_add(amount(b, i)).ok_or(OptionsError::Conflicting)?;
+        sums.extend_from_slice(&sum.to_be_bytes());
+    }
+    Ok(sums)
+}
+
+/// Append a worker option to type-3 `options`, adding the header when `options` is empty.
+pub fn append_worker_option(mut options: Vec<u8>, worker_option: &[u8]) -> Vec<u8> {
+    if options.is_empty() {
+        options.extend_from_slice(&TYPE_3.to_be_bytes());
+    }
+    options.extend_from_slice(worker_option);
+    options
+}
+
+#[cfg(feature = "onchain")]
+impl From<OptionsError> for crate::errors::MyOAppError {
+    fn from(error: OptionsError) -> Self {
+        match error {
+            OptionsError::Conflicting => crate::errors::MyOAppError::ConflictingOptions,
+            _ => crate::errors::MyOAppError::InvalidReturnOptions,
+        }
+    }
+}
+
+// Lets instructions keep using `?` on validation results
+#[cfg(feature = "onchain")]
+impl From<OptionsError> for anchor_lang::error::Error {
+    fn from(error: OptionsError) -> Self {
+        crate::errors::MyOAppError::from(error).into()
+    }
+}
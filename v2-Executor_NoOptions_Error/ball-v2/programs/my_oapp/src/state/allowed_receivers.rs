@@ -0,0 +1,42 @@
+use crate::{errors::MyOAppError, *};
+
+// Override receivers a send may name per destination eid
+pub const MAX_ALLOWED_RECEIVERS: usize = 4;
+
+// Receivers other than the peer address that sends to one eid may target through
+// `SendMessageParams.receiver_override`, e.g. a staging contract on the same chain. Edited by
+// add_allowed_receiver / remove_allowed_receiver.
+#[account]
+pub struct AllowedReceivers {
+    pub bump: u8,
+    pub receivers: [Option<[u8; 32]>; MAX_ALLOWED_RECEIVERS],
+}
+
+impl AllowedReceivers {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    pub fn contains(&self, receiver: &[u8; 32]) -> bool {
+        self.receivers.contains(&Some(*receiver))
+    }
+
+    /// Allow `receiver`; adding one that is already listed does nothing.
+    pub fn add(&mut self, receiver: [u8; 32]) -> Result<()> {
+        if self.contains(&receiver) {
+            return Ok(());
+        }
+        let slot = self
+            .receivers
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(MyOAppError::TooManyReceivers)?;
+        *slot = Some(receiver);
+        Ok(())
+    }
+
+    /// Stop allowing `receiver`; removing one that isn't listed does nothing.
+    pub fn remove(&mut self, receiver: &[u8; 32]) {
+        if let Some(slot) = self.receivers.iter_mut().find(|slot| **slot == Some(*receiver)) {
+            *slot = None;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::*;
+
+// Ball transitions kept per Store
+pub const BALL_HISTORY_LEN: usize = 32;
+// Borsh layout offsets, so one entry can be written without loading the whole ring
+const NEXT_INDEX_OFFSET: usize = 8 + 1;
+const ENTRIES_OFFSET: usize = NEXT_INDEX_OFFSET + 2;
+const ENTRY_LEN: usize = 32 + 4 + 1 + 8;
+
+/// Whether a history entry's ball left or reached this chain.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum BallDirection {
+    #[default]
+    Sent,
+    Received,
+}
+
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct BallHistoryEntry {
+    pub ball: [u8; 32],
+    pub eid: u32, // Destination of a sent ball, source of a received one
+    pub direction: BallDirection,
+    pub slot: u64,
+}
+
+// The last BALL_HISTORY_LEN balls sent by `send` or lz_receive's return and applied by
+// lz_receive, for UIs that can't rely on RPC log retention. Created by `init_history`;
+// until then nothing is recorded.
+#[account]
+#[derive(Default)]
+pub struct BallHistory {
+    pub bump: u8,
+    // Slot the next entry is written to; the oldest entry once the ring is full
+    pub next_index: u8,
+    // Entries written so far, up to BALL_HISTORY_LEN
+    pub len: u8,
+    pub entries: [BallHistoryEntry; BALL_HISTORY_LEN],
+}
+
+impl BallHistory {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    /// Append `entry` to the ring in `history`, overwriting the oldest entry when full. Does
+    /// nothing while the account hasn't been created by `init_history`.
+    pub fn record(history: &AccountInfo, entry: &BallHistoryEntry) -> Result<()> {
+        if history.owner != &crate::ID || history.data_is_empty() {
+            return Ok(());
+        }
+        let mut data = history.try_borrow_mut_data()?;
+        require!(
+            data.len() >= ENTRIES_OFFSET + BALL_HISTORY_LEN * ENTRY_LEN
+                && data[..8] == *Self::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let index = data[NEXT_INDEX_OFFSET] as usize % BALL_HISTORY_LEN;
+        let at = ENTRIES_OFFSET + index * ENTRY_LEN;
+        entry.serialize(&mut &mut data[at..at + ENTRY_LEN])?;
+        data[NEXT_INDEX_OFFSET] = ((index + 1) % BALL_HISTORY_LEN) as u8;
+        let len = &mut data[NEXT_INDEX_OFFSET + 1];
+        *len = len.saturating_add(1).min(BALL_HISTORY_LEN as u8);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    fn entry(slot: u64) -> BallHistoryEntry {
+        BallHistoryEntry {
+            ball: [slot as u8; 32],
+            eid: 30101,
+            direction: BallDirection::Sent,
+            slot,
+        }
+    }
+
+    fn record_all(account: &mut TestAccount, entries: impl Iterator<Item = u64>) {
+        let info = account.info();
+        for slot in entries {
+            BallHistory::record(&info, &entry(slot)).unwrap();
+        }
+    }
+
+    fn initialized() -> TestAccount {
+        let mut data = Vec::new();
+        BallHistory { bump: 255, ..Default::default() }
+            .try_serialize(&mut data)
+            .unwrap();
+        data.resize(BallHistory::SIZE, 0);
+        TestAccount::new(Pubkey::new_unique(), crate::ID, false, data)
+    }
+
+    /// The PDA before `init_history`: owned by the System program
+    fn uninitialized(data: Vec<u8>) -> TestAccount {
+        TestAccount::new(Pubkey::new_unique(), anchor_lang::system_program::ID, false, data)
+    }
+
+    #[test]
+    fn ring_wraps_over_the_oldest_entry() {
+        let mut account = initialized();
+        record_all(&mut account, 1..=BALL_HISTORY_LEN as u64 + 2);
+        let history = BallHistory::try_deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!((history.bump, history.next_index, history.len), (255, 2, 32));
+        // Entries 33 and 34 overwrote 1 and 2; 3 is now the oldest
+        let slots: Vec<u64> = history.entries.iter().map(|entry| entry.slot).collect();
+        assert_eq!(&slots[..3], &[33, 34, 3]);
+        assert_eq!(slots[BALL_HISTORY_LEN - 1], 32);
+        assert_eq!(history.entries[0].ball, [33; 32]);
+    }
+
+    #[test]
+    fn partly_filled_ring_counts_its_entries() {
+        let mut account = initialized();
+        record_all(&mut account, 1..=3);
+        let history = BallHistory::try_deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!((history.next_index, history.len), (3, 3));
+        assert!(history.entries[2].direction == BallDirection::Sent);
+        assert_eq!(history.entries[2].eid, 30101);
+    }
+
+    #[test]
+    fn nothing_is_recorded_before_init_history() {
+        let mut account = uninitialized(Vec::new());
+        record_all(&mut account, 1..=3);
+        assert!(account.data.is_empty());
+        let mut account = uninitialized(vec![0; 16]);
+        record_all(&mut account, 1..=3);
+        assert_eq!(account.data, vec![0; 16]);
+    }
+}
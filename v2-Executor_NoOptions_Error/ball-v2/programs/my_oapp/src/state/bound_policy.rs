@@ -0,0 +1,11 @@
+use crate::*;
+
+/// What lz_receive does with an inbound ball outside the Store's `min_ball..=max_ball`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum BoundPolicy {
+    /// Apply the nearest bound instead, emitting `BallClamped`
+    #[default]
+    Clamp,
+    /// Clear the message without applying it, emitting `BallOutOfBoundsDropped`
+    Reject,
+}
@@ -0,0 +1,12 @@
+use crate::*;
+
+// Remote chains the Store remembers touching the ball, in `Store.touches`
+pub const EID_TOUCH_LEN: usize = 8;
+
+/// A send to, or a receive from, a remote eid, as kept in the Store's touch ring.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct EidTouch {
+    pub eid: u32,
+    pub direction: BallDirection,
+    pub slot: u64,
+}
@@ -0,0 +1,26 @@
+use crate::*;
+
+// Endpoint quote for a return leg to one eid, kept fresh by the permissionless
+// `refresh_quote` crank so lz_receive can price returns without a quote CPI of its own.
+#[account]
+pub struct FeeQuoteCache {
+    pub bump: u8,
+    pub dst_eid: u32,
+    // Native fee the Endpoint quoted for a vanilla return message and the return options
+    pub native_fee: u64,
+    // Slot the quote was taken in
+    pub quoted_slot: u64,
+    // Slots after `quoted_slot` the quote is still used (copied from
+    // `Store.fee_quote_max_age_slots` when refreshed; 0 = never used)
+    pub max_age_slots: u64,
+}
+
+impl FeeQuoteCache {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    /// The cached fee while it is at most `max_age_slots` old at `slot`, otherwise None.
+    pub fn fresh_fee(&self, slot: u64) -> Option<u64> {
+        let age = slot.saturating_sub(self.quoted_slot);
+        (self.max_age_slots != 0 && age <= self.max_age_slots).then_some(self.native_fee)
+    }
+}
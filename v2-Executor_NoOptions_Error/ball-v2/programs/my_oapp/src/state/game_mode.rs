@@ -0,0 +1,11 @@
+use crate::*;
+
+/// What the Store's ball holds and how each leg changes it.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum GameMode {
+    /// A ball decremented on every leg until it is depleted (ABA messages)
+    #[default]
+    Ball,
+    /// A counter incremented on every leg that never runs out (`COUNTER_TYPE` messages)
+    Counter,
+}
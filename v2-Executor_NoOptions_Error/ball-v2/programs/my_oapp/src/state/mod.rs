@@ -1,5 +1,37 @@
 pub mod store;
 mod peer_config;
+mod nonce_window;
+mod pending_return;
+mod return_accounts;
+mod underflow_policy;
+mod receipt;
+mod game_mode;
+mod send_permission;
+mod send_allowlist;
+mod allowed_receivers;
+mod fee_quote_cache;
+mod return_fee_source;
+mod ball_history;
+mod user_ball;
+mod bound_policy;
+mod eid_touch;
+mod send_pda_bumps;
 
 pub use store::*; 
 pub use peer_config::*;
+pub use nonce_window::*;
+pub use pending_return::*;
+pub use return_accounts::*;
+pub use underflow_policy::*;
+pub use receipt::*;
+pub use game_mode::*;
+pub use send_permission::*;
+pub use send_allowlist::*;
+pub use allowed_receivers::*;
+pub use fee_quote_cache::*;
+pub use return_fee_source::*;
+pub use ball_history::*;
+pub use user_ball::*;
+pub use bound_policy::*;
+pub use eid_touch::*;
+pub use send_pda_bumps::*;
@@ -0,0 +1,127 @@
+use crate::{consts::*, *};
+
+const BITMAP_WORDS: usize = MAX_NONCE_WINDOW as usize / 64;
+
+/// Nonces recently applied from one peer: the highest one plus a bitmap of the
+/// `MAX_NONCE_WINDOW` nonces at and below it (bit `i` is nonce `highest - i`).
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct NonceWindow {
+    pub highest: u64,
+    pub bitmap: [u64; BITMAP_WORDS],
+}
+
+impl NonceWindow {
+    /// Whether `nonce` is one of the last `window` nonces and was already applied.
+    /// Nonces older than the window are reported as new.
+    pub fn contains(&self, nonce: u64, window: u16) -> bool {
+        match self.highest.checked_sub(nonce) {
+            Some(distance) if distance < u64::from(window) => {
+                self.bitmap[distance as usize / 64] & (1 << (distance % 64)) != 0
+            },
+            _ => false,
+        }
+    }
+
+    /// Record `nonce` as applied. Nonces older than the window are not recorded.
+    pub fn insert(&mut self, nonce: u64, window: u16) {
+        if nonce > self.highest {
+            self.shift(nonce - self.highest);
+            self.highest = nonce;
+            self.bitmap[0] |= 1;
+        } else if self.highest - nonce < u64::from(window) {
+            let distance = self.highest - nonce;
+            self.bitmap[distance as usize / 64] |= 1 << (distance % 64);
+        }
+    }
+
+    /// Move every recorded nonce `by` positions further from `highest`, dropping those that
+    /// fall out of the bitmap.
+    fn shift(&mut self, by: u64) {
+        if by >= u64::from(MAX_NONCE_WINDOW) {
+            self.bitmap = [0; BITMAP_WORDS];
+            return;
+        }
+        let words = by as usize / 64;
+        let bits = (by % 64) as u32;
+        for i in (0..BITMAP_WORDS).rev() {
+            let mut word = 0;
+            if i >= words {
+                word = self.bitmap[i - words] << bits;
+                if bits > 0 && i > words {
+                    word |= self.bitmap[i - words - 1] >> (64 - bits);
+                }
+            }
+            self.bitmap[i] = word;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: u16 = 128;
+
+    /// A window that has applied `nonces` in order.
+    fn applied(nonces: impl IntoIterator<Item = u64>) -> NonceWindow {
+        let mut window = NonceWindow::default();
+        for nonce in nonces {
+            window.insert(nonce, WINDOW);
+        }
+        window
+    }
+
+    #[test]
+    fn retry_of_last_nonce_is_seen() {
+        let window = applied(1..=5);
+        assert!(window.contains(5, WINDOW));
+        assert!(!window.contains(6, WINDOW));
+    }
+
+    #[test]
+    fn retry_of_older_nonce_in_window_is_seen() {
+        // Unordered delivery: 3 arrives after 4 and 5, 2 never does
+        let window = applied([1, 4, 5, 3]);
+        for nonce in [1, 3, 4, 5] {
+            assert!(window.contains(nonce, WINDOW), "nonce {nonce}");
+        }
+        assert!(!window.contains(2, WINDOW));
+    }
+
+    #[test]
+    fn nonces_across_bitmap_words_are_kept() {
+        // 70 nonces span the first two bitmap words
+        let window = applied(1..=70);
+        assert!((1..=70).all(|nonce| window.contains(nonce, WINDOW)));
+        let window = applied([1, 100]);
+        assert!(window.contains(1, WINDOW));
+        assert!(!window.contains(2, WINDOW));
+    }
+
+    #[test]
+    fn fresh_nonce_after_wraparound_is_new() {
+        let mut window = applied(1..=3);
+        // Jumping a full window ahead drops everything recorded so far
+        window.insert(3 + u64::from(WINDOW), WINDOW);
+        assert!(window.contains(3 + u64::from(WINDOW), WINDOW));
+        // Nonces that fell out of the window are processed normally again
+        for nonce in 1..=3 {
+            assert!(!window.contains(nonce, WINDOW), "nonce {nonce}");
+        }
+        // A fresh nonce inside the new window is not mistaken for a retry
+        assert!(!window.contains(2 + u64::from(WINDOW), WINDOW));
+        // A jump past the whole bitmap clears it as well
+        window.insert(u64::MAX, WINDOW);
+        assert!(window.contains(u64::MAX, WINDOW));
+        assert!(!window.contains(3 + u64::from(WINDOW), WINDOW));
+    }
+
+    #[test]
+    fn nonces_older_than_the_window_are_not_recorded() {
+        let mut window = applied([1000]);
+        window.insert(1000 - u64::from(WINDOW), WINDOW);
+        assert!(!window.contains(1000 - u64::from(WINDOW), WINDOW));
+        window.insert(1000 - u64::from(WINDOW) + 1, WINDOW);
+        assert!(window.contains(1000 - u64::from(WINDOW) + 1, WINDOW));
+    }
+}
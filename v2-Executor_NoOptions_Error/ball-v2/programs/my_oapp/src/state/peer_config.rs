@@ -1,17 +1,161 @@
-use crate::*;
+use crate::{consts::*, errors::MyOAppError, *};
 
 pub const ENFORCED_OPTIONS_SEND_MAX_LEN: usize = 512;
 pub const ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN: usize = 1024;
+// Senders accepted from a peer's eid besides `peer_address`, e.g. while an old and a new
+// EVM contract are both live
+pub const MAX_EXTRA_SENDERS: usize = 3;
+// Client order ids remembered per peer for send deduplication
+pub const CLIENT_ORDER_WINDOW: usize = 8;
 
 #[account]
+#[derive(InitSpace)]
 pub struct PeerConfig {
     pub peer_address: [u8; 32],
     pub enforced_options: EnforcedOptions,
     pub bump: u8,
+    // Last application sequence number received from this peer (0 = none yet).
+    pub last_seen_seq: u64,
+    // Peer exchanges 16-byte balls (`encode_aba_compact`) instead of the full ABI layout.
+    pub compact: bool,
+    // Nonces recently applied from this peer, so Executor retries can be skipped.
+    pub processed_nonces: NonceWindow,
+    // Highest nonce applied from this peer (0 = none yet). Tracked whether or not ordering
+    // is enforced, so the flag can be switched on mid-stream.
+    pub last_processed_nonce: u64,
+    // Reject inbound nonces that don't directly follow `last_processed_nonce`.
+    pub enforce_ordered: bool,
+    // Reject inbound messages from this peer before Clear, so they stay retriable.
+    pub paused_inbound: bool,
+    // Ball last sent to this peer by `send`, so its reply can be checked (None = nothing
+    // outstanding). Only compared while `last_sent_epoch` matches `Store.ball_epoch`.
+    pub last_sent_ball: Option<[u8; 32]>,
+    pub last_sent_epoch: u64,
+    // Reject, rather than only report, replies that don't match `last_sent_ball`. Rejected
+    // replies are cleared without being applied.
+    pub strict_consistency: bool,
+    // Receiver for return legs while it differs from `peer_address`, e.g. during a contract
+    // migration (None = return to `peer_address`). Inbound senders are still checked
+    // against `peer_address`.
+    pub return_receiver: Option<[u8; 32]>,
+    // Messages cleared from this peer and balls sent to it by `send` or lz_receive's
+    // return. Only reset by `PeerConfigParam::ResetCounters`.
+    pub inbound_count: u64,
+    pub outbound_count: u64,
+    // Native gas token the executor drops to `return_drop_receiver` when a return leg is
+    // delivered (0 = no drop).
+    pub return_drop_amount: u128,
+    pub return_drop_receiver: [u8; 32],
+    // Other addresses lz_receive accepts from this eid. Sends still go to `peer_address`.
+    pub extra_senders: [Option<[u8; 32]>; MAX_EXTRA_SENDERS],
+    // Client order ids of the latest sends to this peer; the oldest is overwritten at
+    // `next_client_order_slot`.
+    pub recent_client_orders: [u64; CLIENT_ORDER_WINDOW],
+    pub next_client_order_slot: u8,
+    // Slots that must pass between sends to this peer (0 = no cooldown), and the slot of
+    // the last one. lz_receive's return leg neither checks nor updates them.
+    pub min_send_interval_slots: u64,
+    pub last_send_slot: u64,
+    // Options and return options `send_ping` sends with, set by `PeerConfigParam::PingDefaults`.
+    #[max_len(ENFORCED_OPTIONS_SEND_MAX_LEN)]
+    pub default_options: Vec<u8>,
+    #[max_len(MAX_RETURN_OPTIONS_LEN)]
+    pub default_return_options: Vec<u8>,
+    // Endpoint PDA bumps for the return leg, found on the first return (None = not yet).
+    pub send_pda_bumps: Option<SendPdaBumps>,
 }
 
 impl PeerConfig {
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+    // Room for every option field at its longest. Peers created while SIZE was
+    // `size_of::<Self>()`, which counted only the Vec headers, are grown by set_peer_config.
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
+
+    /// Address the return leg is sent to: the override if set, otherwise the peer.
+    pub fn return_address(&self) -> [u8; 32] {
+        self.return_receiver.unwrap_or(self.peer_address)
+    }
+
+    /// Whether `sender` is the peer address or one of the extra senders.
+    pub fn is_sender(&self, sender: &[u8; 32]) -> bool {
+        self.peer_address == *sender || self.extra_senders.contains(&Some(*sender))
+    }
+
+    /// Accept `sender` in addition to the current ones.
+    pub fn add_sender(&mut self, sender: [u8; 32]) -> Result<()> {
+        if self.is_sender(&sender) {
+            return Ok(());
+        }
+        let slot = self
+            .extra_senders
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(MyOAppError::TooManySenders)?;
+        *slot = Some(sender);
+        Ok(())
+    }
+
+    /// Stop accepting `sender`. Removing the peer address promotes the first extra sender
+    /// in its place; the last remaining sender can't be removed.
+    pub fn remove_sender(&mut self, sender: &[u8; 32]) -> Result<()> {
+        if let Some(slot) = self.extra_senders.iter_mut().find(|slot| **slot == Some(*sender)) {
+            *slot = None;
+        } else if self.peer_address == *sender {
+            let next = self
+                .extra_senders
+                .iter_mut()
+                .find_map(|slot| slot.take())
+                .ok_or(MyOAppError::LastSender)?;
+            self.peer_address = next;
+        }
+        Ok(())
+    }
+
+    /// Fail with `PeerPaused` while inbound messages from this peer are paused.
+    pub fn require_inbound_open(&self) -> Result<()> {
+        require!(!self.paused_inbound, MyOAppError::PeerPaused);
+        Ok(())
+    }
+
+    /// Record `nonce` as applied. Ordered peers must deliver nonce last + 1, failing with
+    /// `OutOfOrder` otherwise; with nothing applied yet (last == 0) any nonce is accepted, so
+    /// ordering can be enabled on a channel already in use.
+    pub fn accept_nonce(&mut self, nonce: u64) -> Result<()> {
+        if self.enforce_ordered && self.last_processed_nonce != 0 {
+            require!(nonce == self.last_processed_nonce.saturating_add(1), MyOAppError::OutOfOrder);
+        }
+        self.last_processed_nonce = self.last_processed_nonce.max(nonce);
+        Ok(())
+    }
+
+    /// Start a send at `slot`, failing with `CooldownActive` while `min_send_interval_slots`
+    /// haven't passed since the last one. The slot exactly one interval later is allowed.
+    pub fn start_send(&mut self, slot: u64) -> Result<()> {
+        if self.min_send_interval_slots != 0 && self.last_send_slot != 0 {
+            let ready_at = self.last_send_slot.saturating_add(self.min_send_interval_slots);
+            if slot < ready_at {
+                msg!("Cooldown active: {} slots remaining", ready_at - slot);
+                return err!(MyOAppError::CooldownActive);
+            }
+        }
+        self.last_send_slot = slot;
+        Ok(())
+    }
+
+    /// Remember a send's client order id, rejecting one among the last CLIENT_ORDER_WINDOW
+    /// sent to this peer. Zero opts out of deduplication and is never remembered.
+    pub fn record_client_order(&mut self, client_order_id: u64) -> Result<()> {
+        if client_order_id == 0 {
+            return Ok(());
+        }
+        require!(
+            !self.recent_client_orders.contains(&client_order_id),
+            MyOAppError::DuplicateClientOrder
+        );
+        let slot = self.next_client_order_slot as usize % CLIENT_ORDER_WINDOW;
+        self.recent_client_orders[slot] = client_order_id;
+        self.next_client_order_slot = ((slot + 1) % CLIENT_ORDER_WINDOW) as u8;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Default, AnchorSerialize, AnchorDeserialize, InitSpace)]
@@ -20,6 +164,9 @@ pub struct EnforcedOptions {
     pub send: Vec<u8>,
     #[max_len(ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN)]
     pub send_and_call: Vec<u8>,
+    // Enforced options for the return leg lz_receive sends; falls back to `send` when empty
+    #[max_len(ENFORCED_OPTIONS_SEND_MAX_LEN)]
+    pub return_send: Vec<u8>,
 }
 
 impl EnforcedOptions {
@@ -31,12 +178,156 @@ impl EnforcedOptions {
         }
     }
 
+    /// Combine the enforced options with the caller's. An executor option present more than
+    /// once is merged into one, or rejected when `reject_conflicts` is set, rather than
+    /// repeated.
     pub fn combine_options(
         &self,
         compose_msg: &Option<Vec<u8>>,
         extra_options: &Vec<u8>,
+        reject_conflicts: bool,
     ) -> Result<Vec<u8>> {
         let enforced_options = self.get_enforced_options(compose_msg);
+        Ok(crate::options_codec::merge_type3(&enforced_options, extra_options, reject_conflicts)?)
+    }
+
+    /// Combine the return-leg enforced options (or the send ones, if none are set) with the
+    /// options the inbound message asked for.
+    pub fn combine_return_options(&self, extra_options: &Vec<u8>) -> Result<Vec<u8>> {
+        let enforced_options = if self.return_send.is_empty() {
+            self.send.clone()
+        } else {
+            self.return_send.clone()
+        };
         oapp::options::combine_options(enforced_options, extra_options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    fn peer() -> PeerConfig {
+        // An all-zero account reads as a peer with nothing configured
+        PeerConfig::deserialize(&mut &vec![0u8; PeerConfig::SIZE][..]).unwrap()
+    }
+
+    fn assert_err(result: Result<()>, expected: MyOAppError) {
+        assert_eq!(
+            ProgramError::from(result.unwrap_err()),
+            ProgramError::from(anchor_lang::error::Error::from(expected))
+        );
+    }
+
+    #[test]
+    fn ordered_peer_accepts_any_first_nonce_then_the_next() {
+        let mut peer = peer();
+        peer.enforce_ordered = true;
+        peer.accept_nonce(5).unwrap();
+        peer.accept_nonce(6).unwrap();
+        assert_eq!(peer.last_processed_nonce, 6);
+    }
+
+    #[test]
+    fn ordered_peer_rejects_gaps_and_replays() {
+        let mut peer = peer();
+        peer.enforce_ordered = true;
+        peer.accept_nonce(1).unwrap();
+        assert_err(peer.accept_nonce(3), MyOAppError::OutOfOrder);
+        assert_err(peer.accept_nonce(1), MyOAppError::OutOfOrder);
+        // A rejected nonce changes nothing, so the missing one still fits
+        assert_eq!(peer.last_processed_nonce, 1);
+        peer.accept_nonce(2).unwrap();
+    }
+
+    #[test]
+    fn unordered_peer_tracks_the_highest_nonce() {
+        let mut peer = peer();
+        for nonce in [3, 1, 7, 2] {
+            peer.accept_nonce(nonce).unwrap();
+        }
+        assert_eq!(peer.last_processed_nonce, 7);
+    }
+
+    #[test]
+    fn ordering_can_be_toggled_mid_stream() {
+        let mut peer = peer();
+        peer.accept_nonce(1).unwrap();
+        peer.accept_nonce(4).unwrap();
+        // Switched on, the next nonce follows the highest one seen while it was off
+        peer.enforce_ordered = true;
+        assert_err(peer.accept_nonce(2), MyOAppError::OutOfOrder);
+        peer.accept_nonce(5).unwrap();
+        // Switched off again, gaps are accepted
+        peer.enforce_ordered = false;
+        peer.accept_nonce(9).unwrap();
+        assert_eq!(peer.last_processed_nonce, 9);
+    }
+
+    #[test]
+    fn paused_peer_keeps_its_nonce_for_after_unpausing() {
+        let mut peer = peer();
+        peer.enforce_ordered = true;
+        peer.accept_nonce(1).unwrap();
+        peer.paused_inbound = true;
+        // lz_receive checks the pause before anything else, so nonce 2 isn't used up
+        assert_err(peer.require_inbound_open(), MyOAppError::PeerPaused);
+        assert_eq!(peer.last_processed_nonce, 1);
+        // Sends to the peer are unaffected
+        peer.start_send(100).unwrap();
+
+        peer.paused_inbound = false;
+        peer.require_inbound_open().unwrap();
+        peer.accept_nonce(2).unwrap();
+    }
+
+    #[test]
+    fn retried_client_order_is_rejected() {
+        let mut peer = peer();
+        peer.record_client_order(42).unwrap();
+        assert_err(peer.record_client_order(42), MyOAppError::DuplicateClientOrder);
+        // A different id goes through, and zero is never deduplicated
+        peer.record_client_order(43).unwrap();
+        peer.record_client_order(0).unwrap();
+        peer.record_client_order(0).unwrap();
+    }
+
+    #[test]
+    fn oldest_client_order_is_evicted() {
+        let mut peer = peer();
+        for id in 1..=CLIENT_ORDER_WINDOW as u64 {
+            peer.record_client_order(id).unwrap();
+        }
+        assert_err(peer.record_client_order(1), MyOAppError::DuplicateClientOrder);
+        // One more send pushes id 1 out of the window, but not id 2
+        peer.record_client_order(100).unwrap();
+        assert_err(peer.record_client_order(2), MyOAppError::DuplicateClientOrder);
+        peer.record_client_order(1).unwrap();
+    }
+
+    #[test]
+    fn longest_options_fit_in_size() {
+        let mut peer = peer();
+        peer.enforced_options = EnforcedOptions {
+            send: vec![1; ENFORCED_OPTIONS_SEND_MAX_LEN],
+            send_and_call: vec![1; ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN],
+            return_send: vec![1; ENFORCED_OPTIONS_SEND_MAX_LEN],
+        };
+        peer.default_options = vec![1; ENFORCED_OPTIONS_SEND_MAX_LEN];
+        peer.default_return_options = vec![1; MAX_RETURN_OPTIONS_LEN];
+        peer.last_sent_ball = Some([1; 32]);
+        peer.return_receiver = Some([1; 32]);
+        peer.extra_senders = [Some([1; 32]); MAX_EXTRA_SENDERS];
+        peer.send_pda_bumps = Some(SendPdaBumps {
+            receiver: [1; 32],
+            send_library_config: 1,
+            default_send_library_config: 1,
+            endpoint_settings: 1,
+            nonce: 1,
+        });
+        let mut data = Vec::new();
+        peer.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PeerConfig::SIZE);
+    }
+}
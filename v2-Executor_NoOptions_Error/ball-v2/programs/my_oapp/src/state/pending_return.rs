@@ -0,0 +1,42 @@
+use crate::*;
+use oapp::endpoint::instructions::SendParams;
+
+// A return leg that lz_receive did not send, either because it couldn't or because the Store
+// is in split-return mode. Created by lz_receive, closed by retry_return or dispatch_return.
+#[account]
+pub struct PendingReturn {
+    pub bump: u8,
+    // Account that paid the rent; refunded when the PDA is closed.
+    pub payer: Pubkey,
+    // Chain the return is addressed to (the inbound message's src_eid).
+    pub dst_eid: u32,
+    // Guid of the inbound message the return answers.
+    pub guid: [u8; 32],
+    // Encoded return message, ready for Endpoint::send.
+    pub message: Vec<u8>,
+    // Return options already combined with the peer's enforced options.
+    pub options: Vec<u8>,
+}
+
+impl PendingReturn {
+    pub fn space(message_len: usize, options_len: usize) -> usize {
+        8 + 1 + 32 + 4 + 32 + (4 + message_len) + (4 + options_len)
+    }
+
+    /// SendParams for the recorded return, addressed to the peer's current address.
+    pub fn send_params(
+        &self,
+        receiver: [u8; 32],
+        native_fee: u64,
+        lz_token_fee: u64,
+    ) -> SendParams {
+        SendParams {
+            dst_eid: self.dst_eid,
+            receiver,
+            message: self.message.clone(),
+            options: self.options.clone(),
+            native_fee,
+            lz_token_fee,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+use oapp::endpoint_cpi::LzAccount;
+
+// Endpoint::send accounts for the return leg, cached per peer by the admin. lz_receive_types only
+// sees the accounts listed in LzReceiveTypesAccounts, so it can't derive the send-library
+// accounts itself; it appends the cached list for the message's src_eid instead.
+#[account]
+pub struct ReturnAccounts {
+    pub bump: u8,
+    pub peers: Vec<PeerSendAccounts>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PeerSendAccounts {
+    pub eid: u32,
+    // In the order Endpoint::send expects. Signers (the fee payer) use Pubkey::default(),
+    // which the Executor replaces with itself.
+    pub accounts: Vec<LzAccount>,
+}
+
+impl ReturnAccounts {
+    const LZ_ACCOUNT_SIZE: usize = 32 + 1 + 1;
+
+    pub fn space(&self) -> usize {
+        8 + 1
+            + 4
+            + self
+                .peers
+                .iter()
+                .map(|peer| 4 + 4 + peer.accounts.len() * Self::LZ_ACCOUNT_SIZE)
+                .sum::<usize>()
+    }
+
+    pub fn get(&self, eid: u32) -> Option<&[LzAccount]> {
+        self.peers.iter().find(|peer| peer.eid == eid).map(|peer| peer.accounts.as_slice())
+    }
+
+    /// Replace the accounts cached for `eid`; an empty list removes the entry.
+    pub fn set(&mut self, eid: u32, accounts: Vec<LzAccount>) {
+        self.peers.retain(|peer| peer.eid != eid);
+        if !accounts.is_empty() {
+            self.peers.push(PeerSendAccounts { eid, accounts });
+        }
+    }
+}
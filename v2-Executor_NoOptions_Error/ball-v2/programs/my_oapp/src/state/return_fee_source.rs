@@ -0,0 +1,13 @@
+use crate::*;
+
+/// Where lz_receive took a return leg's native fee from.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ReturnFeeSource {
+    /// What the sender native-dropped to the Store, minus `RETURN_FEE_RESERVE`
+    NativeDrop,
+    /// A fresh `FeeQuoteCache` quote times `RETURN_FEE_MULTIPLIER`
+    CachedQuote,
+    /// `BASE_SOL_TO_ETH_FEE` times `RETURN_FEE_MULTIPLIER`, with the cache stale or missing
+    #[default]
+    Estimate,
+}
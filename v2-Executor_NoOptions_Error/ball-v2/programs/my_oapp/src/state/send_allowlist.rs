@@ -0,0 +1,41 @@
+use crate::{errors::MyOAppError, *};
+
+// Signers allowed to send while `Store.send_permission` is `Allowlist`
+pub const MAX_ALLOWLISTED_SENDERS: usize = 8;
+
+// Send allowlist, edited by add_allowed_sender / remove_allowed_sender. Only read while the
+// Store's send permission is `Allowlist`.
+#[account]
+pub struct SendAllowlist {
+    pub bump: u8,
+    pub senders: [Option<Pubkey>; MAX_ALLOWLISTED_SENDERS],
+}
+
+impl SendAllowlist {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    pub fn contains(&self, sender: &Pubkey) -> bool {
+        self.senders.contains(&Some(*sender))
+    }
+
+    /// Allow `sender`; adding one that is already listed does nothing.
+    pub fn add(&mut self, sender: Pubkey) -> Result<()> {
+        if self.contains(&sender) {
+            return Ok(());
+        }
+        let slot = self
+            .senders
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(MyOAppError::AllowlistFull)?;
+        *slot = Some(sender);
+        Ok(())
+    }
+
+    /// Stop allowing `sender`; removing one that isn't listed does nothing.
+    pub fn remove(&mut self, sender: &Pubkey) {
+        if let Some(slot) = self.senders.iter_mut().find(|slot| **slot == Some(*sender)) {
+            *slot = None;
+        }
+    }
+}
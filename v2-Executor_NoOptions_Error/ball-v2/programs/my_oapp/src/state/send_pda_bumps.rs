@@ -0,0 +1,13 @@
+use crate::*;
+
+/// Bumps of the endpoint PDAs a peer's return leg is sent with, cached by lz_receive so
+/// later returns check the send accounts with `create_program_address` instead of searching
+/// for each bump again. The nonce PDA depends on the receiver, so they only hold for it.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct SendPdaBumps {
+    pub receiver: [u8; 32],
+    pub send_library_config: u8,
+    pub default_send_library_config: u8,
+    pub endpoint_settings: u8,
+    pub nonce: u8,
+}
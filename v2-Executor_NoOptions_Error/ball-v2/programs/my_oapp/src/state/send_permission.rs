@@ -0,0 +1,14 @@
+use crate::*;
+
+/// Who may call `send` and `send_multi`, and so spend the peers' enforced options and
+/// decrement the shared ball. Quotes are always permissionless.
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum SendPermission {
+    /// Any signer
+    #[default]
+    Anyone,
+    /// Only the Store admin
+    AdminOnly,
+    /// The Store admin and the signers in the SendAllowlist PDA
+    Allowlist,
+}
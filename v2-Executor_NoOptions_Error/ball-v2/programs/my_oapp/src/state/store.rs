@@ -1,4 +1,5 @@
-use crate::*;
+use crate::{consts::*, errors::MyOAppError, *};
+use core::cmp::Ordering;
 use ethnum::U256;
 
 #[account]
@@ -11,6 +12,99 @@ pub struct Store {
     pub endpoint_program: Pubkey,
     // Current ball value.
     pub ball: [u8; 32],
+    // Hop count at which lz_receive stops sending the return leg (0 = unlimited).
+    pub max_hop_count: u16,
+    // Interpret the ball as int256 so decrements may go below zero.
+    pub signed_ball: bool,
+    // Sequence number stamped on the next outbound message. Starts at 1; 0 marks messages
+    // without a sequence number.
+    pub next_seq: u64,
+    // Reject inbound messages without the codec's MAGIC prefix and tag outbound ones.
+    pub require_magic: bool,
+    // Set once a GAME_OVER was sent or received; blocks `send` until a new game starts.
+    pub game_over: bool,
+    // Return sends lz_receive may still perform before an admin tops the budget up
+    // (None = unlimited).
+    pub max_auto_returns: Option<u64>,
+    // How many of each peer's most recent nonces are remembered to make retries no-ops
+    // (0 = disabled). Set at init, at most MAX_NONCE_WINDOW.
+    pub nonce_window: u16,
+    // Store lamports after the last return fee was settled. Anything above it (and above
+    // the rent-exempt minimum) was native-dropped by the sender to prepay the next return.
+    pub fee_baseline: u64,
+    // lz_receive only records the return in a PendingReturn; `dispatch_return` sends it, in
+    // the same transaction or later. Keeps the send accounts out of lz_receive.
+    pub split_return: bool,
+    // Bumped by every admin ball reset, so replies to balls sent before it aren't checked.
+    pub ball_epoch: u64,
+    // How `send` and lz_receive's return decrement a ball that would go out of range.
+    pub underflow_policy: UnderflowPolicy,
+    // Seconds a TTL-carrying message may arrive past its expiry, to absorb clock skew
+    // between chains.
+    pub ttl_grace_secs: u64,
+    // Local program that lz_receive queues every received ball for via Endpoint::send_compose
+    // (None = no compose).
+    pub composer: Option<Pubkey>,
+    // Last message lz_receive applied to the ball (all zero before the first one).
+    pub last_src_eid: u32,
+    pub last_nonce: u64,
+    pub last_guid: [u8; 32],
+    pub last_received_at: i64,
+    // Have lz_receive write a Receipt PDA per processed message (rent paid by the Executor).
+    pub write_receipts: bool,
+    // Ball-decrement or counter-increment game; in counter mode `ball` holds the counter.
+    // Only changed by `StoreConfigParam::Mode`, which also resets the value.
+    pub mode: GameMode,
+    // Fail sends whose options duplicate an enforced executor option instead of merging them.
+    pub reject_conflicting_options: bool,
+    // Who may call send and send_multi.
+    pub send_permission: SendPermission,
+    // Slots a refreshed FeeQuoteCache quote stays usable for lz_receive's return fee
+    // (0 = always use the estimate).
+    pub fee_quote_max_age_slots: u64,
+    // Balls sent by `send` (and the other send_ball paths) or lz_receive's return, and
+    // messages cleared by lz_receive, across all peers. Start at zero, also after
+    // `migrate_store`.
+    pub total_sent: u64,
+    pub total_received: u64,
+    // Key proposed by `propose_admin` that becomes admin once it signs `accept_admin`
+    // (None = no transfer in progress). `admin` stays in charge until then.
+    pub pending_admin: Option<Pubkey>,
+    // Ops key that may pause a peer's inbound messages with `pause_peer` but nothing else
+    // (default = no guardian). Unpausing stays with the admin.
+    pub guardian: Pubkey,
+    // Layout version, Store::VERSION at init. Stores from before versioning read 0 until
+    // `migrate_store` upgrades them.
+    pub version: u8,
+    // Ball NewGame and a switch to ball mode reset to: init_store's initial_ball (since
+    // version 1).
+    pub initial_ball: [u8; 32],
+    // Id the Store PDA is derived from: [STORE_SEED, Store::id_seed(id)]. Zero, the value
+    // Stores from before multi-store support read, is the original [STORE_SEED] address.
+    pub id: u64,
+    // Operator's name for this deployment and a URI describing it, zero-padded UTF-8; set
+    // at init and by `set_metadata`
+    pub label: [u8; STORE_LABEL_LEN],
+    pub metadata_uri: [u8; METADATA_URI_LEN],
+    // Range balls are kept in, as big-endian uint256 (int256 for signed balls). A bound of
+    // all zeros or all ones is unset.
+    pub min_ball: [u8; 32],
+    pub max_ball: [u8; 32],
+    // Whether lz_receive clamps or rejects an inbound ball outside the range
+    pub bound_policy: BoundPolicy,
+    // Pings sent by send, send_with_value and send_ping whose expected answer hasn't arrived
+    pub in_flight: u16,
+    // Refuse those sends with `PingInFlight` while `in_flight` is non-zero
+    pub single_flight: bool,
+    // PendingReturns lz_receive parked that haven't been sent or drained yet
+    pub pending_returns: u16,
+    // Open PendingReturns at which lz_receive refuses messages it would answer (0 = no cap)
+    pub max_pending_returns: u16,
+    // The last EID_TOUCH_LEN sends and receives, as a ring written at `touch_next`; only
+    // the first `touch_len` entries are used
+    pub touches: [EidTouch; EID_TOUCH_LEN],
+    pub touch_next: u8,
+    pub touch_len: u8,
 }
 
 impl Store {
@@ -19,32 +113,516 @@ impl Store {
     /// Initial ball value matching Ethereum contract: 100000000000000000000 (100 * 10^18)
     pub const INITIAL_BALL: u128 = 100_000_000_000_000_000_000u128;
 
-    pub fn new(admin: Pubkey, bump: u8, endpoint_program: Pubkey) -> Self {
+    /// Layout version `init_store` writes and `migrate_store` upgrades to.
+    pub const VERSION: u8 = 1;
+
+    pub fn new(admin: Pubkey, bump: u8, endpoint_program: Pubkey, nonce_window: u16) -> Self {
         // Initialize ball with the same value as Ethereum contract
         let initial_ball = U256::from(Self::INITIAL_BALL);
         Self { 
             admin, 
             bump, 
             endpoint_program, 
-            ball: initial_ball.to_be_bytes()
+            ball: initial_ball.to_be_bytes(),
+            max_hop_count: 0,
+            signed_ball: false,
+            next_seq: 1,
+            require_magic: false,
+            game_over: false,
+            max_auto_returns: None,
+            nonce_window,
+            fee_baseline: 0,
+            split_return: false,
+            ball_epoch: 0,
+            underflow_policy: UnderflowPolicy::Saturate,
+            ttl_grace_secs: 0,
+            composer: None,
+            last_src_eid: 0,
+            last_nonce: 0,
+            last_guid: [0u8; 32],
+            last_received_at: 0,
+            write_receipts: false,
+            mode: GameMode::Ball,
+            reject_conflicting_options: false,
+            send_permission: SendPermission::Anyone,
+            fee_quote_max_age_slots: crate::consts::DEFAULT_FEE_QUOTE_MAX_AGE_SLOTS,
+            total_sent: 0,
+            total_received: 0,
+            pending_admin: None,
+            guardian: Pubkey::default(),
+            version: Self::VERSION,
+            initial_ball: initial_ball.to_be_bytes(),
+            id: 0,
+            label: [0; STORE_LABEL_LEN],
+            metadata_uri: [0; METADATA_URI_LEN],
+            min_ball: [0; 32],
+            max_ball: [0; 32],
+            bound_policy: BoundPolicy::Clamp,
+            in_flight: 0,
+            single_flight: false,
+            pending_returns: 0,
+            max_pending_returns: 0,
+            touches: [EidTouch::default(); EID_TOUCH_LEN],
+            touch_next: 0,
+            touch_len: 0,
+        }
+    }
+
+    /// Record a send to or receive from `eid` in the touch ring, evicting the oldest entry
+    /// when it is full.
+    pub fn touch(&mut self, eid: u32, direction: BallDirection) -> Result<()> {
+        self.touch_at(eid, direction, Clock::get()?.slot);
+        Ok(())
+    }
+
+    /// `touch` with the slot given rather than read from the Clock.
+    pub fn touch_at(&mut self, eid: u32, direction: BallDirection, slot: u64) {
+        let index = self.touch_next as usize % EID_TOUCH_LEN;
+        self.touches[index] = EidTouch { eid, direction, slot };
+        self.touch_next = ((index + 1) % EID_TOUCH_LEN) as u8;
+        self.touch_len = self.touch_len.saturating_add(1).min(EID_TOUCH_LEN as u8);
+    }
+
+    /// The touch ring, newest first.
+    pub fn recent_touches(&self) -> Vec<EidTouch> {
+        let next = self.touch_next as usize;
+        (1..=self.touch_len as usize)
+            .map(|age| self.touches[(next + EID_TOUCH_LEN - age) % EID_TOUCH_LEN])
+            .collect()
+    }
+
+    /// The entry before the newest one: the head the last `touch` replaced, which events
+    /// carry so indexers can chain them in order.
+    pub fn previous_touch(&self) -> Option<EidTouch> {
+        let index = (self.touch_next as usize + EID_TOUCH_LEN - 2) % EID_TOUCH_LEN;
+        (self.touch_len >= 2).then_some(self.touches[index])
+    }
+
+    /// Whether `max_pending_returns` is set and that many PendingReturns are open.
+    pub fn pending_returns_full(&self) -> bool {
+        self.max_pending_returns != 0 && self.pending_returns >= self.max_pending_returns
+    }
+
+    /// Count a PendingReturn parked by lz_receive.
+    pub fn open_pending_return(&mut self) {
+        self.pending_returns = self.pending_returns.saturating_add(1);
+    }
+
+    /// Count a PendingReturn closed by retry_return, dispatch_return or drain_pending_return.
+    /// Ones parked before the count existed aren't in it, so it stops at zero.
+    pub fn close_pending_return(&mut self) {
+        self.pending_returns = self.pending_returns.saturating_sub(1);
+    }
+
+    /// Count a ping as in flight until its answer arrives, failing with `PingInFlight` when
+    /// `single_flight` is set and one already is. An answer the peer never sends, or that
+    /// no longer matches (e.g. after NewGame), leaves it counted until `reset_in_flight`.
+    pub fn start_flight(&mut self) -> Result<()> {
+        require!(!self.single_flight || self.in_flight == 0, MyOAppError::PingInFlight);
+        self.in_flight = self.in_flight.saturating_add(1);
+        Ok(())
+    }
+
+    /// The nearest value to `ball` within `min_ball..=max_ball`, compared as int256 for
+    /// signed balls. Counters are never bounded.
+    pub fn clamp_ball(&self, ball: U256) -> U256 {
+        if self.below_min_ball(ball) {
+            U256::from_be_bytes(self.min_ball)
+        } else if self.mode != GameMode::Counter
+            && Self::bound_set(&self.max_ball)
+            && self.compare_balls(&ball.to_be_bytes(), &self.max_ball) == Ordering::Greater
+        {
+            U256::from_be_bytes(self.max_ball)
+        } else {
+            ball
+        }
+    }
+
+    /// Whether `ball` is below a set `min_ball`. Counters are never bounded.
+    pub fn below_min_ball(&self, ball: U256) -> bool {
+        self.mode != GameMode::Counter
+            && Self::bound_set(&self.min_ball)
+            && self.compare_balls(&ball.to_be_bytes(), &self.min_ball) == Ordering::Less
+    }
+
+    /// Whether both bounds are set and `min_ball` is above `max_ball`.
+    pub fn ball_bounds_crossed(&self) -> bool {
+        Self::bound_set(&self.min_ball)
+            && Self::bound_set(&self.max_ball)
+            && self.compare_balls(&self.min_ball, &self.max_ball) == Ordering::Greater
+    }
+
+    fn compare_balls(&self, a: &[u8; 32], b: &[u8; 32]) -> Ordering {
+        if self.signed_ball {
+            uint256_msg_codec::compare_i256(a, b)
+        } else {
+            a.cmp(b)
+        }
+    }
+
+    fn bound_set(bound: &[u8; 32]) -> bool {
+        *bound != [0u8; 32] && *bound != [0xffu8; 32]
+    }
+
+    /// Set the label and metadata URI. Fails with `InvalidMetadata` unless both are valid
+    /// UTF-8 once their zero padding is removed.
+    pub fn set_metadata(
+        &mut self,
+        label: [u8; STORE_LABEL_LEN],
+        metadata_uri: [u8; METADATA_URI_LEN],
+    ) -> Result<()> {
+        require!(
+            std::str::from_utf8(Self::unpadded(&label)).is_ok()
+                && std::str::from_utf8(Self::unpadded(&metadata_uri)).is_ok(),
+            MyOAppError::InvalidMetadata
+        );
+        self.label = label;
+        self.metadata_uri = metadata_uri;
+        Ok(())
+    }
+
+    /// A zero-padded field such as `label` as a string.
+    pub fn padded_str(bytes: &[u8]) -> String {
+        String::from_utf8_lossy(Self::unpadded(bytes)).into_owned()
+    }
+
+    fn unpadded(bytes: &[u8]) -> &[u8] {
+        let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+        &bytes[..len]
+    }
+
+    /// Seed after STORE_SEED for store `id`: its little-endian bytes, or nothing for id 0 so
+    /// that the original single Store keeps its address.
+    pub fn id_seed(id: u64) -> Vec<u8> {
+        if id == 0 {
+            Vec::new()
+        } else {
+            id.to_le_bytes().to_vec()
         }
     }
 
     pub fn set_ball(&mut self, ball: [u8; 32]) {
         self.ball = ball;
     }
+
+    /// Fail with `StoreNeedsMigration` until `migrate_store` has brought the Store to
+    /// `version`, so fields it introduced aren't read as zero.
+    pub fn require_version(&self, version: u8) -> Result<()> {
+        require_gte!(self.version, version, MyOAppError::StoreNeedsMigration);
+        Ok(())
+    }
+
+    /// Initialise the fields each version added whose starting value isn't zero, from the
+    /// Store's version up to `VERSION`. Everything else, `admin`, `bump`,
+    /// `endpoint_program` and `ball` included, is left as it is.
+    pub fn upgrade(&mut self) {
+        if self.version < 1 {
+            // Stores from before version 1 always started from INITIAL_BALL
+            self.initial_ball = U256::from(Self::INITIAL_BALL).to_be_bytes();
+        }
+        self.version = Self::VERSION;
+    }
+
+    /// Fail with `BallDepleted` when an unsigned ball has nothing left to send: it is zero,
+    /// or smaller than `step` under `UnderflowPolicy::Error`. `UnderflowPolicy::Wrap` is
+    /// exempt, since wrapping past zero is what it is for, and so are counters.
+    pub fn require_sendable(&self, ball: U256, step: U256) -> Result<()> {
+        if self.mode == GameMode::Counter
+            || self.signed_ball
+            || self.underflow_policy == UnderflowPolicy::Wrap
+        {
+            return Ok(());
+        }
+        require!(ball != U256::ZERO, MyOAppError::BallDepleted);
+        if self.underflow_policy == UnderflowPolicy::Error {
+            require!(ball >= step, MyOAppError::BallDepleted);
+        }
+        Ok(())
+    }
+
+    /// Check that `sender` may send under `send_permission`. The admin may always send;
+    /// `allowlist` is only read under `SendPermission::Allowlist`.
+    pub fn require_send_permitted(
+        &self,
+        sender: &Pubkey,
+        allowlist: Option<&SendAllowlist>,
+    ) -> Result<()> {
+        let permitted = match self.send_permission {
+            SendPermission::Anyone => true,
+            SendPermission::AdminOnly => *sender == self.admin,
+            SendPermission::Allowlist => {
+                *sender == self.admin || allowlist.is_some_and(|list| list.contains(sender))
+            }
+        };
+        require!(permitted, MyOAppError::SendNotPermitted);
+        Ok(())
+    }
+
+    /// Return the sequence number for an outbound message and advance the counter.
+    pub fn take_seq(&mut self) -> u64 {
+        let seq = self.peek_seq();
+        self.next_seq = Self::seq_after(seq);
+        seq
+    }
+
+    /// The sequence number the next `take_seq` returns, without advancing the counter.
+    pub fn peek_seq(&self) -> u64 {
+        self.next_seq.max(1)
+    }
+
+    /// The sequence number that follows `seq`, wrapping past `u64::MAX` to 1 since 0 is reserved.
+    pub fn seq_after(seq: u64) -> u64 {
+        seq.checked_add(1).unwrap_or(1)
+    }
 }
 
 // The LzReceiveTypesAccounts PDA is used by the Executor as a prerequisite to calling `lz_receive`.
 #[account]
 pub struct LzReceiveTypesAccounts {
     pub store: Pubkey, // This is required and should be consistent.
+    pub return_accounts: Pubkey, // ReturnAccounts PDA; may not exist until the admin fills it.
 }
 
 impl LzReceiveTypesAccounts {
     pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
 
-    pub fn new(store: Pubkey) -> Self {
-        Self { store }
+    pub fn new(store: Pubkey, return_accounts: Pubkey) -> Self {
+        Self { store, return_accounts }
+    }
+
+    /// Write the ReturnAccounts key into a raw LzReceiveTypesAccounts account. PDAs created
+    /// before the field existed only hold `store`, and growing them zero-fills the key,
+    /// which `lz_receive_types` would then reject. No-op while the account is still too small.
+    pub fn write_return_accounts(info: &AccountInfo, return_accounts: &Pubkey) -> Result<()> {
+        const OFFSET: usize = 8 + 32;
+        let mut data = info.try_borrow_mut_data()?;
+        if data.len() >= OFFSET + 32 {
+            data[OFFSET..OFFSET + 32].copy_from_slice(return_accounts.as_ref());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+    use anchor_lang::solana_program::program_error::ProgramError;
+
+    fn store_pda(id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[STORE_SEED, &Store::id_seed(id)], &crate::ID).0
+    }
+
+    #[test]
+    fn id_zero_is_the_legacy_store() {
+        let (legacy, _) = Pubkey::find_program_address(&[STORE_SEED], &crate::ID);
+        assert_eq!(store_pda(0), legacy);
+        assert_eq!(Store::id_seed(1), 1u64.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    fn ids_give_distinct_stores_peers_and_lz_receive_types() {
+        let eid = 30101u32.to_be_bytes();
+        let ids = [0, 1, 2, 256, u64::MAX];
+        let mut keys = Vec::new();
+        for id in ids {
+            let store = store_pda(id);
+            let seeds: [&[&[u8]]; 2] =
+                [&[PEER_SEED, store.as_ref(), &eid], &[LZ_RECEIVE_TYPES_SEED, store.as_ref()]];
+            keys.push(store);
+            keys.extend(
+                seeds.iter().map(|seeds| Pubkey::find_program_address(seeds, &crate::ID).0),
+            );
+        }
+        let mut unique = keys.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), keys.len());
+    }
+
+    #[test]
+    fn first_message_after_init_is_seq_one() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        assert_eq!(store.peek_seq(), 1);
+        assert_eq!(store.take_seq(), 1);
+        assert_eq!(store.take_seq(), 2);
+        // A Store migrated from before the counter existed reads 0 and starts at 1 as well
+        store.next_seq = 0;
+        assert_eq!(store.take_seq(), 1);
+    }
+
+    #[test]
+    fn seq_wraps_past_u64_max_to_one() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        store.next_seq = u64::MAX;
+        assert_eq!(store.take_seq(), u64::MAX);
+        // 0 is reserved for messages without a sequence number
+        assert_eq!(store.take_seq(), 1);
+    }
+
+    #[test]
+    fn send_permission_modes() {
+        let admin = Pubkey::new_unique();
+        let (listed, stranger) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut store = Store::new(admin, 255, Pubkey::new_unique(), 0);
+        let mut allowlist = SendAllowlist { bump: 255, senders: [None; MAX_ALLOWLISTED_SENDERS] };
+        allowlist.add(listed).unwrap();
+        let permitted = |store: &Store, sender: &Pubkey, allowlist: Option<&SendAllowlist>| {
+            store.require_send_permitted(sender, allowlist).is_ok()
+        };
+
+        assert!(permitted(&store, &stranger, None));
+
+        store.send_permission = SendPermission::AdminOnly;
+        assert!(permitted(&store, &admin, None));
+        assert!(!permitted(&store, &listed, Some(&allowlist)));
+
+        store.send_permission = SendPermission::Allowlist;
+        assert!(permitted(&store, &admin, None));
+        assert!(permitted(&store, &listed, Some(&allowlist)));
+        assert!(!permitted(&store, &stranger, Some(&allowlist)));
+        // Without the allowlist account only the admin may send
+        assert!(!permitted(&store, &listed, None));
+        // A removed sender is refused from then on
+        allowlist.remove(&listed);
+        assert!(!permitted(&store, &listed, Some(&allowlist)));
+    }
+
+    #[test]
+    fn pending_returns_cap_fills_and_drains() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        store.max_pending_returns = 2;
+        store.open_pending_return();
+        assert!(!store.pending_returns_full());
+        store.open_pending_return();
+        assert!(store.pending_returns_full());
+        // Retrying or draining one makes room again
+        store.close_pending_return();
+        assert!(!store.pending_returns_full());
+        store.close_pending_return();
+        // Returns parked before the count existed aren't in it
+        store.close_pending_return();
+        assert_eq!(store.pending_returns, 0);
+    }
+
+    #[test]
+    fn zero_max_pending_returns_is_uncapped() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        for _ in 0..100 {
+            store.open_pending_return();
+        }
+        assert!(!store.pending_returns_full());
+    }
+
+    #[test]
+    fn touch_ring_lists_newest_first() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        assert!(store.recent_touches().is_empty());
+        assert!(store.previous_touch().is_none());
+        store.touch_at(30101, BallDirection::Sent, 10);
+        // A single entry has no previous head
+        assert!(store.previous_touch().is_none());
+        store.touch_at(30110, BallDirection::Received, 11);
+        let eids: Vec<u32> = store.recent_touches().iter().map(|touch| touch.eid).collect();
+        assert_eq!(eids, vec![30110, 30101]);
+        assert_eq!(store.previous_touch().map(|touch| touch.slot), Some(10));
+    }
+
+    #[test]
+    fn touch_ring_evicts_the_oldest_entry() {
+        let mut store = Store::new(Pubkey::new_unique(), 255, Pubkey::new_unique(), 0);
+        let total = EID_TOUCH_LEN as u64 + 3;
+        for slot in 1..=total {
+            store.touch_at(slot as u32, BallDirection::Sent, slot);
+        }
+        assert_eq!(store.touch_len as usize, EID_TOUCH_LEN);
+        assert_eq!(store.touch_next, 3);
+        let slots: Vec<u64> = store.recent_touches().iter().map(|touch| touch.slot).collect();
+        let expected: Vec<u64> = (4..=total).rev().collect();
+        assert_eq!(slots, expected);
+        // The previous head survives the wrap
+        assert_eq!(store.previous_touch().map(|touch| touch.slot), Some(total - 1));
+    }
+
+    #[test]
+    fn upgrade_keeps_what_a_version_0_store_holds() {
+        let mut store = Store::new(Pubkey::new_unique(), 254, Pubkey::new_unique(), 0);
+        store.set_ball(U256::from(42u64).to_be_bytes());
+        // A Store from before versioning, once grown, reads zero for the later fields
+        store.version = 0;
+        store.initial_ball = [0; 32];
+        let before = (store.admin, store.bump, store.endpoint_program, store.ball);
+        assert_eq!(
+            ProgramError::from(store.require_version(1).unwrap_err()),
+            ProgramError::from(anchor_lang::error::Error::from(MyOAppError::StoreNeedsMigration))
+        );
+
+        store.upgrade();
+        assert_eq!((store.admin, store.bump, store.endpoint_program, store.ball), before);
+        assert_eq!(store.version, Store::VERSION);
+        assert_eq!(store.initial_ball, U256::from(Store::INITIAL_BALL).to_be_bytes());
+        store.require_version(1).unwrap();
+        // Upgrading again changes nothing
+        store.initial_ball = [1; 32];
+        store.upgrade();
+        assert_eq!(store.initial_ball, [1; 32]);
+    }
+
+    #[test]
+    fn store_grown_from_the_original_size_deserializes() {
+        // The original Store: discriminator, admin, bump, endpoint_program and ball
+        let (admin, endpoint_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ball = U256::from(42u64).to_be_bytes();
+        let mut data = Store::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(admin.as_ref());
+        data.push(254);
+        data.extend_from_slice(endpoint_program.as_ref());
+        data.extend_from_slice(&ball);
+        assert_eq!(data.len(), 105);
+        assert!(Store::try_deserialize(&mut &data[..]).is_err());
+
+        // What `extend_store` leaves: the old bytes followed by a zeroed tail
+        data.resize(Store::SIZE, 0);
+        let mut grown = Store::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(grown.admin, admin);
+        assert_eq!(grown.bump, 254);
+        assert_eq!(grown.endpoint_program, endpoint_program);
+        assert_eq!(grown.ball, ball);
+        // Everything added since reads as its zero value
+        assert_eq!((grown.version, grown.id, grown.next_seq, grown.in_flight), (0, 0, 0, 0));
+        assert!(grown.max_auto_returns.is_none());
+        assert!(grown.composer.is_none());
+        assert!(grown.pending_admin.is_none());
+        assert!(grown.mode == GameMode::default());
+        assert!(grown.recent_touches().is_empty());
+
+        grown.upgrade();
+        assert_eq!(grown.ball, ball);
+        assert_eq!(grown.initial_ball, U256::from(Store::INITIAL_BALL).to_be_bytes());
+    }
+
+    #[test]
+    fn lz_receive_types_grown_from_the_store_only_size_gets_the_return_accounts() {
+        let return_accounts = Pubkey::new_unique();
+        let write = |account: &mut TestAccount| {
+            LzReceiveTypesAccounts::write_return_accounts(&account.info(), &return_accounts)
+                .unwrap();
+        };
+        let store = Pubkey::new_unique();
+        let mut data = Vec::new();
+        LzReceiveTypesAccounts::new(store, Pubkey::default())
+            .try_serialize(&mut data)
+            .unwrap();
+        // Older PDAs only held `store`, too little to write the key into
+        data.truncate(8 + 32);
+        let mut account = TestAccount::new(Pubkey::new_unique(), crate::ID, false, data);
+        write(&mut account);
+        assert!(LzReceiveTypesAccounts::try_deserialize(&mut &account.data[..]).is_err());
+
+        account.data.resize(LzReceiveTypesAccounts::SIZE, 0);
+        write(&mut account);
+        let grown = LzReceiveTypesAccounts::try_deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!(grown.store, store);
+        assert_eq!(grown.return_accounts, return_accounts);
     }
 }
@@ -0,0 +1,91 @@
+use crate::{errors::MyOAppError, *};
+use ethnum::U256;
+
+/// What a decrement does when the step is larger than what the ball has left (below zero
+/// for unsigned balls, below `I256::MIN` for signed ones).
+#[derive(Clone, Copy, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum UnderflowPolicy {
+    /// Stop at the lower bound
+    #[default]
+    Saturate,
+    /// Reject the send with `BallUnderflow`; a return leg is skipped with
+    /// `ReturnUnderflowSkipped`, since the inbound message is already cleared
+    Error,
+    /// Wrap modulo 2^256 (for testing)
+    Wrap,
+}
+
+impl UnderflowPolicy {
+    /// Decrement `ball` by `step` under this policy.
+    pub fn decrement(self, ball: U256, step: U256, signed: bool) -> Result<U256> {
+        Ok(match self {
+            Self::Saturate => uint256_msg_codec::decrement_ball_by(ball, step, signed),
+            Self::Error => uint256_msg_codec::checked_decrement_ball_by(ball, step, signed)
+                .ok_or(MyOAppError::BallUnderflow)?,
+            Self::Wrap => uint256_msg_codec::wrapping_decrement_ball_by(ball, step),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_error::ProgramError;
+    use ethnum::I256;
+
+    fn assert_underflow(result: Result<U256>) {
+        assert_eq!(
+            ProgramError::from(result.unwrap_err()),
+            ProgramError::from(anchor_lang::error::Error::from(MyOAppError::BallUnderflow))
+        );
+    }
+
+    /// Decrement an unsigned ball of 1 by one, twice
+    fn from_one(policy: UnderflowPolicy) -> (U256, Result<U256>) {
+        let zero = policy.decrement(U256::ONE, U256::ONE, false).unwrap();
+        (zero, policy.decrement(zero, U256::ONE, false))
+    }
+
+    #[test]
+    fn saturate_stops_at_zero() {
+        let (zero, below) = from_one(UnderflowPolicy::Saturate);
+        assert_eq!(zero, U256::ZERO);
+        assert_eq!(below.unwrap(), U256::ZERO);
+        let step = U256::from(5u128);
+        assert_eq!(
+            UnderflowPolicy::Saturate.decrement(U256::ONE, step, false).unwrap(),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn error_rejects_going_below_zero() {
+        let (zero, below) = from_one(UnderflowPolicy::Error);
+        assert_eq!(zero, U256::ZERO);
+        assert_underflow(below);
+        assert_underflow(UnderflowPolicy::Error.decrement(U256::ONE, U256::from(2u128), false));
+    }
+
+    #[test]
+    fn wrap_goes_round_to_the_maximum() {
+        let (zero, below) = from_one(UnderflowPolicy::Wrap);
+        assert_eq!(zero, U256::ZERO);
+        assert_eq!(below.unwrap(), U256::MAX);
+    }
+
+    #[test]
+    fn signed_balls_hit_the_boundary_at_i256_min() {
+        let min = U256::from_be_bytes(I256::MIN.to_be_bytes());
+        let above_min = U256::from_be_bytes((I256::MIN + I256::ONE).to_be_bytes());
+        for policy in [UnderflowPolicy::Saturate, UnderflowPolicy::Error, UnderflowPolicy::Wrap] {
+            // Zero goes negative under every policy
+            let minus_one = policy.decrement(U256::ZERO, U256::ONE, true).unwrap();
+            assert_eq!(minus_one, U256::MAX);
+            assert_eq!(policy.decrement(above_min, U256::ONE, true).unwrap(), min);
+        }
+        assert_eq!(UnderflowPolicy::Saturate.decrement(min, U256::ONE, true).unwrap(), min);
+        assert_underflow(UnderflowPolicy::Error.decrement(min, U256::ONE, true));
+        let max = U256::from_be_bytes(I256::MAX.to_be_bytes());
+        assert_eq!(UnderflowPolicy::Wrap.decrement(min, U256::ONE, true).unwrap(), max);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{consts::*, *};
+
+// One Solana user's own game against the peer, created by `init_user_ball`. The user's
+// pings carry their key as the ABA origin caller, and inbound ABA messages whose origin
+// caller has a UserBall update it instead of Store.ball.
+#[account]
+#[derive(Default)]
+pub struct UserBall {
+    pub bump: u8,
+    pub user: Pubkey,
+    pub ball: [u8; 32],
+    pub sent_count: u64,     // Pings `send_user_ping` sent for this user
+    pub received_count: u64, // Inbound messages applied to this ball
+}
+
+impl UserBall {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+
+    /// The UserBall PDA of `user` under `store`.
+    pub fn address(store: &Pubkey, user: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[USER_BALL_SEED, &store.to_bytes(), user], &crate::ID).0
+    }
+
+    /// Load the UserBall in `account`, or None while `init_user_ball` hasn't created it.
+    pub fn load(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        let data = account.try_borrow_data()?;
+        Ok(Some(Self::try_deserialize(&mut &data[..])?))
+    }
+
+    /// Write this UserBall back to `account`, which `load` read it from.
+    pub fn save(&self, account: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+    }
+}
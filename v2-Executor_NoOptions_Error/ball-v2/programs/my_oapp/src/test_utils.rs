@@ -0,0 +1,36 @@
+// Host-side fixtures for the instruction tests, which run the Accounts checks and handlers
+// against accounts built in memory.
+use anchor_lang::prelude::*;
+
+/// The key, owner, lamports and data behind one AccountInfo.
+pub(crate) struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub is_signer: bool,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, is_signer: bool, data: Vec<u8>) -> Self {
+        Self { key, owner, is_signer, lamports: 1_000_000, data }
+    }
+
+    /// A system-owned signer without data, as an admin or payer is.
+    pub fn signer(key: Pubkey) -> Self {
+        Self::new(key, anchor_lang::system_program::ID, true, Vec::new())
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
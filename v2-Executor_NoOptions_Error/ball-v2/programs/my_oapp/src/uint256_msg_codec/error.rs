@@ -0,0 +1,35 @@
+use core::fmt;
+
+/// Reasons a message can fail to encode or decode.
+/// On-chain these map one-to-one onto the `MyOAppError` variants of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgCodecError {
+    InvalidMessageLength,
+    InvalidMessageType, // Message is not of the expected type
+    InvalidMessageOffset, // Offset word is not where the canonical layout puts it
+    TrailingMessageBytes, // Non-zero padding or extra bytes after the last tail
+    ReturnOptionsTooLong, // Return options exceed MAX_RETURN_OPTIONS_LEN
+    UnsupportedCodecVersion, // Message version header is newer (or older) than CODEC_VERSION
+    ComposeMsgTooLong, // Compose message exceeds MAX_COMPOSE_MSG_LEN
+    InvalidBatchLength, // Batch is empty or exceeds MAX_BATCH_LEN
+    MemoTooLong, // Memo exceeds MAX_MEMO_LEN
+    ExtensionsTooLong, // Encoded TLV extensions exceed MAX_EXTENSIONS_LEN
+    InvalidExtension, // Truncated TLV entry, or a known extension with the wrong length
+    ForeignMessage, // Message lacks the MAGIC prefix
+    TooShort, // Message ends before a fixed-size part (head, length word, padding) is complete
+    LengthMismatch, // A length word disagrees with the bytes actually present
+    UnknownMsgType, // Type word is a valid uint16 but not a type this decoder handles
+    MessageTooLarge, // Message exceeds MAX_MESSAGE_LEN
+    ValueTooLarge, // Ball does not fit the requested integer width
+    ZeroStep, // Step word is present but zero
+}
+
+pub type Result<T> = core::result::Result<T, MsgCodecError>;
+
+impl fmt::Display for MsgCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for MsgCodecError {}
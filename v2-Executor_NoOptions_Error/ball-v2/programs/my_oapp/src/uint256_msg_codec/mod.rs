@@ -0,0 +1,1755 @@
+// Encoding and decoding of the messages exchanged with the EVM contract.
+// Only `ethnum` is required, so off-chain tooling can build this module without
+// anchor_lang; the `onchain` feature adds the conversion into the program's error type.
+// Decoders take untrusted bytes from the EVM side: every offset and length word is
+// range-checked with checked arithmetic before indexing, so malformed input must surface
+// as a `MsgCodecError` and never as a panic or an out-of-bounds read.
+
+use core::cmp::Ordering;
+use ethnum::{I256, U256};
+
+use crate::consts::{
+    MAX_BATCH_LEN, MAX_COMPOSE_MSG_LEN, MAX_EXTENSIONS_LEN, MAX_MEMO_LEN, MAX_MESSAGE_LEN,
+    MAX_RETURN_OPTIONS_LEN,
+};
+
+// Same as anchor's `require!`: return `$err` unless `$cond` holds
+macro_rules! ensure {
+    ($cond:expr, $err:expr $(,)?) => {
+        if !$cond {
+            return Err($err);
+        }
+    };
+}
+
+mod error;
+#[cfg(feature = "onchain")]
+mod onchain;
+
+pub use error::*;
+
+pub const UINT256_SIZE: usize = 32;
+// Type reported for bare 32-byte `abi.encode(uint256)` payloads, which carry no type word
+pub const VANILLA_TYPE: u16 = 0;
+pub const ABA_TYPE: u16 = 2;
+// Return leg (B→A) of an ABA exchange; carries no return options of its own
+pub const PONG_TYPE: u16 = 3;
+// Several ball values in one message; see `encode_batch_message`
+pub const BATCH_TYPE: u16 = 4;
+// Sent instead of a return once the ball reaches zero; never answered
+pub const GAME_OVER_TYPE: u16 = 5;
+// Bounce-counter game mode: a counter each side increments and sends back; see `encode_counter`
+pub const COUNTER_TYPE: u16 = 6;
+
+// Head length (and so the options offset) of the legacy `abi.encode(uint256, uint16, bytes)`
+pub const ABA_LEGACY_HEAD_LEN: usize = 96;
+// Longest head `AbaMessage::encode` writes: `abi.encode(uint256, uint16, bytes, uint16,
+// bytes32, bytes, string, bytes32, uint64, bytes, uint256)`, appending the hop count, origin
+// guid, compose message, memo, origin caller, sequence number, TLV extensions and step.
+// The head ends at the last field a message uses, so decoders accept any whole-word head
+// between the legacy and this length.
+pub const ABA_HEAD_LEN: usize = 352;
+// Shortest possible ABA message: the legacy head plus an empty options length word
+pub const ABA_MIN_SIZE: usize = ABA_LEGACY_HEAD_LEN + UINT256_SIZE;
+// Word indexes of the optional fields inside the ABA head
+const ABA_HOP_COUNT_WORD: usize = 3;
+const ABA_ORIGIN_GUID_WORD: usize = 4;
+const ABA_COMPOSE_OFFSET_WORD: usize = 5;
+const ABA_MEMO_OFFSET_WORD: usize = 6;
+const ABA_ORIGIN_CALLER_WORD: usize = 7;
+const ABA_SEQ_WORD: usize = 8;
+const ABA_EXTENSIONS_OFFSET_WORD: usize = 9;
+const ABA_STEP_WORD: usize = 10;
+// Each extension is a 1-byte type and a 2-byte big-endian length followed by the value
+const EXTENSION_HEADER_LEN: usize = 3;
+// Extension holding a unix timestamp (8-byte big-endian) after which no return leg is sent.
+// Types this program does not know are carried through untouched.
+pub const DEADLINE_EXT_TYPE: u8 = 1;
+// Extension holding the origin timestamp and a time-to-live in seconds (two 8-byte
+// big-endian values). Messages delivered after timestamp + ttl are dropped unapplied.
+pub const TTL_EXT_TYPE: u8 = 2;
+// Empty extension by which the sender asks for the ball to be applied without a return leg.
+pub const NO_RETURN_EXT_TYPE: u8 = 3;
+// Offset of the string in `abi.encode(uint256, string)`
+const MEMO_MSG_OFFSET: usize = 2 * UINT256_SIZE;
+// Start of the return options in an `abi.encodePacked(uint256, uint16, bytes)` message
+pub const PACKED_DATA_OFFSET: usize = UINT256_SIZE + 2;
+// Length of `abi.encode(uint16, uint256)`, the typed vanilla layout
+pub const TYPED_VANILLA_LEN: usize = 2 * UINT256_SIZE;
+// Ball width for compact-capable peers: `abi.encodePacked(uint128)`
+pub const COMPACT_BALL_SIZE: usize = 16;
+// Start of the return options in an `abi.encodePacked(uint128, uint16, bytes)` message
+pub const COMPACT_DATA_OFFSET: usize = COMPACT_BALL_SIZE + 2;
+
+// Domain separator some senders put in front of the message (before any version header).
+// Tagged messages are 4 (or 5 with a version header) bytes longer than a multiple of 32.
+pub const MAGIC: [u8; 4] = *b"BALL";
+
+// Highest version header this program understands. Unversioned messages are implicitly v1.
+pub const CODEC_VERSION: u8 = 2;
+
+/// Structure representing an ABA message
+#[derive(Clone, Default)]
+pub struct AbaMessage {
+    pub ball: [u8; 32],
+    pub msg_type: u16,
+    pub return_options: Vec<u8>,
+    // Number of legs this ball has already travelled; 0 for messages without the field
+    pub hop_count: u16,
+    // GUID of the message this one answers; all zeros for pings and older messages
+    pub origin_guid: [u8; 32],
+    // Payload to forward to a composer after the ball is updated; empty when unused
+    pub compose_msg: Vec<u8>,
+    // Human-readable note as raw bytes; inbound memos are not guaranteed to be valid UTF-8
+    pub memo: Vec<u8>,
+    // Account that triggered the ping on the source chain (an EVM `msg.sender` is
+    // left-padded); all zeros when unknown
+    pub origin_caller: [u8; 32],
+    // Sender's application-level sequence number; 0 for messages without the field
+    pub seq: u64,
+    // Type-length-value fields appended after the known fields, in wire order
+    pub extensions: Vec<Extension>,
+    // Amount the receiver subtracts from the ball for the return leg; all zeros means the
+    // default of 1 and is what messages without the field decode to
+    pub step: [u8; 32],
+}
+
+/// An `AbaMessage` whose variable-length fields borrow from the encoded message, as
+/// returned by `decode_aba_ref`
+#[derive(Clone, Default)]
+pub struct AbaMessageRef<'a> {
+    pub ball: [u8; 32],
+    pub msg_type: u16,
+    pub return_options: &'a [u8],
+    pub hop_count: u16,
+    pub origin_guid: [u8; 32],
+    pub compose_msg: &'a [u8],
+    pub memo: &'a [u8],
+    pub origin_caller: [u8; 32],
+    pub seq: u64,
+    // The TLV extension area, already validated; entries are read on demand
+    pub extensions: &'a [u8],
+    pub step: [u8; 32],
+}
+
+/// One entry of the TLV extension area
+#[derive(Clone)]
+pub struct Extension {
+    pub ext_type: u8,
+    pub value: Vec<u8>,
+}
+
+impl Extension {
+    /// A `TTL_EXT_TYPE` extension for a message sent at `sent_at` that expires `ttl`
+    /// seconds later.
+    pub fn ttl(sent_at: u64, ttl: u64) -> Self {
+        let mut value = Vec::with_capacity(16);
+        value.extend_from_slice(&sent_at.to_be_bytes());
+        value.extend_from_slice(&ttl.to_be_bytes());
+        Self { ext_type: TTL_EXT_TYPE, value }
+    }
+
+    /// A `NO_RETURN_EXT_TYPE` extension, for a one-way ABA message.
+    pub fn no_return() -> Self {
+        Self { ext_type: NO_RETURN_EXT_TYPE, value: Vec::new() }
+    }
+}
+
+/// An `AbaMessage` whose ball has already been converted to `U256`
+#[derive(Clone)]
+pub struct AbaMessageU256 {
+    pub ball: U256,
+    // The decoded message; `message.ball` holds the same value as raw bytes
+    pub message: AbaMessage,
+}
+
+/// Any message the EVM contract can send, as returned by `decode_any`
+#[derive(Clone)]
+pub enum DecodedMessage<'a> {
+    // Bare `abi.encode(uint256)` or typed `abi.encode(uint16, uint256)` payload
+    Vanilla([u8; 32]),
+    // ABA ping, pong or game over; `msg_type` is `ABA_TYPE`, `PONG_TYPE` or `GAME_OVER_TYPE`
+    Aba(AbaMessageRef<'a>),
+    // `encode_batch_message` payload (never empty)
+    Batch(Vec<[u8; 32]>),
+    // `encode_counter` payload
+    Counter([u8; 32]),
+}
+
+impl AbaMessage {
+    /// Encode this message following Solidity's `abi.encode(uint256, uint16, bytes, uint16,
+    /// bytes32, bytes, string, bytes32, uint64, bytes, uint256)`, cut after the last field
+    /// that isn't at its default, so a plain ping is the 128-byte legacy layout. Fields
+    /// past the end of the head decode as their defaults.
+    /// A step of 0 or 1 is left out, since receivers default to 1 and reject an explicit zero.
+    /// Decoders that only know `(uint256, uint16, bytes)` still work since they follow the
+    /// options offset and ignore the extra head words and trailing tails.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded message bytes
+    /// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If `return_options` exceeds `MAX_RETURN_OPTIONS_LEN`
+    /// * `Err(MsgCodecError::ComposeMsgTooLong)` - If `compose_msg` exceeds `MAX_COMPOSE_MSG_LEN`
+    /// * `Err(MsgCodecError::MemoTooLong)` - If `memo` exceeds `MAX_MEMO_LEN`
+    /// * `Err(MsgCodecError::ExtensionsTooLong)` - If the extensions exceed `MAX_EXTENSIONS_LEN`
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        ensure!(
+            self.return_options.len() <= MAX_RETURN_OPTIONS_LEN,
+            MsgCodecError::ReturnOptionsTooLong
+        );
+        ensure!(self.compose_msg.len() <= MAX_COMPOSE_MSG_LEN, MsgCodecError::ComposeMsgTooLong);
+        ensure!(self.memo.len() <= MAX_MEMO_LEN, MsgCodecError::MemoTooLong);
+        let extensions = encode_extensions(&self.extensions)?;
+        let step = self.step_u256();
+
+        // The head runs up to the last word in use; the legacy three words are always there
+        let used = [
+            (ABA_HOP_COUNT_WORD, self.hop_count != 0),
+            (ABA_ORIGIN_GUID_WORD, self.origin_guid != [0u8; 32]),
+            (ABA_COMPOSE_OFFSET_WORD, !self.compose_msg.is_empty()),
+            (ABA_MEMO_OFFSET_WORD, !self.memo.is_empty()),
+            (ABA_ORIGIN_CALLER_WORD, self.origin_caller != [0u8; 32]),
+            (ABA_SEQ_WORD, self.seq != 0),
+            (ABA_EXTENSIONS_OFFSET_WORD, !extensions.is_empty()),
+            (ABA_STEP_WORD, step != U256::ONE),
+        ];
+        let head_len = used
+            .iter()
+            .filter(|(_, used)| *used)
+            .map(|(word, _)| (word + 1) * UINT256_SIZE)
+            .max()
+            .unwrap_or(ABA_LEGACY_HEAD_LEN);
+        let has = |word: usize| (word + 1) * UINT256_SIZE <= head_len;
+
+        // Tails follow the head in field order, each one word of length plus padded data.
+        // A tail is only written when its offset word is part of the head.
+        let tail_len = |word: usize, data: &[u8]| if has(word) { bytes_tail_len(data) } else { 0 };
+        let options_offset = head_len;
+        let compose_offset = options_offset + bytes_tail_len(&self.return_options);
+        let memo_offset = compose_offset + tail_len(ABA_COMPOSE_OFFSET_WORD, &self.compose_msg);
+        let extensions_offset = memo_offset + tail_len(ABA_MEMO_OFFSET_WORD, &self.memo);
+        let mut encoded = Vec::with_capacity(
+            extensions_offset + tail_len(ABA_EXTENSIONS_OFFSET_WORD, &extensions),
+        );
+
+        // Head: ball, msg_type, options offset, hop_count, origin_guid, compose offset,
+        // memo offset, origin_caller, seq, extensions offset, step
+        encoded.extend_from_slice(&self.ball);
+        push_u64_word(&mut encoded, self.msg_type as u64);
+        push_u64_word(&mut encoded, options_offset as u64);
+        push_u64_word(&mut encoded, self.hop_count as u64);
+        encoded.extend_from_slice(&self.origin_guid);
+        push_u64_word(&mut encoded, compose_offset as u64);
+        push_u64_word(&mut encoded, memo_offset as u64);
+        encoded.extend_from_slice(&self.origin_caller);
+        push_u64_word(&mut encoded, self.seq);
+        push_u64_word(&mut encoded, extensions_offset as u64);
+        encoded.extend_from_slice(&step.to_be_bytes());
+        encoded.truncate(head_len);
+
+        // Tails: return options, compose message, memo, extensions
+        push_bytes_tail(&mut encoded, &self.return_options);
+        for (word, data) in [
+            (ABA_COMPOSE_OFFSET_WORD, &self.compose_msg[..]),
+            (ABA_MEMO_OFFSET_WORD, &self.memo[..]),
+            (ABA_EXTENSIONS_OFFSET_WORD, &extensions[..]),
+        ] {
+            if has(word) {
+                push_bytes_tail(&mut encoded, data);
+            }
+        }
+
+        Ok(encoded)
+    }
+
+    /// The origin caller, or `None` when the sender left it unset.
+    pub fn known_origin_caller(&self) -> Option<[u8; 32]> {
+        (self.origin_caller != [0u8; 32]).then_some(self.origin_caller)
+    }
+
+    /// The value of the first extension of type `ext_type`, if any.
+    pub fn extension(&self, ext_type: u8) -> Option<&[u8]> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.ext_type == ext_type)
+            .map(|extension| extension.value.as_slice())
+    }
+
+    /// The deadline extension as a unix timestamp, if present.
+    ///
+    /// # Returns
+    /// * `Ok(Option<u64>)` - The deadline, or `None` when the message carries none
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the deadline value is not 8 bytes
+    pub fn deadline(&self) -> Result<Option<u64>> {
+        read_deadline(self.extension(DEADLINE_EXT_TYPE))
+    }
+
+    /// The TTL extension as `(sent_at, ttl)`, if present.
+    ///
+    /// # Returns
+    /// * `Ok(Option<(u64, u64)>)` - Origin timestamp and TTL in seconds, or `None`
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the TTL value is not 16 bytes
+    pub fn ttl(&self) -> Result<Option<(u64, u64)>> {
+        read_ttl(self.extension(TTL_EXT_TYPE))
+    }
+
+    /// Whether the sender asked for no return leg.
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - `true` when the no-return extension is present
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the no-return extension has a value
+    pub fn no_return(&self) -> Result<bool> {
+        read_flag(self.extension(NO_RETURN_EXT_TYPE))
+    }
+
+    /// The step as a `U256`, with the all-zero default read as 1.
+    pub fn step_u256(&self) -> U256 {
+        U256::from_be_bytes(self.step).max(U256::ONE)
+    }
+
+    /// The ball as a `U256`.
+    pub fn ball_u256(&self) -> U256 {
+        U256::from_be_bytes(self.ball)
+    }
+
+    /// The memo as text, replacing invalid UTF-8 sequences instead of failing.
+    pub fn memo_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.memo).into_owned()
+    }
+}
+
+impl AbaMessageRef<'_> {
+    /// Copy the borrowed fields into an owned `AbaMessage`.
+    pub fn into_owned(self) -> AbaMessage {
+        let mut extensions = Vec::new();
+        let mut rest = self.extensions;
+        while let Ok((ext_type, value, next)) = split_extension(rest) {
+            extensions.push(Extension { ext_type, value: value.to_vec() });
+            rest = next;
+        }
+        AbaMessage {
+            ball: self.ball,
+            msg_type: self.msg_type,
+            return_options: self.return_options.to_vec(),
+            hop_count: self.hop_count,
+            origin_guid: self.origin_guid,
+            compose_msg: self.compose_msg.to_vec(),
+            memo: self.memo.to_vec(),
+            origin_caller: self.origin_caller,
+            seq: self.seq,
+            extensions,
+            step: self.step,
+        }
+    }
+
+    /// The origin caller, or `None` when the sender left it unset.
+    pub fn known_origin_caller(&self) -> Option<[u8; 32]> {
+        (self.origin_caller != [0u8; 32]).then_some(self.origin_caller)
+    }
+
+    /// The value of the first extension of type `ext_type`, if any.
+    pub fn extension(&self, ext_type: u8) -> Option<&[u8]> {
+        let mut rest = self.extensions;
+        while let Ok((entry_type, value, next)) = split_extension(rest) {
+            if entry_type == ext_type {
+                return Some(value);
+            }
+            rest = next;
+        }
+        None
+    }
+
+    /// The deadline extension as a unix timestamp, if present.
+    ///
+    /// # Returns
+    /// * `Ok(Option<u64>)` - The deadline, or `None` when the message carries none
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the deadline value is not 8 bytes
+    pub fn deadline(&self) -> Result<Option<u64>> {
+        read_deadline(self.extension(DEADLINE_EXT_TYPE))
+    }
+
+    /// The TTL extension as `(sent_at, ttl)`, if present.
+    ///
+    /// # Returns
+    /// * `Ok(Option<(u64, u64)>)` - Origin timestamp and TTL in seconds, or `None`
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the TTL value is not 16 bytes
+    pub fn ttl(&self) -> Result<Option<(u64, u64)>> {
+        read_ttl(self.extension(TTL_EXT_TYPE))
+    }
+
+    /// Whether the sender asked for no return leg.
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - `true` when the no-return extension is present
+    /// * `Err(MsgCodecError::InvalidExtension)` - If the no-return extension has a value
+    pub fn no_return(&self) -> Result<bool> {
+        read_flag(self.extension(NO_RETURN_EXT_TYPE))
+    }
+
+    /// The step as a `U256`, with the all-zero default read as 1.
+    pub fn step_u256(&self) -> U256 {
+        U256::from_be_bytes(self.step).max(U256::ONE)
+    }
+
+    /// The ball as a `U256`.
+    pub fn ball_u256(&self) -> U256 {
+        U256::from_be_bytes(self.ball)
+    }
+
+    /// The memo as text, replacing invalid UTF-8 sequences instead of failing.
+    pub fn memo_lossy(&self) -> String {
+        String::from_utf8_lossy(self.memo).into_owned()
+    }
+}
+
+/// Encode a uint256 value (represented as 32 bytes in big-endian) into a message format.
+/// This matches Solidity's `abi.encode(uint256)` which produces 32 bytes in big-endian format.
+/// 
+/// # Arguments
+/// * `value` - A 32-byte array representing the uint256 in big-endian format
+/// 
+/// # Example
+/// ```
+/// use ethnum::U256;
+/// use my_oapp::uint256_msg_codec::encode;
+/// let value = U256::from(100_000_000_000_000_000_000u128);
+/// let bytes = value.to_be_bytes();
+/// let encoded = encode(&bytes);
+/// ```
+pub fn encode(value: &[u8; 32]) -> Vec<u8> {
+    value.to_vec()
+}
+
+/// Decode a message into a uint256 value (32 bytes in big-endian format).
+/// Returns an error if the message is not exactly 32 bytes.
+/// This matches Solidity's `abi.decode(bytes, (uint256))`.
+/// 
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 32 bytes)
+/// 
+/// # Returns
+/// * `Ok([u8; 32])` - The uint256 value as a 32-byte array in big-endian format
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 32 bytes
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If the message is longer than 32 bytes
+/// 
+/// # Example
+/// ```
+/// use ethnum::U256;
+/// use my_oapp::uint256_msg_codec::{decode, encode};
+/// let message = encode(&U256::ONE.to_be_bytes());
+/// let bytes = decode(&message)?;
+/// let value = U256::from_be_bytes(bytes);  // Convert to U256 using big-endian
+/// # Ok::<(), my_oapp::uint256_msg_codec::MsgCodecError>(())
+/// ```
+pub fn decode(message: &[u8]) -> Result<[u8; 32]> {
+    ensure!(message.len() >= UINT256_SIZE, MsgCodecError::TooShort);
+    ensure!(message.len() == UINT256_SIZE, MsgCodecError::TrailingMessageBytes);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(message);
+    Ok(result)
+}
+
+/// Encode a `U256` value into a message.
+/// This matches Solidity's `abi.encode(uint256)`.
+///
+/// # Arguments
+/// * `value` - The value to encode
+pub fn encode_u256(value: U256) -> Vec<u8> {
+    encode(&value.to_be_bytes())
+}
+
+/// Decode a message into a `U256` value.
+/// This matches Solidity's `abi.decode(bytes, (uint256))`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 32 bytes)
+///
+/// # Returns
+/// * `Ok(U256)` - The decoded value
+/// * `Err(_)` - Any error from `decode`
+pub fn decode_u256(message: &[u8]) -> Result<U256> {
+    Ok(U256::from_be_bytes(decode(message)?))
+}
+
+/// Read a ball as a `u64`.
+///
+/// # Returns
+/// * `Ok(u64)` - The ball value
+/// * `Err(MsgCodecError::ValueTooLarge)` - If any of the high 24 bytes is non-zero
+pub fn ball_as_u64(ball: &[u8; 32]) -> Result<u64> {
+    let (high, low) = ball.split_at(24);
+    ensure!(high.iter().all(|byte| *byte == 0), MsgCodecError::ValueTooLarge);
+    let mut value = [0u8; 8];
+    value.copy_from_slice(low);
+    Ok(u64::from_be_bytes(value))
+}
+
+/// Read a ball as a `u128`.
+///
+/// # Returns
+/// * `Ok(u128)` - The ball value
+/// * `Err(MsgCodecError::ValueTooLarge)` - If any of the high 16 bytes is non-zero
+pub fn ball_as_u128(ball: &[u8; 32]) -> Result<u128> {
+    let (high, low) = ball.split_at(16);
+    ensure!(high.iter().all(|byte| *byte == 0), MsgCodecError::ValueTooLarge);
+    let mut value = [0u8; 16];
+    value.copy_from_slice(low);
+    Ok(u128::from_be_bytes(value))
+}
+
+/// Build a ball from a `u64`, the inverse of `ball_as_u64`.
+pub fn ball_from_u64(value: u64) -> [u8; 32] {
+    let mut ball = [0u8; 32];
+    ball[24..].copy_from_slice(&value.to_be_bytes());
+    ball
+}
+
+/// Build a ball from a `u128`, the inverse of `ball_as_u128`.
+pub fn ball_from_u128(value: u128) -> [u8; 32] {
+    let mut ball = [0u8; 32];
+    ball[16..].copy_from_slice(&value.to_be_bytes());
+    ball
+}
+
+/// Encode a ball as a typed vanilla message, so receivers can dispatch on the type word
+/// instead of the payload length.
+/// This matches Solidity's `abi.encode(uint16, uint256)` with `VANILLA_TYPE`.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+pub fn encode_typed_vanilla(ball: &[u8; 32]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(TYPED_VANILLA_LEN);
+    push_u64_word(&mut encoded, VANILLA_TYPE as u64);
+    encoded.extend_from_slice(ball);
+    encoded
+}
+
+/// Decode a message produced by `encode_typed_vanilla`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 64 bytes)
+///
+/// # Returns
+/// * `Ok([u8; 32])` - The ball
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 64 bytes
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If the message is longer than 64 bytes
+/// * `Err(MsgCodecError::InvalidMessageType)` - If the type word is not a canonical uint16
+/// * `Err(MsgCodecError::UnknownMsgType)` - If the type is not `VANILLA_TYPE`
+pub fn decode_typed_vanilla(message: &[u8]) -> Result<[u8; 32]> {
+    ensure!(message.len() >= TYPED_VANILLA_LEN, MsgCodecError::TooShort);
+    ensure!(message.len() == TYPED_VANILLA_LEN, MsgCodecError::TrailingMessageBytes);
+    let msg_type = read_u16_word(&message[0..32]).ok_or(MsgCodecError::InvalidMessageType)?;
+    ensure!(msg_type == VANILLA_TYPE, MsgCodecError::UnknownMsgType);
+    decode(&message[UINT256_SIZE..])
+}
+
+/// Encode a counter of the bounce-counter game mode.
+/// This matches Solidity's `abi.encode(uint16, uint256)` with `COUNTER_TYPE`, the typed
+/// vanilla layout with its own type.
+///
+/// # Arguments
+/// * `counter` - A 32-byte array representing the uint256 in big-endian format
+pub fn encode_counter(counter: &[u8; 32]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(TYPED_VANILLA_LEN);
+    push_u64_word(&mut encoded, COUNTER_TYPE as u64);
+    encoded.extend_from_slice(counter);
+    encoded
+}
+
+/// Decode a message produced by `encode_counter`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 64 bytes)
+///
+/// # Returns
+/// * `Ok([u8; 32])` - The counter
+/// * `Err(_)` - Any error listed for `decode_typed_vanilla`, with `COUNTER_TYPE` as the
+///   expected type
+pub fn decode_counter(message: &[u8]) -> Result<[u8; 32]> {
+    ensure!(message.len() >= TYPED_VANILLA_LEN, MsgCodecError::TooShort);
+    ensure!(message.len() == TYPED_VANILLA_LEN, MsgCodecError::TrailingMessageBytes);
+    let msg_type = read_u16_word(&message[0..32]).ok_or(MsgCodecError::InvalidMessageType)?;
+    ensure!(msg_type == COUNTER_TYPE, MsgCodecError::UnknownMsgType);
+    decode(&message[UINT256_SIZE..])
+}
+
+/// The counter that follows `counter`, wrapping past the uint256 maximum to zero.
+pub fn increment_counter(counter: &[u8; 32]) -> [u8; 32] {
+    U256::from_be_bytes(*counter).wrapping_add(U256::ONE).to_be_bytes()
+}
+
+/// Encode the compose message lz_receive hands to a local composer:
+/// `abi.encode(uint256 ball, bytes32 guid)` with the new ball and the inbound guid.
+pub fn encode_ball_compose(ball: &[u8; 32], guid: &[u8; 32]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(2 * UINT256_SIZE);
+    encoded.extend_from_slice(ball);
+    encoded.extend_from_slice(guid);
+    encoded
+}
+
+/// Decode a message produced by `encode_ball_compose`, for composer programs.
+///
+/// # Arguments
+/// * `message` - The compose message (must be exactly 64 bytes)
+///
+/// # Returns
+/// * `Ok(([u8; 32], [u8; 32]))` - The ball and the inbound guid
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 64 bytes
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If the message is longer than 64 bytes
+pub fn decode_ball_compose(message: &[u8]) -> Result<([u8; 32], [u8; 32])> {
+    ensure!(message.len() >= 2 * UINT256_SIZE, MsgCodecError::TooShort);
+    ensure!(message.len() == 2 * UINT256_SIZE, MsgCodecError::TrailingMessageBytes);
+    Ok((decode(&message[..UINT256_SIZE])?, decode(&message[UINT256_SIZE..])?))
+}
+
+/// Decrement a `U256` by one, returning `None` instead of wrapping below zero.
+pub fn checked_decrement(value: U256) -> Option<U256> {
+    value.checked_sub(U256::ONE)
+}
+
+/// Encode a signed int256 value into a message.
+/// This matches Solidity's `abi.encode(int256)`: 32 bytes of big-endian two's complement.
+///
+/// # Arguments
+/// * `value` - The signed value to encode
+pub fn encode_i256(value: I256) -> Vec<u8> {
+    value.to_be_bytes().to_vec()
+}
+
+/// Decode a message into a signed int256 value.
+/// This matches Solidity's `abi.decode(bytes, (int256))`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 32 bytes)
+///
+/// # Returns
+/// * `Ok(I256)` - The decoded signed value
+/// * `Err(_)` - Any error from `decode`
+pub fn decode_i256(message: &[u8]) -> Result<I256> {
+    Ok(I256::from_be_bytes(decode(message)?))
+}
+
+/// Compare two 32-byte two's-complement values as signed integers.
+pub fn compare_i256(a: &[u8; 32], b: &[u8; 32]) -> Ordering {
+    I256::from_be_bytes(*a).cmp(&I256::from_be_bytes(*b))
+}
+
+/// Decrement a 32-byte two's-complement value by one, crossing zero into negative values
+/// and saturating at `I256::MIN` instead of wrapping.
+pub fn decrement_i256(ball: &[u8; 32]) -> [u8; 32] {
+    I256::from_be_bytes(*ball).saturating_sub(I256::ONE).to_be_bytes()
+}
+
+/// Decrement a ball by one. Unsigned balls saturate at zero; signed balls go negative.
+///
+/// # Arguments
+/// * `ball` - The ball; signed balls are stored as their two's-complement bits
+/// * `signed` - Whether the ball is interpreted as an int256 rather than a uint256
+pub fn decrement_ball(ball: U256, signed: bool) -> U256 {
+    decrement_ball_by(ball, U256::ONE, signed)
+}
+
+/// Decrement a ball by `step`, saturating like `decrement_ball`: unsigned balls stop at
+/// zero, signed balls at `I256::MIN`.
+///
+/// # Arguments
+/// * `ball` - The ball; signed balls are stored as their two's-complement bits
+/// * `step` - The unsigned amount to subtract
+/// * `signed` - Whether the ball is interpreted as an int256 rather than a uint256
+pub fn decrement_ball_by(ball: U256, step: U256, signed: bool) -> U256 {
+    if signed {
+        // Subtract in chunks that stay positive as int256 values; at most three are needed
+        let max_chunk = U256::from_be_bytes(I256::MAX.to_be_bytes());
+        let mut ball = I256::from_be_bytes(ball.to_be_bytes());
+        let mut step = step;
+        while step > U256::ZERO {
+            let chunk = step.min(max_chunk);
+            ball = ball.saturating_sub(I256::from_be_bytes(chunk.to_be_bytes()));
+            step -= chunk;
+        }
+        U256::from_be_bytes(ball.to_be_bytes())
+    } else {
+        ball.saturating_sub(step)
+    }
+}
+
+/// Decrement a ball by `step`, or `None` if the result would fall below zero (unsigned) or
+/// `I256::MIN` (signed).
+///
+/// # Arguments
+/// * `ball` - The ball; signed balls are stored as their two's-complement bits
+/// * `step` - The unsigned amount to subtract
+/// * `signed` - Whether the ball is interpreted as an int256 rather than a uint256
+pub fn checked_decrement_ball_by(ball: U256, step: U256, signed: bool) -> Option<U256> {
+    if signed {
+        // Distance from I256::MIN, as an unsigned value: the bits with the sign bit flipped
+        let mut headroom = ball.to_be_bytes();
+        headroom[0] ^= 0x80;
+        (step <= U256::from_be_bytes(headroom)).then(|| ball.wrapping_sub(step))
+    } else {
+        ball.checked_sub(step)
+    }
+}
+
+/// Decrement a ball by `step` modulo 2^256. Signed and unsigned balls wrap to the same bits.
+pub fn wrapping_decrement_ball_by(ball: U256, step: U256) -> U256 {
+    ball.wrapping_sub(step)
+}
+
+/// Render a ball as a decimal string, e.g. `-1` for an all-ones signed ball.
+///
+/// # Arguments
+/// * `ball` - The ball; signed balls are stored as their two's-complement bits
+/// * `signed` - Whether the ball is interpreted as an int256 rather than a uint256
+pub fn ball_to_string(ball: U256, signed: bool) -> String {
+    if signed {
+        I256::from_be_bytes(ball.to_be_bytes()).to_string()
+    } else {
+        ball.to_string()
+    }
+}
+
+/// Read the message type without decoding the rest of the message.
+/// Returns `VANILLA_TYPE` for 32-byte payloads, the leading type word (bytes 30-31) for
+/// 64-byte typed vanilla payloads and the uint16 type word (bytes 62-63) for anything
+/// longer. The remaining layout is not validated here.
+/// A leading version header (see `encode_v2`) is checked and skipped.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(u16)` - The message type
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 32 bytes or between 33
+///   and 63 bytes long
+/// * `Err(MsgCodecError::UnsupportedCodecVersion)` - If the version header is not understood
+pub fn peek_msg_type(message: &[u8]) -> Result<u16> {
+    let message = unwrap_tuple(strip_version(message)?);
+    if message.len() == UINT256_SIZE {
+        return Ok(VANILLA_TYPE);
+    }
+    ensure!(message.len() >= 2 * UINT256_SIZE, MsgCodecError::TooShort);
+    if message.len() == TYPED_VANILLA_LEN {
+        return Ok(u16::from_be_bytes([message[30], message[31]]));
+    }
+    Ok(u16::from_be_bytes([message[62], message[63]]))
+}
+
+/// Encode a uint256 value with ABA pattern (includes message type and return options).
+/// This matches `AbaMessage::encode` with the hop count and origin guid left at zero;
+/// use `AbaMessage::encode` directly to set those fields.
+/// 
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `msg_type` - The message type, e.g. `ABA_TYPE` for pings or `PONG_TYPE` for returns
+/// * `return_options` - Options for the return message
+/// 
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If `return_options` exceeds `MAX_RETURN_OPTIONS_LEN`
+pub fn encode_aba(ball: &[u8; 32], msg_type: u16, return_options: &[u8]) -> Result<Vec<u8>> {
+    AbaMessage {
+        ball: *ball,
+        msg_type,
+        return_options: return_options.to_vec(),
+        ..Default::default()
+    }
+    .encode()
+}
+
+/// Encode a return (PONG) message that echoes the GUID of the message it answers,
+/// so the origin chain can match request/response pairs deterministically.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `origin_guid` - GUID of the inbound message being answered
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+pub fn encode_return(ball: &[u8; 32], origin_guid: &[u8; 32]) -> Result<Vec<u8>> {
+    AbaMessage {
+        ball: *ball,
+        msg_type: PONG_TYPE,
+        origin_guid: *origin_guid,
+        ..Default::default()
+    }
+    .encode()
+}
+
+/// Encode the message announcing that the ball has reached zero.
+/// It uses the ABA layout with `GAME_OVER_TYPE` and no return options.
+///
+/// # Arguments
+/// * `final_ball` - The ball the game ended on
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+pub fn encode_game_over(final_ball: &[u8; 32]) -> Result<Vec<u8>> {
+    encode_aba(final_ball, GAME_OVER_TYPE, &[])
+}
+
+/// Decode a message produced by `encode_game_over`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok([u8; 32])` - The final ball
+/// * `Err(MsgCodecError::InvalidMessageType)` - If the message is not a `GAME_OVER_TYPE` message
+/// * `Err(_)` - Any error from `decode_aba`
+pub fn decode_game_over(message: &[u8]) -> Result<[u8; 32]> {
+    let message = decode_aba_ref(message)?;
+    ensure!(message.msg_type == GAME_OVER_TYPE, MsgCodecError::InvalidMessageType);
+    Ok(message.ball)
+}
+
+/// Decode an ABA message format, requiring the canonical ABI layout.
+/// Handles both vanilla (32 bytes) and ABA (>= 128 bytes) formats.
+/// The options offset doubles as the head length, which tells the layouts apart:
+/// 96 for the legacy `abi.encode(uint256, uint16, bytes)`, up to `ABA_HEAD_LEN` for the
+/// current layout. Head fields missing from shorter layouts decode as zero or empty.
+/// Each dynamic field must start exactly where the previous one ends, so tails can never
+/// overlap, and nothing may follow the last zero-padded tail.
+/// Struct-wrapped messages (`abi.encode(MyStruct)`) are detected and decoded to the same
+/// `AbaMessage`; see `decode_aba_wrapped`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded ABA message
+/// * `Err(MsgCodecError::MessageTooLarge)` - If the message exceeds `MAX_MESSAGE_LEN`
+/// * `Err(MsgCodecError::TooShort)` - If the message ends inside the head, a length word or
+///   padding
+/// * `Err(MsgCodecError::LengthMismatch)` - If a length word runs past the end of the message
+/// * `Err(MsgCodecError::InvalidMessageType)` - If the type word is not a canonical uint16
+/// * `Err(MsgCodecError::UnknownMsgType)` - If the type is not `ABA_TYPE`, `PONG_TYPE` or
+///   `GAME_OVER_TYPE`
+/// * `Err(MsgCodecError::InvalidMessageLength)` - If the hop count or sequence word overflows
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If an offset word is not where the ABI puts it
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If padding is dirty or bytes follow the last tail
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If the return options exceed `MAX_RETURN_OPTIONS_LEN`
+/// * `Err(MsgCodecError::ComposeMsgTooLong)` - If the compose message exceeds `MAX_COMPOSE_MSG_LEN`
+/// * `Err(MsgCodecError::ZeroStep)` - If the head carries a step word and it is zero
+pub fn decode_aba(message: &[u8]) -> Result<AbaMessage> {
+    decode_aba_ref(message).map(AbaMessageRef::into_owned)
+}
+
+/// Decode like `decode_aba`, borrowing the variable-length fields from `message` instead of
+/// copying them. The same validation is applied.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessageRef)` - Decoded ABA message borrowing from `message`
+/// * `Err(_)` - Any error listed for `decode_aba`
+pub fn decode_aba_ref(message: &[u8]) -> Result<AbaMessageRef<'_>> {
+    ensure!(message.len() <= MAX_MESSAGE_LEN, MsgCodecError::MessageTooLarge);
+    let message = unwrap_tuple(message);
+
+    // Vanilla format: 32 bytes (just uint256)
+    if message.len() == UINT256_SIZE {
+        let mut ball = [0u8; 32];
+        ball.copy_from_slice(&message[0..32]);
+        return Ok(AbaMessageRef { ball, msg_type: VANILLA_TYPE, ..Default::default() });
+    }
+
+    // ABA format: minimum 128 bytes (32 uint256 + 32 uint16 padded + 32 offset + 32 length)
+    ensure!(message.len() >= ABA_MIN_SIZE, MsgCodecError::TooShort);
+
+    let mut ball = [0u8; 32];
+    ball.copy_from_slice(&message[0..32]);
+    let msg_type = read_u16_word(&message[32..64]).ok_or(MsgCodecError::InvalidMessageType)?;
+    ensure!(is_aba_type(msg_type), MsgCodecError::UnknownMsgType);
+
+    // The whole offset word must equal a known head length, not just its low bytes
+    let head_len = read_size_word(&message[64..96])
+        .filter(|offset| {
+            offset % UINT256_SIZE == 0 && (ABA_LEGACY_HEAD_LEN..=ABA_HEAD_LEN).contains(offset)
+        })
+        .ok_or(MsgCodecError::InvalidMessageOffset)?;
+    ensure!(message.len() >= head_len, MsgCodecError::TooShort);
+
+    let hop_count = match head_word(message, head_len, ABA_HOP_COUNT_WORD) {
+        Some(word) => read_u16_word(word).ok_or(MsgCodecError::InvalidMessageLength)?,
+        None => 0,
+    };
+    let origin_guid = read_bytes32_word(message, head_len, ABA_ORIGIN_GUID_WORD);
+    let origin_caller = read_bytes32_word(message, head_len, ABA_ORIGIN_CALLER_WORD);
+    let seq = match head_word(message, head_len, ABA_SEQ_WORD) {
+        Some(word) => read_u64_word(word).ok_or(MsgCodecError::InvalidMessageLength)?,
+        None => 0,
+    };
+    // A zero step would bounce the ball forever without it ever reaching zero
+    let step = read_bytes32_word(message, head_len, ABA_STEP_WORD);
+    ensure!(
+        head_word(message, head_len, ABA_STEP_WORD).is_none() || step != [0u8; 32],
+        MsgCodecError::ZeroStep
+    );
+
+    let (return_options, mut end) = read_bytes_tail(message, head_len)?;
+    ensure!(return_options.len() <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+
+    // Later tails, when present in the head, must each start right after the previous one
+    let compose_msg = read_next_tail(message, head_len, ABA_COMPOSE_OFFSET_WORD, &mut end)?;
+    ensure!(compose_msg.len() <= MAX_COMPOSE_MSG_LEN, MsgCodecError::ComposeMsgTooLong);
+    let memo = read_next_tail(message, head_len, ABA_MEMO_OFFSET_WORD, &mut end)?;
+    ensure!(memo.len() <= MAX_MEMO_LEN, MsgCodecError::MemoTooLong);
+    let extensions = read_next_tail(message, head_len, ABA_EXTENSIONS_OFFSET_WORD, &mut end)?;
+    validate_extensions(extensions)?;
+    ensure!(message.len() == end, MsgCodecError::TrailingMessageBytes);
+
+    Ok(AbaMessageRef {
+        ball,
+        msg_type,
+        return_options,
+        hop_count,
+        origin_guid,
+        compose_msg,
+        memo,
+        origin_caller,
+        seq,
+        extensions,
+        step,
+    })
+}
+
+/// Decode a struct-wrapped ABA message, as produced by `abi.encode(MyStruct)` on the EVM side.
+/// Solidity encodes a dynamic struct as a single tuple parameter, so the message starts with
+/// a 0x20 offset word pointing at the tuple body; the body is the plain ABA layout, with its
+/// offsets relative to the start of the tuple.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded ABA message, identical to decoding the unwrapped layout
+/// * `Err(MsgCodecError::MessageTooLarge)` - If the message exceeds `MAX_MESSAGE_LEN`
+/// * `Err(MsgCodecError::TooShort)` - If the message cannot hold the tuple offset and body
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If the leading word is not 0x20
+/// * Any error returned by `decode_aba` for the tuple body
+pub fn decode_aba_wrapped(message: &[u8]) -> Result<AbaMessage> {
+    ensure!(message.len() <= MAX_MESSAGE_LEN, MsgCodecError::MessageTooLarge);
+    ensure!(message.len() >= UINT256_SIZE + ABA_MIN_SIZE, MsgCodecError::TooShort);
+    ensure!(
+        read_size_word(&message[0..32]) == Some(UINT256_SIZE),
+        MsgCodecError::InvalidMessageOffset
+    );
+    decode_aba(&message[UINT256_SIZE..])
+}
+
+/// Decode an ABA message format without canonical-encoding checks.
+/// Handles both vanilla (32 bytes) and ABA (>= 128 bytes) formats.
+/// Any offset >= 96 is accepted and bytes after the return options are ignored.
+/// Prefer `decode_aba` unless you need to accept loosely encoded payloads.
+/// 
+/// # Arguments
+/// * `message` - The encoded message bytes
+/// 
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded ABA message with ball, msg_type, and return_options
+/// * `Err(MsgCodecError::MessageTooLarge)` - If the message exceeds `MAX_MESSAGE_LEN`
+/// * `Err(MsgCodecError::TooShort)` - If the message ends inside the head or a length word
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If the options offset is below 96
+/// * `Err(MsgCodecError::LengthMismatch)` - If a length word runs past the end of the message
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If the return options exceed `MAX_RETURN_OPTIONS_LEN`
+pub fn decode_aba_lenient(message: &[u8]) -> Result<AbaMessage> {
+    ensure!(message.len() <= MAX_MESSAGE_LEN, MsgCodecError::MessageTooLarge);
+    let message = unwrap_tuple(message);
+
+    // Vanilla format: 32 bytes (just uint256)
+    if message.len() == UINT256_SIZE {
+        let mut ball = [0u8; 32];
+        ball.copy_from_slice(&message[0..32]);
+        return Ok(AbaMessage { ball, msg_type: VANILLA_TYPE, ..Default::default() });
+    }
+    
+    // ABA format: minimum 128 bytes (32 uint256 + 32 uint16 padded + 32 offset + 32 length)
+    // For empty return_options, the message will be exactly 128 bytes
+    ensure!(message.len() >= 128, MsgCodecError::TooShort);
+    
+    // Decode ball (uint256) - first 32 bytes (bytes 0-31)
+    let mut ball = [0u8; 32];
+    ball.copy_from_slice(&message[0..32]);
+    
+    // Decode msg_type (uint16) - bytes 32-63, actual value in last 2 bytes (bytes 62-63)
+    let msg_type = u16::from_be_bytes([message[62], message[63]]);
+    
+    // Decode return_options offset - bytes 64-95. The offset and length are full uint256
+    // words; values that cannot fit in the message are rejected before any indexing.
+    let offset = read_size_word(&message[64..96]).ok_or(MsgCodecError::InvalidMessageOffset)?;
+    
+    // Validate offset is reasonable (should point to where the length field starts)
+    // In ABI encoding for (uint256, uint16, bytes), the offset is 96
+    // Offset: 32 (ball) + 32 (msg_type) + 32 (offset field) = 96
+    ensure!(offset >= 96, MsgCodecError::InvalidMessageOffset);
+    let data_start = offset.checked_add(UINT256_SIZE).ok_or(MsgCodecError::InvalidMessageOffset)?;
+    ensure!(message.len() >= data_start, MsgCodecError::TooShort);
+    
+    // Decode return_options length - the word at offset
+    let len = read_size_word(&message[offset..data_start]).ok_or(MsgCodecError::LengthMismatch)?;
+    ensure!(len <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+    
+    // Validate we have enough bytes for the data after the length field
+    let data_end = data_start.checked_add(len).ok_or(MsgCodecError::LengthMismatch)?;
+    ensure!(message.len() >= data_end, MsgCodecError::LengthMismatch);
+    let return_options = message[data_start..data_end].to_vec();
+
+    // Extra head words are only present when the head was extended past the legacy 96 bytes
+    let hop_count = head_word(message, offset, ABA_HOP_COUNT_WORD)
+        .map(|word| u16::from_be_bytes([word[30], word[31]]))
+        .unwrap_or(0);
+    let origin_guid = read_bytes32_word(message, offset, ABA_ORIGIN_GUID_WORD);
+    let origin_caller = read_bytes32_word(message, offset, ABA_ORIGIN_CALLER_WORD);
+    let seq = head_word(message, offset, ABA_SEQ_WORD)
+        .map(|word| word[24..].iter().fold(0u64, |acc, byte| (acc << 8) | *byte as u64))
+        .unwrap_or(0);
+    let step = read_bytes32_word(message, offset, ABA_STEP_WORD);
+    // Later tails may sit anywhere their offsets point, as long as they are in bounds
+    let compose_msg = read_tail_lenient(message, offset, ABA_COMPOSE_OFFSET_WORD)?.to_vec();
+    ensure!(compose_msg.len() <= MAX_COMPOSE_MSG_LEN, MsgCodecError::ComposeMsgTooLong);
+    let memo = read_tail_lenient(message, offset, ABA_MEMO_OFFSET_WORD)?.to_vec();
+    ensure!(memo.len() <= MAX_MEMO_LEN, MsgCodecError::MemoTooLong);
+    let extensions =
+        decode_extensions(read_tail_lenient(message, offset, ABA_EXTENSIONS_OFFSET_WORD)?)?;
+    
+    Ok(AbaMessage {
+        ball,
+        msg_type,
+        return_options,
+        hop_count,
+        origin_guid,
+        compose_msg,
+        memo,
+        origin_caller,
+        seq,
+        extensions,
+        step,
+    })
+}
+
+/// Encode a uint256 value with ABA pattern and a list of TLV extensions.
+/// Receivers that predate an extension type skip it; see `decode_extensions`.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `return_options` - Options for the return message (B→A)
+/// * `exts` - `(type, value)` pairs, encoded in the given order
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+/// * `Err(MsgCodecError::ExtensionsTooLong)` - If the extensions exceed `MAX_EXTENSIONS_LEN`
+/// * `Err(_)` - Any other error from `AbaMessage::encode`
+pub fn encode_aba_ext(
+    ball: &[u8; 32],
+    return_options: &[u8],
+    exts: &[(u8, &[u8])],
+) -> Result<Vec<u8>> {
+    AbaMessage {
+        ball: *ball,
+        msg_type: ABA_TYPE,
+        return_options: return_options.to_vec(),
+        extensions: exts
+            .iter()
+            .map(|(ext_type, value)| Extension { ext_type: *ext_type, value: value.to_vec() })
+            .collect(),
+        ..Default::default()
+    }
+    .encode()
+}
+
+/// Encode a uint256 value with ABA pattern using the compact packed layout.
+/// This matches Solidity's `abi.encodePacked(uint256, uint16, bytes)`: the ball, the 2-byte
+/// message type and the raw return options, with no offset or length words.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `msg_type` - The message type; must not be `VANILLA_TYPE`
+/// * `return_options` - Options for the return message
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - `32 + 2 + return_options.len()` encoded bytes
+/// * `Err(MsgCodecError::InvalidMessageType)` - If `msg_type` is `VANILLA_TYPE`
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If `return_options` exceeds `MAX_RETURN_OPTIONS_LEN`
+pub fn encode_packed(ball: &[u8; 32], msg_type: u16, return_options: &[u8]) -> Result<Vec<u8>> {
+    // A zero type would make the packed header look like a canonical ABI type word
+    ensure!(msg_type != VANILLA_TYPE, MsgCodecError::InvalidMessageType);
+    ensure!(return_options.len() <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+
+    let mut encoded = Vec::with_capacity(PACKED_DATA_OFFSET + return_options.len());
+    encoded.extend_from_slice(ball);
+    encoded.extend_from_slice(&msg_type.to_be_bytes());
+    encoded.extend_from_slice(return_options);
+    Ok(encoded)
+}
+
+/// Decode a message produced by `encode_packed`.
+/// Everything after the 2-byte message type is taken as the return options.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (at least 34 bytes)
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded ABA message with ball, msg_type, and return_options
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 34 bytes
+/// * `Err(MsgCodecError::InvalidMessageType)` - If the message type is `VANILLA_TYPE`
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If the return options exceed `MAX_RETURN_OPTIONS_LEN`
+pub fn decode_packed(message: &[u8]) -> Result<AbaMessage> {
+    ensure!(message.len() >= PACKED_DATA_OFFSET, MsgCodecError::TooShort);
+
+    let mut ball = [0u8; 32];
+    ball.copy_from_slice(&message[0..32]);
+    let msg_type = u16::from_be_bytes([message[32], message[33]]);
+    ensure!(msg_type != VANILLA_TYPE, MsgCodecError::InvalidMessageType);
+
+    let return_options = &message[PACKED_DATA_OFFSET..];
+    ensure!(return_options.len() <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+
+    Ok(AbaMessage { ball, msg_type, return_options: return_options.to_vec(), ..Default::default() })
+}
+
+/// Encode a ball for a compact-capable peer.
+/// This matches Solidity's `abi.encodePacked(uint128)`: 16 big-endian bytes.
+///
+/// # Arguments
+/// * `value` - The ball; use `ball_as_u128` to narrow a stored 32-byte ball
+pub fn encode_compact(value: u128) -> Vec<u8> {
+    value.to_be_bytes().to_vec()
+}
+
+/// Decode a message produced by `encode_compact`.
+/// Only exactly 16 bytes are accepted, so a truncated or full-width 32-byte ball is never
+/// read as a compact one.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (must be exactly 16 bytes)
+///
+/// # Returns
+/// * `Ok(u128)` - The decoded ball
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 16 bytes
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If the message is longer than 16 bytes
+pub fn decode_compact(message: &[u8]) -> Result<u128> {
+    ensure!(message.len() >= COMPACT_BALL_SIZE, MsgCodecError::TooShort);
+    ensure!(message.len() == COMPACT_BALL_SIZE, MsgCodecError::TrailingMessageBytes);
+    let mut value = [0u8; COMPACT_BALL_SIZE];
+    value.copy_from_slice(message);
+    Ok(u128::from_be_bytes(value))
+}
+
+/// Encode an ABA message for a compact-capable peer.
+/// This matches Solidity's `abi.encodePacked(uint128, uint16, bytes)`: the `encode_packed`
+/// layout with a 16-byte ball. Only the ball, type and return options are carried.
+///
+/// # Arguments
+/// * `ball` - The ball; use `ball_as_u128` to narrow a stored 32-byte ball
+/// * `msg_type` - The message type; must not be `VANILLA_TYPE`
+/// * `return_options` - Options for the return message
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - `16 + 2 + return_options.len()` encoded bytes
+/// * `Err(MsgCodecError::InvalidMessageType)` - If `msg_type` is `VANILLA_TYPE`
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If `return_options` exceeds `MAX_RETURN_OPTIONS_LEN`
+pub fn encode_aba_compact(ball: u128, msg_type: u16, return_options: &[u8]) -> Result<Vec<u8>> {
+    ensure!(msg_type != VANILLA_TYPE, MsgCodecError::InvalidMessageType);
+    ensure!(return_options.len() <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+
+    let mut encoded = Vec::with_capacity(COMPACT_DATA_OFFSET + return_options.len());
+    encoded.extend_from_slice(&ball.to_be_bytes());
+    encoded.extend_from_slice(&msg_type.to_be_bytes());
+    encoded.extend_from_slice(return_options);
+    Ok(encoded)
+}
+
+/// Decode a message produced by `encode_aba_compact`, widening the ball to 32 bytes.
+/// The layout has no self-describing header, so callers choose it from the peer's
+/// configuration rather than by inspecting the message.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (at least 18 bytes)
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded ABA message with ball, msg_type, and return_options
+/// * `Err(MsgCodecError::TooShort)` - If the message is shorter than 18 bytes
+/// * `Err(MsgCodecError::UnknownMsgType)` - If the type is not `ABA_TYPE`, `PONG_TYPE` or
+///   `GAME_OVER_TYPE`
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If the return options exceed `MAX_RETURN_OPTIONS_LEN`
+pub fn decode_aba_compact(message: &[u8]) -> Result<AbaMessage> {
+    decode_aba_compact_ref(message).map(AbaMessageRef::into_owned)
+}
+
+/// Decode like `decode_aba_compact`, borrowing the return options from `message`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes (at least 18 bytes)
+///
+/// # Returns
+/// * `Ok(AbaMessageRef)` - Decoded ABA message borrowing from `message`
+/// * `Err(_)` - Any error listed for `decode_aba_compact`
+pub fn decode_aba_compact_ref(message: &[u8]) -> Result<AbaMessageRef<'_>> {
+    ensure!(message.len() >= COMPACT_DATA_OFFSET, MsgCodecError::TooShort);
+
+    let ball = ball_from_u128(decode_compact(&message[..COMPACT_BALL_SIZE])?);
+    let msg_type = u16::from_be_bytes([message[COMPACT_BALL_SIZE], message[COMPACT_BALL_SIZE + 1]]);
+    ensure!(is_aba_type(msg_type), MsgCodecError::UnknownMsgType);
+
+    let return_options = &message[COMPACT_DATA_OFFSET..];
+    ensure!(return_options.len() <= MAX_RETURN_OPTIONS_LEN, MsgCodecError::ReturnOptionsTooLong);
+
+    Ok(AbaMessageRef { ball, msg_type, return_options, ..Default::default() })
+}
+
+/// Decode a vanilla, ABI-encoded ABA or packed ABA message, detecting the layout.
+/// The layouts cannot be confused: a canonical ABI type word starts with 30 zero bytes,
+/// while a packed message always has a non-zero type at bytes 32-33.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded message; vanilla payloads report `VANILLA_TYPE`
+/// * `Err(_)` - Any error from `decode_aba` or `decode_packed` for the detected layout
+pub fn decode_auto(message: &[u8]) -> Result<AbaMessage> {
+    if message.len() == UINT256_SIZE {
+        return decode_aba(message);
+    }
+    ensure!(message.len() >= PACKED_DATA_OFFSET, MsgCodecError::TooShort);
+    if message[32..34] == [0, 0] {
+        decode_aba(message)
+    } else {
+        decode_packed(message)
+    }
+}
+
+/// Encode a uint256 value with a short human-readable memo.
+/// This matches Solidity's `abi.encode(uint256, string)`.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `memo` - The memo text (at most `MAX_MEMO_LEN` bytes)
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+/// * `Err(MsgCodecError::MemoTooLong)` - If `memo` exceeds `MAX_MEMO_LEN`
+pub fn encode_with_memo(ball: &[u8; 32], memo: &str) -> Result<Vec<u8>> {
+    ensure!(memo.len() <= MAX_MEMO_LEN, MsgCodecError::MemoTooLong);
+    let mut encoded = Vec::with_capacity(MEMO_MSG_OFFSET + bytes_tail_len(memo.as_bytes()));
+    encoded.extend_from_slice(ball);
+    push_u64_word(&mut encoded, MEMO_MSG_OFFSET as u64);
+    push_bytes_tail(&mut encoded, memo.as_bytes());
+    Ok(encoded)
+}
+
+/// Decode a message produced by `encode_with_memo` / Solidity's `abi.encode(uint256, string)`.
+/// Invalid UTF-8 in the memo is replaced rather than rejected, since the memo is only
+/// informational and should never cause a message to be dropped.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(([u8; 32], String))` - The ball and the (lossily decoded) memo
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If the offset word is not 64
+/// * `Err(MsgCodecError::TooShort)` - If the message is truncated
+/// * `Err(MsgCodecError::LengthMismatch)` - If the length word runs past the end of the message
+/// * `Err(MsgCodecError::TrailingMessageBytes)` - If padding is dirty or bytes follow the memo
+/// * `Err(MsgCodecError::MemoTooLong)` - If the memo exceeds `MAX_MEMO_LEN`
+pub fn decode_with_memo(message: &[u8]) -> Result<([u8; 32], String)> {
+    ensure!(message.len() >= MEMO_MSG_OFFSET, MsgCodecError::TooShort);
+    let mut ball = [0u8; 32];
+    ball.copy_from_slice(&message[0..32]);
+    ensure!(
+        read_u64_word(&message[32..64]) == Some(MEMO_MSG_OFFSET as u64),
+        MsgCodecError::InvalidMessageOffset
+    );
+    let (memo, end) = read_bytes_tail(message, MEMO_MSG_OFFSET)?;
+    ensure!(memo.len() <= MAX_MEMO_LEN, MsgCodecError::MemoTooLong);
+    ensure!(message.len() == end, MsgCodecError::TrailingMessageBytes);
+    Ok((ball, String::from_utf8_lossy(memo).into_owned()))
+}
+
+/// Encode an array of uint256 values.
+/// This matches Solidity's `abi.encode(uint256[])`: an offset word (32), a length word
+/// and the elements.
+///
+/// # Arguments
+/// * `values` - The uint256 values as 32-byte big-endian arrays
+///
+/// # Returns
+/// * Encoded message bytes (`64 + 32 * values.len()`)
+pub fn encode_batch(values: &[[u8; 32]]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(2 * UINT256_SIZE + values.len() * UINT256_SIZE);
+    push_u64_word(&mut encoded, UINT256_SIZE as u64);
+    push_uint256_array(&mut encoded, values);
+    encoded
+}
+
+/// Decode a message produced by `encode_batch` / Solidity's `abi.encode(uint256[])`.
+/// The declared length is checked against the message size before anything is allocated.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(Vec<[u8; 32]>)` - The decoded values
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If the offset word is not 32
+/// * `Err(MsgCodecError::TooShort)` - If the message ends inside the head
+/// * `Err(MsgCodecError::LengthMismatch)` - If the length word disagrees with the message size
+/// * `Err(MsgCodecError::InvalidBatchLength)` - If the array has more than `MAX_BATCH_LEN` elements
+pub fn decode_batch(message: &[u8]) -> Result<Vec<[u8; 32]>> {
+    ensure!(message.len() >= 2 * UINT256_SIZE, MsgCodecError::TooShort);
+    ensure!(
+        read_u64_word(&message[0..32]) == Some(UINT256_SIZE as u64),
+        MsgCodecError::InvalidMessageOffset
+    );
+    read_uint256_array(message, UINT256_SIZE)
+}
+
+/// Encode a typed batch message: `abi.encode(uint256, uint16, uint256[])` with `BATCH_TYPE`.
+/// The leading uint256 repeats the last value so the message shares the ABA head shape
+/// (ball, type, offset) and can be routed with `peek_msg_type`.
+///
+/// # Arguments
+/// * `values` - The uint256 values as 32-byte big-endian arrays (at least one)
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Encoded message bytes
+/// * `Err(MsgCodecError::InvalidBatchLength)` - If `values` is empty or longer than `MAX_BATCH_LEN`
+pub fn encode_batch_message(values: &[[u8; 32]]) -> Result<Vec<u8>> {
+    let last = values.last().ok_or(MsgCodecError::InvalidBatchLength)?;
+    ensure!(values.len() <= MAX_BATCH_LEN, MsgCodecError::InvalidBatchLength);
+
+    let mut encoded =
+        Vec::with_capacity(ABA_LEGACY_HEAD_LEN + UINT256_SIZE + values.len() * UINT256_SIZE);
+    encoded.extend_from_slice(last);
+    push_u64_word(&mut encoded, BATCH_TYPE as u64);
+    push_u64_word(&mut encoded, ABA_LEGACY_HEAD_LEN as u64);
+    push_uint256_array(&mut encoded, values);
+    Ok(encoded)
+}
+
+/// Decode a message produced by `encode_batch_message`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(Vec<[u8; 32]>)` - The decoded values (never empty)
+/// * `Err(MsgCodecError::InvalidMessageType)` - If the type word is not `BATCH_TYPE`
+/// * `Err(MsgCodecError::InvalidMessageOffset)` - If the offset word is not 96
+/// * `Err(MsgCodecError::TooShort)` - If the message ends inside the head
+/// * `Err(MsgCodecError::LengthMismatch)` - If the length word disagrees with the message size
+/// * `Err(MsgCodecError::InvalidBatchLength)` - If the array is empty or exceeds `MAX_BATCH_LEN`
+pub fn decode_batch_message(message: &[u8]) -> Result<Vec<[u8; 32]>> {
+    ensure!(message.len() >= ABA_MIN_SIZE, MsgCodecError::TooShort);
+    ensure!(
+        read_u16_word(&message[32..64]) == Some(BATCH_TYPE),
+        MsgCodecError::InvalidMessageType
+    );
+    ensure!(
+        read_u64_word(&message[64..96]) == Some(ABA_LEGACY_HEAD_LEN as u64),
+        MsgCodecError::InvalidMessageOffset
+    );
+    let values = read_uint256_array(message, ABA_LEGACY_HEAD_LEN)?;
+    ensure!(!values.is_empty(), MsgCodecError::InvalidBatchLength);
+    Ok(values)
+}
+
+/// Encode a message with a leading one-byte `CODEC_VERSION` header.
+/// The body is the legacy layout: `abi.encode(uint256)` for `VANILLA_TYPE`, otherwise
+/// `abi.encode(uint256, uint16, bytes)`. The header makes every versioned message
+/// one byte longer than a multiple of 32, which is how decoders tell them apart.
+///
+/// # Arguments
+/// * `ball` - A 32-byte array representing the uint256 in big-endian format
+/// * `msg_type` - The message type
+/// * `return_options` - Options for the return message; ignored for `VANILLA_TYPE`
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The version byte followed by the legacy encoding
+/// * `Err(MsgCodecError::ReturnOptionsTooLong)` - If `return_options` exceeds `MAX_RETURN_OPTIONS_LEN`
+pub fn encode_v2(ball: &[u8; 32], msg_type: u16, return_options: &[u8]) -> Result<Vec<u8>> {
+    let body = if msg_type == VANILLA_TYPE {
+        encode(ball)
+    } else {
+        encode_aba(ball, msg_type, return_options)?
+    };
+    let mut encoded = Vec::with_capacity(1 + body.len());
+    encoded.push(CODEC_VERSION);
+    encoded.extend_from_slice(&body);
+    Ok(encoded)
+}
+
+/// Decode a versioned message, or a legacy unversioned 32-byte / ABA message.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessage)` - Decoded message; vanilla payloads report `VANILLA_TYPE`
+/// * `Err(MsgCodecError::UnsupportedCodecVersion)` - If the version header is not understood
+/// * `Err(_)` - Any error from `decode_aba` for the message body
+pub fn decode_v2(message: &[u8]) -> Result<AbaMessage> {
+    decode_aba(strip_version(message)?)
+}
+
+/// Decode a versioned or legacy message of any type.
+/// Unlike `decode_aba`, a vanilla payload is reported as its own variant rather than as an
+/// `AbaMessage` with `VANILLA_TYPE`, and only 32-byte and 64-byte typed bodies are vanilla:
+/// a full ABA layout whose type word is 0 is rejected.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(DecodedMessage)` - The decoded message; ABA fields borrow from `message`
+/// * `Err(MsgCodecError::UnsupportedCodecVersion)` - If the version header is not understood
+/// * `Err(_)` - Any error from `decode`, `decode_typed_vanilla`, `decode_counter`,
+///   `decode_batch_message` or `decode_aba_ref` for the body
+pub fn decode_any(message: &[u8]) -> Result<DecodedMessage<'_>> {
+    let body = strip_version(message)?;
+    if body.len() == UINT256_SIZE {
+        return Ok(DecodedMessage::Vanilla(decode(body)?));
+    }
+    // No other layout is exactly two words long; counters share it with their own type
+    if body.len() == TYPED_VANILLA_LEN {
+        if read_u16_word(&body[0..32]) == Some(COUNTER_TYPE) {
+            return Ok(DecodedMessage::Counter(decode_counter(body)?));
+        }
+        return Ok(DecodedMessage::Vanilla(decode_typed_vanilla(body)?));
+    }
+    if peek_msg_type(body)? == BATCH_TYPE {
+        return Ok(DecodedMessage::Batch(decode_batch_message(body)?));
+    }
+    Ok(DecodedMessage::Aba(decode_aba_ref(body)?))
+}
+
+/// Decode like `decode_v2`, additionally converting the ball to a `U256`.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(AbaMessageU256)` - Decoded message with its ball as a `U256`
+/// * `Err(_)` - Any error from `decode_v2`
+pub fn decode_aba_u256(message: &[u8]) -> Result<AbaMessageU256> {
+    let message = decode_v2(message)?;
+    Ok(AbaMessageU256 { ball: message.ball_u256(), message })
+}
+
+/// Prefix a message with the `MAGIC` domain separator.
+/// A 32-byte ball becomes a 36-byte tagged vanilla message.
+///
+/// # Arguments
+/// * `body` - Any message produced by this module, versioned or not
+pub fn encode_tagged(body: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(MAGIC.len() + body.len());
+    encoded.extend_from_slice(&MAGIC);
+    encoded.extend_from_slice(body);
+    encoded
+}
+
+/// Return the body of a message carrying the `MAGIC` prefix.
+///
+/// # Arguments
+/// * `message` - The encoded message bytes
+///
+/// # Returns
+/// * `Ok(&[u8])` - The message with the prefix removed, ready for the other decoders
+/// * `Err(MsgCodecError::ForeignMessage)` - If the message is not tagged
+pub fn decode_tagged(message: &[u8]) -> Result<&[u8]> {
+    match split_tag(message) {
+        (true, body) => Ok(body),
+        (false, _) => Err(MsgCodecError::ForeignMessage),
+    }
+}
+
+/// Split off the `MAGIC` prefix when present.
+/// Only messages whose length leaves room for exactly the prefix (and an optional version
+/// header) are treated as tagged, so a legacy ball that happens to start with `MAGIC` is
+/// never misread.
+///
+/// # Returns
+/// * `(true, body)` - The message was tagged and `body` follows the prefix
+/// * `(false, message)` - The message was untagged and is returned unchanged
+pub fn split_tag(message: &[u8]) -> (bool, &[u8]) {
+    let remainder = message.len() % UINT256_SIZE;
+    let tagged = (remainder == MAGIC.len() || remainder == MAGIC.len() + 1)
+        && message.starts_with(&MAGIC);
+    if tagged {
+        (true, &message[MAGIC.len()..])
+    } else {
+        (false, message)
+    }
+}
+
+/// Tag `body` with `MAGIC` when `tag` is set, otherwise return it unchanged.
+pub fn tag_if(body: Vec<u8>, tag: bool) -> Vec<u8> {
+    if tag {
+        encode_tagged(&body)
+    } else {
+        body
+    }
+}
+
+/// Return the message body with its version header removed.
+/// Legacy messages are a whole number of 32-byte words and are returned unchanged.
+fn strip_version(message: &[u8]) -> Result<&[u8]> {
+    if message.len() % UINT256_SIZE != 1 {
+        return Ok(message);
+    }
+    ensure!(message[0] == CODEC_VERSION, MsgCodecError::UnsupportedCodecVersion);
+    Ok(&message[1..])
+}
+
+/// Whether `msg_type` is one of the types carried in the ABA layout.
+fn is_aba_type(msg_type: u16) -> bool {
+    matches!(msg_type, ABA_TYPE | PONG_TYPE | GAME_OVER_TYPE)
+}
+
+/// Round `len` up to the next multiple of 32, the ABI word size.
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(UINT256_SIZE) * UINT256_SIZE
+}
+
+/// Append `value` as a big-endian, left-zero-padded 32-byte ABI word.
+fn push_u64_word(encoded: &mut Vec<u8>, value: u64) {
+    let mut word = [0u8; 32];
+    word[24..32].copy_from_slice(&value.to_be_bytes());
+    encoded.extend_from_slice(&word);
+}
+
+/// Append `data` as an ABI `bytes` tail: a length word, the data, then zero padding.
+fn push_bytes_tail(encoded: &mut Vec<u8>, data: &[u8]) {
+    push_u64_word(encoded, data.len() as u64);
+    encoded.extend_from_slice(data);
+    encoded.resize(encoded.len() + padded_len(data.len()) - data.len(), 0);
+}
+
+/// Serialize extensions as consecutive `type || length (u16 BE) || value` entries.
+fn encode_extensions(extensions: &[Extension]) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    for extension in extensions {
+        let len = u16::try_from(extension.value.len())
+            .map_err(|_| MsgCodecError::ExtensionsTooLong)?;
+        encoded.push(extension.ext_type);
+        encoded.extend_from_slice(&len.to_be_bytes());
+        encoded.extend_from_slice(&extension.value);
+        ensure!(encoded.len() <= MAX_EXTENSIONS_LEN, MsgCodecError::ExtensionsTooLong);
+    }
+    Ok(encoded)
+}
+
+/// Parse the TLV extension area. Every entry is returned, known or not, so callers decide
+/// which types they understand. Lengths come from the sender and are checked against the
+/// remaining bytes before any value is sliced.
+fn decode_extensions(data: &[u8]) -> Result<Vec<Extension>> {
+    ensure!(data.len() <= MAX_EXTENSIONS_LEN, MsgCodecError::ExtensionsTooLong);
+    let mut extensions = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (ext_type, value, next) = split_extension(rest)?;
+        extensions.push(Extension { ext_type, value: value.to_vec() });
+        rest = next;
+    }
+    Ok(extensions)
+}
+
+/// Check the TLV extension area like `decode_extensions`, without collecting the entries.
+fn validate_extensions(data: &[u8]) -> Result<()> {
+    ensure!(data.len() <= MAX_EXTENSIONS_LEN, MsgCodecError::ExtensionsTooLong);
+    let mut rest = data;
+    while !rest.is_empty() {
+        rest = split_extension(rest)?.2;
+    }
+    Ok(())
+}
+
+/// Split the first TLV entry off `data`, returning its type, its value and the bytes after it.
+fn split_extension(data: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    ensure!(data.len() >= EXTENSION_HEADER_LEN, MsgCodecError::InvalidExtension);
+    let len = u16::from_be_bytes([data[1], data[2]]) as usize;
+    let value = data[EXTENSION_HEADER_LEN..]
+        .get(..len)
+        .ok_or(MsgCodecError::InvalidExtension)?;
+    Ok((data[0], value, &data[EXTENSION_HEADER_LEN + len..]))
+}
+
+/// Read a deadline extension value, which must be an 8-byte big-endian timestamp.
+fn read_deadline(value: Option<&[u8]>) -> Result<Option<u64>> {
+    match value {
+        Some(value) => {
+            let value: [u8; 8] = value.try_into().map_err(|_| MsgCodecError::InvalidExtension)?;
+            Ok(Some(u64::from_be_bytes(value)))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Read a TTL extension value: an 8-byte big-endian origin timestamp followed by an 8-byte
+/// big-endian TTL in seconds.
+fn read_ttl(value: Option<&[u8]>) -> Result<Option<(u64, u64)>> {
+    match value {
+        Some(value) => {
+            let value: [u8; 16] = value.try_into().map_err(|_| MsgCodecError::InvalidExtension)?;
+            let value = u128::from_be_bytes(value);
+            Ok(Some(((value >> 64) as u64, value as u64)))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Read a flag extension, whose presence is the flag and whose value must be empty.
+fn read_flag(value: Option<&[u8]>) -> Result<bool> {
+    match value {
+        Some(value) => {
+            ensure!(value.is_empty(), MsgCodecError::InvalidExtension);
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+/// Size of `data` once encoded as an ABI `bytes` tail.
+fn bytes_tail_len(data: &[u8]) -> usize {
+    UINT256_SIZE + padded_len(data.len())
+}
+
+/// Read the optional `bytes` field whose offset sits in head word `index`.
+/// The tail must start exactly at `*end`, where the previous tail finished, and `*end`
+/// is advanced past it. Fields missing from a shorter head read as empty.
+fn read_next_tail<'a>(
+    message: &'a [u8],
+    head_len: usize,
+    index: usize,
+    end: &mut usize,
+) -> Result<&'a [u8]> {
+    let Some(word) = head_word(message, head_len, index) else {
+        return Ok(&[]);
+    };
+    ensure!(read_u64_word(word) == Some(*end as u64), MsgCodecError::InvalidMessageOffset);
+    let (data, next) = read_bytes_tail(message, *end)?;
+    *end = next;
+    Ok(data)
+}
+
+/// Read the optional `bytes` field whose offset sits in head word `index`, wherever it
+/// points, as long as it is in bounds. Fields missing from a shorter head read as empty.
+fn read_tail_lenient(message: &[u8], head_len: usize, index: usize) -> Result<&[u8]> {
+    match head_word(message, head_len, index) {
+        Some(word) => {
+            let offset = read_size_word(word).ok_or(MsgCodecError::InvalidMessageOffset)?;
+            read_bytes_unpadded(message, offset)
+        },
+        None => Ok(&[]),
+    }
+}
+
+/// Read the canonical ABI `bytes` tail starting at `offset`, requiring zero padding.
+/// Returns the data and the offset right after its padding.
+fn read_bytes_tail(message: &[u8], offset: usize) -> Result<(&[u8], usize)> {
+    let data = read_bytes_unpadded(message, offset)?;
+    let data_start = offset + UINT256_SIZE;
+    let data_end = data_start + data.len();
+    let padded_end = data_start + padded_len(data.len());
+    ensure!(message.len() >= padded_end, MsgCodecError::TooShort);
+    ensure!(
+        message[data_end..padded_end].iter().all(|b| *b == 0),
+        MsgCodecError::TrailingMessageBytes
+    );
+    Ok((data, padded_end))
+}
+
+/// Read the ABI `bytes` data whose length word sits at `offset`, without checking padding.
+fn read_bytes_unpadded(message: &[u8], offset: usize) -> Result<&[u8]> {
+    let data_start = offset.checked_add(UINT256_SIZE).ok_or(MsgCodecError::InvalidMessageOffset)?;
+    ensure!(message.len() >= data_start, MsgCodecError::TooShort);
+    // The length can never exceed the bytes actually present after the length word
+    let len = read_size_word(&message[offset..data_start])
+        .filter(|len| *len <= message.len() - data_start)
+        .ok_or(MsgCodecError::LengthMismatch)?;
+    Ok(&message[data_start..data_start + len])
+}
+
+/// Append a uint256 array tail: a length word followed by the elements.
+fn push_uint256_array(encoded: &mut Vec<u8>, values: &[[u8; 32]]) {
+    push_u64_word(encoded, values.len() as u64);
+    for value in values {
+        encoded.extend_from_slice(value);
+    }
+}
+
+/// Read the uint256 array whose length word sits at `offset`; it must end the message.
+fn read_uint256_array(message: &[u8], offset: usize) -> Result<Vec<[u8; 32]>> {
+    let data_start = offset + UINT256_SIZE;
+    ensure!(message.len() >= data_start, MsgCodecError::TooShort);
+    // Validate the declared count against the actual size before allocating
+    let count = read_size_word(&message[offset..data_start])
+        .filter(|count| *count == (message.len() - data_start) / UINT256_SIZE)
+        .ok_or(MsgCodecError::LengthMismatch)?;
+    ensure!((message.len() - data_start) % UINT256_SIZE == 0, MsgCodecError::LengthMismatch);
+    ensure!(count <= MAX_BATCH_LEN, MsgCodecError::InvalidBatchLength);
+
+    Ok(message[data_start..]
+        .chunks_exact(UINT256_SIZE)
+        .map(|chunk| {
+            let mut value = [0u8; 32];
+            value.copy_from_slice(chunk);
+            value
+        })
+        .collect())
+}
+
+/// Return the tuple body of a struct-wrapped message, or the message itself if it is plain.
+/// A wrapped message starts with a 0x20 tuple offset followed by the ball and type words, so
+/// its third word is a type; in a plain message that word is a head length of at least 96,
+/// which keeps a plain ball of 32 from being mistaken for the tuple offset.
+fn unwrap_tuple(message: &[u8]) -> &[u8] {
+    let wrapped = message.len() >= UINT256_SIZE + ABA_MIN_SIZE
+        && read_size_word(&message[0..32]) == Some(UINT256_SIZE)
+        && read_size_word(&message[64..96]).map_or(true, |word| word < ABA_LEGACY_HEAD_LEN);
+    if wrapped {
+        &message[UINT256_SIZE..]
+    } else {
+        message
+    }
+}
+
+/// Return head word `index` if the head of `head_len` bytes is long enough to contain it.
+fn head_word(message: &[u8], head_len: usize, index: usize) -> Option<&[u8]> {
+    let start = index * UINT256_SIZE;
+    let end = start + UINT256_SIZE;
+    (end <= head_len).then(|| &message[start..end])
+}
+
+/// Read an offset or length word. Values that do not fit in a u64, or that exceed
+/// `MAX_MESSAGE_LEN`, can never describe a valid message and are rejected before they are
+/// used for indexing.
+fn read_size_word(word: &[u8]) -> Option<usize> {
+    read_u64_word(word)
+        .and_then(|value| usize::try_from(value).ok())
+        .filter(|value| *value <= MAX_MESSAGE_LEN)
+}
+
+/// Read the `bytes32` head word at `index`, or all zeros when the head is too short for it.
+fn read_bytes32_word(message: &[u8], head_len: usize, index: usize) -> [u8; 32] {
+    let mut value = [0u8; 32];
+    if let Some(word) = head_word(message, head_len, index) {
+        value.copy_from_slice(word);
+    }
+    value
+}
+
+/// Read a 32-byte ABI word as a u16, returning `None` if it does not fit.
+fn read_u16_word(word: &[u8]) -> Option<u16> {
+    read_u64_word(word).and_then(|value| u16::try_from(value).ok())
+}
+
+/// Read a 32-byte ABI word as a u64, returning `None` if it does not fit.
+fn read_u64_word(word: &[u8]) -> Option<u64> {
+    if word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&word[24..32]);
+    Some(u64::from_be_bytes(value))
+}
@@ -0,0 +1,34 @@
+use super::MsgCodecError;
+use crate::errors::MyOAppError;
+
+impl From<MsgCodecError> for MyOAppError {
+    fn from(error: MsgCodecError) -> Self {
+        match error {
+            MsgCodecError::InvalidMessageLength => MyOAppError::InvalidMessageLength,
+            MsgCodecError::InvalidMessageType => MyOAppError::InvalidMessageType,
+            MsgCodecError::InvalidMessageOffset => MyOAppError::InvalidMessageOffset,
+            MsgCodecError::TrailingMessageBytes => MyOAppError::TrailingMessageBytes,
+            MsgCodecError::ReturnOptionsTooLong => MyOAppError::ReturnOptionsTooLong,
+            MsgCodecError::UnsupportedCodecVersion => MyOAppError::UnsupportedCodecVersion,
+            MsgCodecError::ComposeMsgTooLong => MyOAppError::ComposeMsgTooLong,
+            MsgCodecError::InvalidBatchLength => MyOAppError::InvalidBatchLength,
+            MsgCodecError::MemoTooLong => MyOAppError::MemoTooLong,
+            MsgCodecError::ExtensionsTooLong => MyOAppError::ExtensionsTooLong,
+            MsgCodecError::InvalidExtension => MyOAppError::InvalidExtension,
+            MsgCodecError::ForeignMessage => MyOAppError::ForeignMessage,
+            MsgCodecError::TooShort => MyOAppError::MessageTooShort,
+            MsgCodecError::LengthMismatch => MyOAppError::MessageLengthMismatch,
+            MsgCodecError::UnknownMsgType => MyOAppError::UnknownMessageType,
+            MsgCodecError::MessageTooLarge => MyOAppError::MessageTooLarge,
+            MsgCodecError::ValueTooLarge => MyOAppError::ValueTooLarge,
+            MsgCodecError::ZeroStep => MyOAppError::ZeroStep,
+        }
+    }
+}
+
+// Lets instructions keep using `?` on codec results
+impl From<MsgCodecError> for anchor_lang::error::Error {
+    fn from(error: MsgCodecError) -> Self {
+        MyOAppError::from(error).into()
+    }
+}
@@ -0,0 +1,663 @@
+// Host-side tests of the message codec, which builds and runs without the Solana toolchain:
+// cargo test -p my_oapp --no-default-features --test codec
+
+use ethnum::U256;
+use my_oapp::consts::{MAX_MESSAGE_LEN, MAX_RETURN_OPTIONS_LEN};
+use my_oapp::uint256_msg_codec::{
+    self, decode_aba, decode_aba_lenient, decode_any, encode_aba, AbaMessage, DecodedMessage,
+    MsgCodecError, ABA_TYPE, GAME_OVER_TYPE, PONG_TYPE, VANILLA_TYPE,
+};
+
+/// A 32-byte big-endian ABI word holding `value`.
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+#[test]
+fn vanilla_round_trip() {
+    let ball = U256::from(100_000_000_000_000_000_000u128);
+    // Vanilla `abi.encode(uint256)`
+    let vanilla = uint256_msg_codec::encode_u256(ball);
+    assert_eq!(vanilla.len(), 32);
+    assert_eq!(uint256_msg_codec::decode_u256(&vanilla), Ok(ball));
+}
+
+#[test]
+fn ping_and_pong_round_trip() {
+    let ball = U256::from(100_000_000_000_000_000_000u128);
+
+    // ABA ping carrying return options, as `Send::apply` builds it
+    let ping = AbaMessage {
+        ball: ball.to_be_bytes(),
+        msg_type: ABA_TYPE,
+        return_options: vec![0, 3],
+        seq: 1,
+        ..Default::default()
+    }
+    .encode()
+    .unwrap();
+    let decoded = uint256_msg_codec::decode_aba_u256(&ping).unwrap();
+    assert_eq!(decoded.ball, ball);
+    assert_eq!(decoded.message.return_options, vec![0, 3]);
+    assert_eq!(decoded.message.seq, 1);
+    assert_eq!(uint256_msg_codec::peek_msg_type(&ping), Ok(ABA_TYPE));
+
+    // Return message echoing the ping's guid
+    let pong = uint256_msg_codec::encode_return(&ball.to_be_bytes(), &[1; 32]).unwrap();
+    let decoded = uint256_msg_codec::decode_aba(&pong).unwrap();
+    assert_eq!(decoded.msg_type, PONG_TYPE);
+    assert_eq!(decoded.origin_guid, [1; 32]);
+}
+
+#[test]
+fn malformed_input_is_an_error() {
+    let ping = uint256_msg_codec::encode_aba(&[7; 32], ABA_TYPE, &[0, 3]).unwrap();
+    // Malformed input surfaces a codec error rather than panicking
+    assert_eq!(
+        uint256_msg_codec::decode_aba(&ping[..ping.len() - 1]).err(),
+        Some(MsgCodecError::TooShort)
+    );
+    assert_eq!(uint256_msg_codec::decode(&[]).err(), Some(MsgCodecError::TooShort));
+}
+
+#[test]
+fn strict_decode_accepts_canonical_layouts() {
+    for len in [0u8, 1, 31, 32, 33, 100] {
+        let options: Vec<u8> = (0..len).collect();
+        let message = encode_aba(&[7; 32], ABA_TYPE, &options).unwrap();
+        assert_eq!(message.len() % 32, 0);
+        let decoded = decode_aba(&message).unwrap();
+        assert_eq!(decoded.msg_type, ABA_TYPE);
+        assert_eq!(decoded.return_options, options);
+    }
+}
+
+#[test]
+fn strict_decode_rejects_non_canonical_offsets() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2]).unwrap();
+    // Unaligned, below the legacy head, and with bits set outside the low bytes
+    for offset in [word(97), word(100), word(64), word(0), word(u64::MAX)] {
+        let mut bad = message.clone();
+        bad[64..96].copy_from_slice(&offset);
+        assert_eq!(decode_aba(&bad).err(), Some(MsgCodecError::InvalidMessageOffset));
+    }
+    let mut bad = message.clone();
+    bad[64] = 1;
+    assert_eq!(decode_aba(&bad).err(), Some(MsgCodecError::InvalidMessageOffset));
+}
+
+#[test]
+fn lenient_decode_accepts_a_gap_before_the_options() {
+    // `abi.encode(uint256, uint16, bytes)` with the options 32 bytes further than the ABI
+    // puts them; the gap word isn't a valid hop count either
+    let mut message = Vec::new();
+    message.extend_from_slice(&[7; 32]);
+    message.extend_from_slice(&word(ABA_TYPE as u64));
+    message.extend_from_slice(&word(128));
+    message.extend_from_slice(&[0xab; 32]);
+    message.extend_from_slice(&word(2));
+    message.extend_from_slice(&[1, 2]);
+    message.resize(message.len() + 30, 0);
+
+    assert_eq!(decode_aba(&message).err(), Some(MsgCodecError::InvalidMessageLength));
+    assert_eq!(decode_aba_lenient(&message).unwrap().return_options, vec![1, 2]);
+}
+
+#[test]
+fn strict_decode_requires_zero_padded_tails() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2, 3]).unwrap();
+
+    // The options tail without its padding
+    let unpadded = &message[..96 + 32 + 3];
+    assert_eq!(decode_aba(unpadded).err(), Some(MsgCodecError::TooShort));
+    assert_eq!(decode_aba_lenient(unpadded).unwrap().return_options, vec![1, 2, 3]);
+
+    // Dirty padding
+    let mut dirty = message.clone();
+    *dirty.last_mut().unwrap() = 1;
+    assert_eq!(decode_aba(&dirty).err(), Some(MsgCodecError::TrailingMessageBytes));
+    assert_eq!(decode_aba_lenient(&dirty).unwrap().return_options, vec![1, 2, 3]);
+}
+
+#[test]
+fn strict_decode_rejects_trailing_bytes() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2, 3]).unwrap();
+    for extra in [1, 31, 32] {
+        let mut trailing = message.clone();
+        trailing.resize(message.len() + extra, 0);
+        assert_eq!(decode_aba(&trailing).err(), Some(MsgCodecError::TrailingMessageBytes));
+        assert_eq!(decode_aba_lenient(&trailing).unwrap().return_options, vec![1, 2, 3]);
+    }
+}
+
+#[test]
+fn strict_decode_rejects_truncated_length_words() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[]).unwrap();
+    assert_eq!(message.len(), 128);
+    // Cut inside the options length word
+    for len in [96, 100, 127] {
+        assert_eq!(decode_aba(&message[..len]).err(), Some(MsgCodecError::TooShort));
+        assert_eq!(decode_aba_lenient(&message[..len]).err(), Some(MsgCodecError::TooShort));
+    }
+    // A length word promising more than is there
+    let mut long = message.clone();
+    long[96..128].copy_from_slice(&word(64));
+    long.resize(160, 0);
+    assert_eq!(decode_aba(&long).err(), Some(MsgCodecError::LengthMismatch));
+    assert_eq!(decode_aba_lenient(&long).err(), Some(MsgCodecError::LengthMismatch));
+}
+
+#[test]
+fn encode_aba_enforces_max_return_options_len() {
+    let ball = [1; 32];
+    let empty = encode_aba(&ball, ABA_TYPE, &[]).unwrap();
+    assert!(decode_aba(&empty).unwrap().return_options.is_empty());
+
+    let max = encode_aba(&ball, ABA_TYPE, &vec![3; MAX_RETURN_OPTIONS_LEN]).unwrap();
+    assert_eq!(decode_aba(&max).unwrap().return_options.len(), MAX_RETURN_OPTIONS_LEN);
+
+    assert_eq!(
+        encode_aba(&ball, ABA_TYPE, &vec![3; MAX_RETURN_OPTIONS_LEN + 1]).err(),
+        Some(MsgCodecError::ReturnOptionsTooLong)
+    );
+}
+
+#[test]
+fn decode_aba_enforces_max_return_options_len() {
+    // What an EVM sender without the limit would produce for MAX + 1 bytes of options
+    let len = MAX_RETURN_OPTIONS_LEN + 1;
+    let mut message = Vec::new();
+    message.extend_from_slice(&[1; 32]);
+    message.extend_from_slice(&word(ABA_TYPE as u64));
+    message.extend_from_slice(&word(96));
+    message.extend_from_slice(&word(len as u64));
+    message.extend_from_slice(&vec![3; len]);
+    message.resize(message.len() + len.next_multiple_of(32) - len, 0);
+
+    assert_eq!(decode_aba(&message).err(), Some(MsgCodecError::ReturnOptionsTooLong));
+    assert_eq!(decode_aba_lenient(&message).err(), Some(MsgCodecError::ReturnOptionsTooLong));
+}
+
+#[test]
+fn msg_types_round_trip() {
+    for msg_type in [ABA_TYPE, PONG_TYPE, GAME_OVER_TYPE] {
+        let message = encode_aba(&[3; 32], msg_type, &[9, 9]).unwrap();
+        let decoded = decode_aba(&message).unwrap();
+        assert_eq!(decoded.msg_type, msg_type);
+        assert_eq!(decoded.ball, [3; 32]);
+        assert_eq!(decoded.return_options, vec![9, 9]);
+        assert_eq!(uint256_msg_codec::peek_msg_type(&message), Ok(msg_type));
+    }
+}
+
+#[test]
+fn lenient_decode_surfaces_any_msg_type() {
+    for msg_type in [0, 1, 7, u16::MAX] {
+        let message = encode_aba(&[3; 32], msg_type, &[9, 9]).unwrap();
+        assert_eq!(decode_aba_lenient(&message).unwrap().msg_type, msg_type);
+        assert_eq!(uint256_msg_codec::peek_msg_type(&message), Ok(msg_type));
+    }
+}
+
+#[test]
+fn malformed_fixtures_report_specific_errors() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2, 3]).unwrap();
+    let with = |index: usize, bytes: &[u8]| {
+        let mut fixture = message.clone();
+        fixture[index..index + bytes.len()].copy_from_slice(bytes);
+        fixture
+    };
+    let mut trailing = message.clone();
+    trailing.push(0);
+
+    let fixtures = [
+        ("short head", message[..100].to_vec(), MsgCodecError::TooShort),
+        ("truncated tail", message[..message.len() - 1].to_vec(), MsgCodecError::TooShort),
+        ("unaligned offset", with(64, &word(97)), MsgCodecError::InvalidMessageOffset),
+        ("length past the end", with(96, &word(64)), MsgCodecError::LengthMismatch),
+        ("trailing byte", trailing, MsgCodecError::TrailingMessageBytes),
+        ("dirty padding", with(message.len() - 1, &[1]), MsgCodecError::TrailingMessageBytes),
+        ("unknown type", with(32, &word(9)), MsgCodecError::UnknownMsgType),
+        ("type wider than uint16", with(32, &word(0x1_0002)), MsgCodecError::InvalidMessageType),
+    ];
+    for (name, fixture, error) in fixtures {
+        assert_eq!(decode_aba(&fixture).err(), Some(error), "{name}");
+    }
+}
+
+#[test]
+fn vanilla_decode_reports_specific_errors() {
+    assert_eq!(uint256_msg_codec::decode(&[0; 31]).err(), Some(MsgCodecError::TooShort));
+    assert_eq!(
+        uint256_msg_codec::decode(&[0; 33]).err(),
+        Some(MsgCodecError::TrailingMessageBytes)
+    );
+    assert_eq!(uint256_msg_codec::decode(&[5; 32]), Ok([5; 32]));
+}
+
+#[test]
+fn decoders_enforce_max_message_len() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2, 3]).unwrap();
+    let mut at_limit = message.clone();
+    at_limit.resize(MAX_MESSAGE_LEN, 0);
+    let mut over_limit = message.clone();
+    over_limit.resize(MAX_MESSAGE_LEN + 1, 0);
+
+    // At the limit the message is decoded; the strict decoder then objects to the tail
+    assert_eq!(decode_aba(&at_limit).err(), Some(MsgCodecError::TrailingMessageBytes));
+    assert_eq!(decode_aba_lenient(&at_limit).unwrap().return_options, vec![1, 2, 3]);
+
+    assert_eq!(decode_aba(&over_limit).err(), Some(MsgCodecError::MessageTooLarge));
+    assert_eq!(decode_aba_lenient(&over_limit).err(), Some(MsgCodecError::MessageTooLarge));
+    assert_eq!(
+        uint256_msg_codec::decode_aba_wrapped(&over_limit).err(),
+        Some(MsgCodecError::MessageTooLarge)
+    );
+}
+
+#[test]
+fn absurd_offset_and_length_words_are_rejected() {
+    let message = encode_aba(&[7; 32], ABA_TYPE, &[1, 2, 3]).unwrap();
+
+    // offset = u64::MAX, in the low 8 bytes of the offset word and across the whole word
+    let mut offset = message.clone();
+    offset[88..96].copy_from_slice(&u64::MAX.to_be_bytes());
+    assert_eq!(decode_aba(&offset).err(), Some(MsgCodecError::InvalidMessageOffset));
+    assert_eq!(decode_aba_lenient(&offset).err(), Some(MsgCodecError::InvalidMessageOffset));
+    offset[64..96].copy_from_slice(&[0xff; 32]);
+    assert_eq!(decode_aba(&offset).err(), Some(MsgCodecError::InvalidMessageOffset));
+    assert_eq!(decode_aba_lenient(&offset).err(), Some(MsgCodecError::InvalidMessageOffset));
+
+    // len = u64::MAX - 10 in the options length word
+    let mut len = message.clone();
+    len[120..128].copy_from_slice(&(u64::MAX - 10).to_be_bytes());
+    assert_eq!(decode_aba(&len).err(), Some(MsgCodecError::LengthMismatch));
+    assert_eq!(decode_aba_lenient(&len).err(), Some(MsgCodecError::LengthMismatch));
+}
+
+#[test]
+fn decode_any_reports_vanilla_payloads_as_their_own_variant() {
+    let vanilla = uint256_msg_codec::encode(&[5; 32]);
+    assert!(matches!(decode_any(&vanilla), Ok(DecodedMessage::Vanilla(ball)) if ball == [5; 32]));
+    // decode_aba still answers with the VANILLA_TYPE sentinel
+    assert_eq!(decode_aba(&vanilla).unwrap().msg_type, VANILLA_TYPE);
+
+    let aba = encode_aba(&[5; 32], ABA_TYPE, &[1]).unwrap();
+    assert!(
+        matches!(decode_any(&aba), Ok(DecodedMessage::Aba(message)) if message.ball == [5; 32])
+    );
+}
+
+#[test]
+fn decode_any_rejects_an_aba_layout_with_type_zero() {
+    let message = encode_aba(&[5; 32], VANILLA_TYPE, &[]).unwrap();
+    assert_eq!(message.len(), 128);
+    assert_eq!(decode_any(&message).err(), Some(MsgCodecError::UnknownMsgType));
+}
+
+#[test]
+fn decode_any_rejects_lengths_between_vanilla_and_aba() {
+    // 64 bytes is the typed vanilla layout, `abi.encode(uint16, uint256)`
+    for len in (33..128).filter(|len| *len != 64) {
+        assert!(decode_any(&vec![0; len]).is_err(), "{len} bytes");
+    }
+}
+
+/// The answer the ABA handler sends for an inbound message: the ball decremented by the
+/// step, typed GAME_OVER once it reaches zero. Only pings are answered.
+fn answer(inbound: &[u8]) -> Option<Vec<u8>> {
+    let inbound = decode_aba(inbound).unwrap();
+    if inbound.msg_type != ABA_TYPE {
+        return None;
+    }
+    let ball = U256::from_be_bytes(inbound.ball);
+    let ball = uint256_msg_codec::decrement_ball_by(ball, inbound.step_u256(), false);
+    let msg_type = if ball == U256::ZERO { GAME_OVER_TYPE } else { PONG_TYPE };
+    Some(encode_aba(&ball.to_be_bytes(), msg_type, &[]).unwrap())
+}
+
+#[test]
+fn simulated_exchange_ends_with_game_over() {
+    // ball = 2 inbound, 1 returned
+    let ping = encode_aba(&U256::from(2u128).to_be_bytes(), ABA_TYPE, &[]).unwrap();
+    let pong = answer(&ping).unwrap();
+    let returned = decode_aba(&pong).unwrap();
+    assert_eq!(returned.msg_type, PONG_TYPE);
+    assert_eq!(U256::from_be_bytes(returned.ball), U256::ONE);
+    assert_eq!(answer(&pong), None);
+
+    // The peer plays the 1 back and the ball reaches zero
+    let ping = encode_aba(&returned.ball, ABA_TYPE, &[]).unwrap();
+    let game_over = answer(&ping).unwrap();
+    assert_eq!(game_over, uint256_msg_codec::encode_game_over(&[0; 32]).unwrap());
+    assert_eq!(uint256_msg_codec::decode_game_over(&game_over), Ok([0; 32]));
+
+    // A GAME_OVER is never answered
+    assert_eq!(answer(&game_over), None);
+    assert_eq!(uint256_msg_codec::decode_game_over(&pong), Err(MsgCodecError::InvalidMessageType));
+}
+
+#[test]
+fn packed_and_abi_encodings_do_not_cross_decode() {
+    let ball = [7; 32];
+    for return_options in [vec![], vec![0, 3], vec![0xaa; 30], vec![0xaa; 94]] {
+        let abi = encode_aba(&ball, ABA_TYPE, &return_options).unwrap();
+        let packed = uint256_msg_codec::encode_packed(&ball, ABA_TYPE, &return_options).unwrap();
+        assert_eq!(packed.len(), 32 + 2 + return_options.len());
+
+        // Each decoder rejects the other layout
+        assert!(decode_aba(&packed).is_err(), "{} option bytes", return_options.len());
+        assert_eq!(
+            uint256_msg_codec::decode_packed(&abi).err(),
+            Some(MsgCodecError::InvalidMessageType)
+        );
+
+        // decode_auto tells them apart and finds the same message in both
+        for message in [&abi, &packed] {
+            let decoded = uint256_msg_codec::decode_auto(message).unwrap();
+            assert_eq!(decoded.ball, ball);
+            assert_eq!(decoded.msg_type, ABA_TYPE);
+            assert_eq!(decoded.return_options, return_options);
+        }
+    }
+    // A packed message can't carry the vanilla type, which is what keeps the layouts apart
+    assert_eq!(
+        uint256_msg_codec::encode_packed(&ball, VANILLA_TYPE, &[]).err(),
+        Some(MsgCodecError::InvalidMessageType)
+    );
+}
+
+#[test]
+fn legacy_and_versioned_messages_decode_side_by_side() {
+    let ball = [9; 32];
+    let legacy_vanilla = uint256_msg_codec::encode(&ball);
+    let legacy_aba = encode_aba(&ball, ABA_TYPE, &[0, 3]).unwrap();
+    let v2_vanilla = uint256_msg_codec::encode_v2(&ball, VANILLA_TYPE, &[]).unwrap();
+    let v2_aba = uint256_msg_codec::encode_v2(&ball, ABA_TYPE, &[0, 3]).unwrap();
+    assert_eq!(v2_vanilla, [&[uint256_msg_codec::CODEC_VERSION][..], &legacy_vanilla].concat());
+    assert_eq!(v2_aba.len(), legacy_aba.len() + 1);
+
+    // An upgraded receiver takes a mixed stream of old and new senders' messages
+    for (vanilla, aba) in [(&legacy_vanilla, &legacy_aba), (&v2_vanilla, &v2_aba)] {
+        let decoded = uint256_msg_codec::decode_v2(vanilla).unwrap();
+        assert_eq!((decoded.ball, decoded.msg_type), (ball, VANILLA_TYPE));
+        let decoded = uint256_msg_codec::decode_v2(aba).unwrap();
+        assert_eq!((decoded.ball, decoded.msg_type), (ball, ABA_TYPE));
+        assert_eq!(decoded.return_options, vec![0, 3]);
+
+        assert!(matches!(decode_any(vanilla), Ok(DecodedMessage::Vanilla(b)) if b == ball));
+        assert!(matches!(decode_any(aba), Ok(DecodedMessage::Aba(m)) if m.ball == ball));
+    }
+    // A receiver that hasn't been upgraded rejects versioned messages instead of misreading them
+    assert!(decode_aba(&v2_vanilla).is_err());
+    assert!(decode_aba(&v2_aba).is_err());
+}
+
+#[test]
+fn newer_codec_versions_are_rejected() {
+    let mut message = uint256_msg_codec::encode_v2(&[9; 32], ABA_TYPE, &[]).unwrap();
+    message[0] = uint256_msg_codec::CODEC_VERSION + 1;
+    assert_eq!(
+        uint256_msg_codec::decode_v2(&message).err(),
+        Some(MsgCodecError::UnsupportedCodecVersion)
+    );
+    assert_eq!(decode_any(&message).err(), Some(MsgCodecError::UnsupportedCodecVersion));
+}
+
+/// A ping carrying `return_options` and `compose_msg`.
+fn compose_ping(return_options: &[u8], compose_msg: &[u8]) -> Vec<u8> {
+    AbaMessage {
+        ball: [3; 32],
+        msg_type: ABA_TYPE,
+        return_options: return_options.to_vec(),
+        compose_msg: compose_msg.to_vec(),
+        ..Default::default()
+    }
+    .encode()
+    .unwrap()
+}
+
+#[test]
+fn compose_msg_round_trips_next_to_return_options() {
+    let (options, compose) = (vec![0, 3, 1], vec![5; 40]);
+    // Expected lengths: the head (192 bytes once the compose word is in use), then each
+    // tail's length word and padded data
+    let cases = [
+        (vec![], vec![], 96 + 32),
+        (vec![], compose.clone(), 192 + 32 + 32 + 64),
+        (options.clone(), vec![], 96 + 64),
+        (options.clone(), compose.clone(), 192 + 64 + 32 + 64),
+    ];
+    for (return_options, compose_msg, len) in cases {
+        let message = compose_ping(&return_options, &compose_msg);
+        assert_eq!(message.len(), len);
+        let decoded = decode_aba(&message).unwrap();
+        assert_eq!(decoded.return_options, return_options);
+        assert_eq!(decoded.compose_msg, compose_msg);
+    }
+}
+
+#[test]
+fn overlapping_compose_offsets_are_rejected() {
+    // Both tails empty but the compose word present: each needs its own length word
+    let mut aliased = compose_ping(&[], &[5]);
+    aliased.truncate(192);
+    aliased.extend_from_slice(&word(0));
+    aliased[160..192].copy_from_slice(&word(192));
+    assert_eq!(decode_aba(&aliased).err(), Some(MsgCodecError::InvalidMessageOffset));
+
+    let message = compose_ping(&[0, 3, 1], &[5; 40]);
+    assert_eq!(&message[160..192], &word(256));
+    // The compose offset pointing back at the return options, into their data, or past
+    // where they end
+    for offset in [192, 224, 288] {
+        let mut attack = message.clone();
+        attack[160..192].copy_from_slice(&word(offset));
+        assert_eq!(
+            decode_aba(&attack).err(),
+            Some(MsgCodecError::InvalidMessageOffset),
+            "offset {offset}"
+        );
+    }
+}
+
+#[test]
+fn seq_round_trips_up_to_u64_max() {
+    for seq in [0, 1, u64::MAX] {
+        let ping = AbaMessage { msg_type: ABA_TYPE, seq, ..Default::default() }.encode().unwrap();
+        assert_eq!(decode_aba(&ping).unwrap().seq, seq);
+        // Unsequenced messages keep the legacy layout
+        assert_eq!(ping.len() == 128, seq == 0, "seq {seq}");
+    }
+}
+
+#[test]
+fn tagged_messages_round_trip() {
+    let ball = [7u8; 32];
+    // A tagged vanilla ball is 36 bytes
+    let vanilla = uint256_msg_codec::encode_tagged(&uint256_msg_codec::encode(&ball));
+    assert_eq!(vanilla.len(), 36);
+    assert!(uint256_msg_codec::split_tag(&vanilla).0);
+    let body = uint256_msg_codec::decode_tagged(&vanilla).unwrap();
+    assert_eq!(uint256_msg_codec::decode(body).unwrap(), ball);
+
+    let ping = encode_aba(&ball, ABA_TYPE, &[0, 3]).unwrap();
+    let tagged = uint256_msg_codec::tag_if(ping.clone(), true);
+    assert_eq!(tagged.len(), ping.len() + 4);
+    let decoded = decode_aba(uint256_msg_codec::decode_tagged(&tagged).unwrap()).unwrap();
+    assert_eq!(decoded.ball, ball);
+    assert_eq!(decoded.return_options, vec![0, 3]);
+
+    // A tagged versioned message keeps its version header after the prefix
+    let v2 = uint256_msg_codec::encode_tagged(
+        &uint256_msg_codec::encode_v2(&ball, ABA_TYPE, &[]).unwrap(),
+    );
+    assert_eq!(v2.len() % 32, 5);
+    let body = uint256_msg_codec::decode_tagged(&v2).unwrap();
+    assert_eq!(uint256_msg_codec::decode_v2(body).unwrap().ball, ball);
+}
+
+#[test]
+fn untagged_messages_pass_through() {
+    let ball = [7u8; 32];
+    let legacy = [uint256_msg_codec::encode(&ball), encode_aba(&ball, ABA_TYPE, &[]).unwrap()];
+    for message in &legacy {
+        assert_eq!(uint256_msg_codec::split_tag(message), (false, &message[..]));
+        assert_eq!(uint256_msg_codec::tag_if(message.clone(), false), *message);
+        assert_eq!(
+            uint256_msg_codec::decode_tagged(message).err(),
+            Some(MsgCodecError::ForeignMessage)
+        );
+    }
+    assert_eq!(uint256_msg_codec::decode(&legacy[0]).unwrap(), ball);
+    assert_eq!(decode_aba(&legacy[1]).unwrap().ball, ball);
+
+    // A legacy ball that happens to start with MAGIC is still a plain ball
+    let mut lookalike = [0u8; 32];
+    lookalike[..4].copy_from_slice(&uint256_msg_codec::MAGIC);
+    let message = uint256_msg_codec::encode(&lookalike);
+    assert_eq!(uint256_msg_codec::split_tag(&message), (false, &message[..]));
+    assert_eq!(uint256_msg_codec::decode(&message).unwrap(), lookalike);
+}
+
+// `abi.encode(Ping({ball: 100e18, msgType: 2, options: <lzReceive 200k>}))` from the upgraded
+// EVM contract: a 0x20 tuple offset, then the plain ABA layout
+const WRAPPED_PING: &str = concat!(
+    "0000000000000000000000000000000000000000000000000000000000000020",
+    "0000000000000000000000000000000000000000000000056bc75e2d63100000",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "0000000000000000000000000000000000000000000000000000000000000060",
+    "0000000000000000000000000000000000000000000000000000000000000016",
+    "00030100110100000000000000000000000000030d4000000000000000000000",
+);
+
+/// Bytes from a hex string, as `cast abi-encode` prints them.
+fn hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn struct_wrapped_messages_decode_like_plain_ones() {
+    let wrapped = hex(WRAPPED_PING);
+    let ball = U256::from(100_000_000_000_000_000_000u128).to_be_bytes();
+    let options = hex("00030100110100000000000000000000000000030d40");
+    let plain = encode_aba(&ball, ABA_TYPE, &options).unwrap();
+    assert_eq!(&wrapped[32..], &plain[..]);
+
+    for decoded in [
+        decode_aba(&plain).unwrap(),
+        decode_aba(&wrapped).unwrap(),
+        uint256_msg_codec::decode_aba_wrapped(&wrapped).unwrap(),
+        decode_aba_lenient(&wrapped).unwrap(),
+    ] {
+        assert_eq!(decoded.ball, ball);
+        assert_eq!(decoded.msg_type, ABA_TYPE);
+        assert_eq!(decoded.return_options, options);
+    }
+
+    // decode_aba_wrapped insists on the tuple offset; a plain ball of 32 isn't one
+    assert_eq!(
+        uint256_msg_codec::decode_aba_wrapped(&plain).err(),
+        Some(MsgCodecError::InvalidMessageOffset)
+    );
+    let plain_32 = encode_aba(&word(32), ABA_TYPE, &options).unwrap();
+    assert_eq!(decode_aba(&plain_32).unwrap().ball, word(32));
+}
+
+#[test]
+fn ball_integer_helpers_reject_truncation() {
+    // Zero and exact fits
+    assert_eq!(uint256_msg_codec::ball_as_u64(&[0; 32]), Ok(0));
+    assert_eq!(uint256_msg_codec::ball_as_u128(&[0; 32]), Ok(0));
+    let max_u64 = uint256_msg_codec::ball_from_u64(u64::MAX);
+    assert_eq!(uint256_msg_codec::ball_as_u64(&max_u64), Ok(u64::MAX));
+    assert_eq!(uint256_msg_codec::ball_as_u128(&max_u64), Ok(u64::MAX as u128));
+    let max_u128 = uint256_msg_codec::ball_from_u128(u128::MAX);
+    assert_eq!(uint256_msg_codec::ball_as_u128(&max_u128), Ok(u128::MAX));
+    assert_eq!(max_u128, U256::from(u128::MAX).to_be_bytes());
+
+    // One past the width is an error rather than the low bytes
+    let over_u64 = uint256_msg_codec::ball_from_u128(u64::MAX as u128 + 1);
+    assert_eq!(uint256_msg_codec::ball_as_u64(&over_u64), Err(MsgCodecError::ValueTooLarge));
+    let mut over_u128 = [0u8; 32];
+    over_u128[15] = 1;
+    assert_eq!(uint256_msg_codec::ball_as_u128(&over_u128), Err(MsgCodecError::ValueTooLarge));
+    let mut high_bit = [0u8; 32];
+    high_bit[0] = 0x80;
+    assert_eq!(uint256_msg_codec::ball_as_u64(&high_bit), Err(MsgCodecError::ValueTooLarge));
+    assert_eq!(uint256_msg_codec::ball_as_u128(&high_bit), Err(MsgCodecError::ValueTooLarge));
+}
+
+#[test]
+fn compact_messages_are_not_full_width_ones() {
+    let value = 100_000_000_000_000_000_000u128;
+    let compact = uint256_msg_codec::encode_compact(value);
+    assert_eq!(compact.len(), 16);
+    assert_eq!(uint256_msg_codec::decode_compact(&compact), Ok(value));
+    // A compact ball is too short to be a full-width one, and a full-width ball (or a
+    // truncated one) is not a compact one
+    let full = uint256_msg_codec::encode(&uint256_msg_codec::ball_from_u128(value));
+    assert_eq!(uint256_msg_codec::decode(&compact).err(), Some(MsgCodecError::TooShort));
+    assert_eq!(
+        uint256_msg_codec::decode_compact(&full).err(),
+        Some(MsgCodecError::TrailingMessageBytes)
+    );
+    assert_eq!(uint256_msg_codec::decode_compact(&full[..15]).err(), Some(MsgCodecError::TooShort));
+
+    let ping = uint256_msg_codec::encode_aba_compact(value, ABA_TYPE, &[0, 3]).unwrap();
+    assert_eq!(ping.len(), 20);
+    let decoded = uint256_msg_codec::decode_aba_compact(&ping).unwrap();
+    assert_eq!(decoded.ball, uint256_msg_codec::ball_from_u128(value));
+    assert_eq!(decoded.msg_type, ABA_TYPE);
+    assert_eq!(decoded.return_options, vec![0, 3]);
+    // Neither layout decodes as the other
+    assert!(decode_aba(&ping).is_err());
+    assert!(decode_any(&ping).is_err());
+    let full_ping = encode_aba(&decoded.ball, ABA_TYPE, &[0, 3]).unwrap();
+    assert_eq!(
+        uint256_msg_codec::decode_aba_compact(&full_ping).err(),
+        Some(MsgCodecError::UnknownMsgType)
+    );
+    assert_eq!(
+        uint256_msg_codec::encode_aba_compact(value, VANILLA_TYPE, &[]).err(),
+        Some(MsgCodecError::InvalidMessageType)
+    );
+
+    // A stored ball wider than u128 can't be sent compact
+    let mut wide = uint256_msg_codec::ball_from_u128(u128::MAX);
+    wide[15] = 1;
+    assert_eq!(uint256_msg_codec::ball_as_u128(&wide), Err(MsgCodecError::ValueTooLarge));
+}
+
+#[test]
+fn no_return_flag_round_trips() {
+    let ping = |extensions| AbaMessage {
+        ball: word(5),
+        msg_type: ABA_TYPE,
+        extensions,
+        ..Default::default()
+    };
+    let one_way = ping(vec![uint256_msg_codec::Extension::no_return()]).encode().unwrap();
+    assert_eq!(decode_aba(&one_way).unwrap().no_return(), Ok(true));
+    assert_eq!(uint256_msg_codec::decode_aba_ref(&one_way).unwrap().no_return(), Ok(true));
+
+    let ttl_only = ping(vec![uint256_msg_codec::Extension::ttl(1, 60)]).encode().unwrap();
+    assert_eq!(decode_aba(&ttl_only).unwrap().no_return(), Ok(false));
+
+    // Legacy messages have no extension area and always expect a return
+    let legacy = encode_aba(&word(5), ABA_TYPE, &[]).unwrap();
+    assert_eq!(decode_aba(&legacy).unwrap().no_return(), Ok(false));
+    assert_eq!(uint256_msg_codec::decode_aba_ref(&legacy).unwrap().no_return(), Ok(false));
+
+    // The flag carries no value
+    let with_value = ping(vec![uint256_msg_codec::Extension {
+        ext_type: uint256_msg_codec::NO_RETURN_EXT_TYPE,
+        value: vec![1],
+    }]);
+    assert_eq!(with_value.no_return(), Err(MsgCodecError::InvalidExtension));
+}
@@ -0,0 +1,213 @@
+// Host-side tests of the type-3 options helpers, which build without the Solana toolchain:
+// cargo test -p my_oapp --no-default-features --test options
+
+use my_oapp::consts::MAX_RETURN_OPTIONS_LEN;
+use my_oapp::options_builder::{combine, OptionItem};
+use my_oapp::options_codec::{
+    append_worker_option, executor_native_drop_option, merge_type3, validate_type3, worker_options,
+    OptionsError,
+};
+
+/// Bytes from a hex string, as the TypeScript SDK's `Options.toHex()` prints them.
+fn hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// `Options.newOptions().addExecutorLzReceiveOption(200000, 0)`
+const LZ_RECEIVE_200K: &str = "00030100110100000000000000000000000000030d40";
+
+#[test]
+fn valid_executor_options() {
+    let options = hex(LZ_RECEIVE_200K);
+    assert_eq!(validate_type3(&options, MAX_RETURN_OPTIONS_LEN), Ok(()));
+    assert_eq!(worker_options(&options), Ok(vec![&options[2..]]));
+    // No options, or only the header, request nothing beyond the enforced options
+    assert_eq!(validate_type3(&[], MAX_RETURN_OPTIONS_LEN), Ok(()));
+    assert_eq!(validate_type3(&[0, 3], MAX_RETURN_OPTIONS_LEN), Ok(()));
+    // An executor option followed by a DVN option
+    let mut options = options;
+    options.extend_from_slice(&[2, 0, 2, 0, 5]);
+    assert_eq!(validate_type3(&options, MAX_RETURN_OPTIONS_LEN), Ok(()));
+    assert_eq!(worker_options(&options).unwrap().len(), 2);
+}
+
+#[test]
+fn truncated_options() {
+    let options = hex(LZ_RECEIVE_200K);
+    // Short of the declared size, and cut inside the worker header
+    for len in [options.len() - 1, 5, 4, 3] {
+        assert_eq!(
+            validate_type3(&options[..len], MAX_RETURN_OPTIONS_LEN),
+            Err(OptionsError::Truncated),
+            "length {len}"
+        );
+        assert_eq!(worker_options(&options[..len]), Err(OptionsError::Truncated));
+    }
+    // A worker option without even its option type byte
+    assert_eq!(
+        validate_type3(&[0, 3, 1, 0, 0], MAX_RETURN_OPTIONS_LEN),
+        Err(OptionsError::EmptyOption)
+    );
+}
+
+#[test]
+fn garbage_options() {
+    // Not type 3, or too short for a header
+    assert_eq!(
+        validate_type3(&[0, 1, 0, 0], MAX_RETURN_OPTIONS_LEN),
+        Err(OptionsError::InvalidHeader)
+    );
+    assert_eq!(validate_type3(&[3], MAX_RETURN_OPTIONS_LEN), Err(OptionsError::InvalidHeader));
+    assert_eq!(
+        validate_type3(&[0xff; 40], MAX_RETURN_OPTIONS_LEN),
+        Err(OptionsError::InvalidHeader)
+    );
+    // A worker id that is neither the executor nor a DVN
+    assert_eq!(
+        validate_type3(&[0, 3, 9, 0, 1, 1], MAX_RETURN_OPTIONS_LEN),
+        Err(OptionsError::UnknownWorker)
+    );
+    // Well-formed but over the cap
+    let options = hex(LZ_RECEIVE_200K);
+    assert_eq!(validate_type3(&options, options.len()), Ok(()));
+    assert_eq!(validate_type3(&options, options.len() - 1), Err(OptionsError::TooLong));
+}
+
+#[test]
+fn return_native_drop_round_trips_through_combine() {
+    let receiver = [0xab; 32];
+    let drop = executor_native_drop_option(5, &receiver);
+    // Worker 1, size 49, native drop, a u128 amount and the receiver
+    let expected_drop = hex(&format!("01003102{:032x}{}", 5, "ab".repeat(32)));
+    assert_eq!(drop, expected_drop);
+
+    // Appended after the combined options, as lz_receive builds the return leg's options
+    let combined = merge_type3(&hex(LZ_RECEIVE_200K), &[], false).unwrap();
+    let options = append_worker_option(combined, &drop);
+    assert_eq!(options, [hex(LZ_RECEIVE_200K), expected_drop.clone()].concat());
+    assert_eq!(validate_type3(&options, MAX_RETURN_OPTIONS_LEN), Ok(()));
+    assert_eq!(worker_options(&options).unwrap().last(), Some(&&drop[..]));
+    // Combining again, e.g. when quoting, leaves the drop as it is
+    assert_eq!(merge_type3(&options, &[], false).unwrap(), options);
+    assert_eq!(merge_type3(&[], &options, true).unwrap(), options);
+
+    // Without other options the drop gets the type-3 header
+    let options = append_worker_option(Vec::new(), &drop);
+    assert_eq!(options, [vec![0, 3], expected_drop].concat());
+    assert_eq!(validate_type3(&options, MAX_RETURN_OPTIONS_LEN), Ok(()));
+}
+
+/// An executor lzReceive option with `gas` and, when given, `value`, without the header.
+fn lz_receive(gas: u128, value: Option<u128>) -> Vec<u8> {
+    let mut data = gas.to_be_bytes().to_vec();
+    if let Some(value) = value {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut option = vec![1];
+    option.extend_from_slice(&((1 + data.len()) as u16).to_be_bytes());
+    option.push(1);
+    option.extend(data);
+    option
+}
+
+/// Type-3 options holding `worker_options` in order.
+fn type3(worker_options: &[Vec<u8>]) -> Vec<u8> {
+    [vec![0, 3], worker_options.concat()].concat()
+}
+
+#[test]
+fn duplicate_lz_receive_options() {
+    let enforced = type3(&[lz_receive(100, None)]);
+    let extra = type3(&[lz_receive(50, Some(7))]);
+    // Gas and value add up; a missing value counts as zero
+    assert_eq!(merge_type3(&enforced, &extra, false), Ok(type3(&[lz_receive(150, Some(7))])));
+    assert_eq!(merge_type3(&enforced, &extra, true), Err(OptionsError::Conflicting));
+    // A sum that overflows is a conflict under either policy
+    let max = type3(&[lz_receive(u128::MAX, None)]);
+    assert_eq!(merge_type3(&max, &enforced, false), Err(OptionsError::Conflicting));
+}
+
+#[test]
+fn duplicate_native_drop_options() {
+    let (receiver, other) = ([7; 32], [8; 32]);
+    let enforced = type3(&[lz_receive(100, None), executor_native_drop_option(5, &receiver)]);
+    let extra =
+        type3(&[executor_native_drop_option(6, &receiver), executor_native_drop_option(1, &other)]);
+    // Drops to the same receiver add up; a drop to another receiver is kept apart
+    assert_eq!(
+        merge_type3(&enforced, &extra, false),
+        Ok(type3(&[
+            lz_receive(100, None),
+            executor_native_drop_option(11, &receiver),
+            executor_native_drop_option(1, &other),
+        ]))
+    );
+    assert_eq!(merge_type3(&enforced, &extra, true), Err(OptionsError::Conflicting));
+}
+
+#[test]
+fn disjoint_options() {
+    let enforced = type3(&[lz_receive(100, None)]);
+    let dvn = vec![2, 0, 2, 0, 5];
+    let extra = type3(&[executor_native_drop_option(5, &[7; 32]), dvn]);
+    // Enforced first, then the caller's, whatever the policy
+    let expected = [enforced.clone(), extra[2..].to_vec()].concat();
+    assert_eq!(merge_type3(&enforced, &extra, false), Ok(expected.clone()));
+    assert_eq!(merge_type3(&enforced, &extra, true), Ok(expected));
+    assert_eq!(merge_type3(&[], &extra, true), Ok(extra));
+    assert_eq!(merge_type3(&enforced, &[], true), Ok(enforced.clone()));
+    assert_eq!(merge_type3(&[], &[], true), Ok(Vec::new()));
+    // Malformed caller options are rejected rather than merged
+    assert_eq!(merge_type3(&enforced, &[0, 1], true), Err(OptionsError::InvalidHeader));
+    assert_eq!(
+        merge_type3(&enforced, &type3(&[vec![1, 0, 5, 1]]), true),
+        Err(OptionsError::Truncated)
+    );
+}
+
+#[test]
+fn builder_matches_sdk_blobs() {
+    let lz_receive = |gas, value| OptionItem::LzReceive { gas, value };
+    let mut receiver = [0; 32];
+    receiver[31] = 1;
+    // Each blob is `Options.newOptions().<call>.toHex()` from the TypeScript SDK
+    let cases = [
+        // addExecutorLzReceiveOption(200000, 0)
+        (vec![lz_receive(200_000, 0)], LZ_RECEIVE_200K.to_string()),
+        // addExecutorLzReceiveOption(200000, 1)
+        (vec![lz_receive(200_000, 1)], format!("000301002101{:032x}{:032x}", 200_000, 1)),
+        // addExecutorNativeDropOption(1, "0x00…01")
+        (
+            vec![OptionItem::NativeDrop { amount: 1, receiver }],
+            format!("000301003102{:032x}{:064x}", 1, 1),
+        ),
+        // addExecutorComposeOption(0, 50000, 0)
+        (
+            vec![OptionItem::Compose { index: 0, gas: 50_000, value: 0 }],
+            format!("000301001303{:04x}{:032x}", 0, 50_000),
+        ),
+        // addExecutorComposeOption(1, 50000, 2)
+        (
+            vec![OptionItem::Compose { index: 1, gas: 50_000, value: 2 }],
+            format!("000301002303{:04x}{:032x}{:032x}", 1, 50_000, 2),
+        ),
+        // addExecutorOrderedExecutionOption()
+        (vec![OptionItem::OrderedExecution], "000301000104".to_string()),
+        // addExecutorLzReceiveOption(200000, 0).addExecutorOrderedExecutionOption()
+        (
+            vec![lz_receive(200_000, 0), OptionItem::OrderedExecution],
+            format!("{LZ_RECEIVE_200K}01000104"),
+        ),
+    ];
+    for (items, blob) in cases {
+        let options = combine(&items);
+        assert_eq!(options, hex(&blob), "{blob}");
+        assert_eq!(validate_type3(&options, MAX_RETURN_OPTIONS_LEN), Ok(()));
+        assert_eq!(worker_options(&options).unwrap().len(), items.len());
+    }
+    // No items, no options
+    assert!(combine(&[]).is_empty());
+}
@@ -0,0 +1,199 @@
+// Property tests of the message codec and the options helpers, host-side like `codec.rs`:
+// cargo test -p my_oapp --no-default-features --test proptest_codec
+
+use my_oapp::consts::{MAX_COMPOSE_MSG_LEN, MAX_MEMO_LEN, MAX_RETURN_OPTIONS_LEN};
+use my_oapp::options_builder::{combine, OptionItem};
+use my_oapp::options_codec::{merge_type3, validate_type3, worker_options};
+use my_oapp::uint256_msg_codec::{self as codec, AbaMessage, ABA_TYPE, GAME_OVER_TYPE, PONG_TYPE};
+use proptest::prelude::*;
+
+/// Run every public decoder over `message`; each must return rather than panic.
+fn decode_everything(message: &[u8]) {
+    let _ = codec::decode(message);
+    let _ = codec::decode_u256(message);
+    let _ = codec::decode_typed_vanilla(message);
+    let _ = codec::decode_counter(message);
+    let _ = codec::decode_ball_compose(message);
+    let _ = codec::decode_i256(message);
+    let _ = codec::peek_msg_type(message);
+    let _ = codec::decode_game_over(message);
+    let _ = codec::decode_aba(message);
+    let _ = codec::decode_aba_ref(message);
+    let _ = codec::decode_aba_wrapped(message);
+    let _ = codec::decode_aba_lenient(message);
+    let _ = codec::decode_packed(message);
+    let _ = codec::decode_compact(message);
+    let _ = codec::decode_aba_compact(message);
+    let _ = codec::decode_aba_compact_ref(message);
+    let _ = codec::decode_auto(message);
+    let _ = codec::decode_with_memo(message);
+    let _ = codec::decode_batch(message);
+    let _ = codec::decode_batch_message(message);
+    let _ = codec::decode_v2(message);
+    let _ = codec::decode_any(message);
+    let _ = codec::decode_aba_u256(message);
+    let _ = codec::decode_tagged(message);
+    let _ = codec::split_tag(message);
+}
+
+/// Run the options checks over `options`, alone and merged with itself.
+fn check_options(options: &[u8]) {
+    let _ = validate_type3(options, MAX_RETURN_OPTIONS_LEN);
+    let _ = worker_options(options);
+    let _ = merge_type3(options, options, false);
+    let _ = merge_type3(options, options, true);
+    let _ = merge_type3(&[], options, false);
+}
+
+fn aba_type() -> impl Strategy<Value = u16> {
+    prop_oneof![Just(ABA_TYPE), Just(PONG_TYPE), Just(GAME_OVER_TYPE)]
+}
+
+fn option_item() -> impl Strategy<Value = OptionItem> {
+    prop_oneof![
+        (any::<u128>(), any::<u128>())
+            .prop_map(|(gas, value)| OptionItem::LzReceive { gas, value }),
+        (any::<u128>(), any::<[u8; 32]>())
+            .prop_map(|(amount, receiver)| OptionItem::NativeDrop { amount, receiver }),
+        (any::<u16>(), any::<u128>(), any::<u128>())
+            .prop_map(|(index, gas, value)| OptionItem::Compose { index, gas, value }),
+        Just(OptionItem::OrderedExecution),
+    ]
+}
+
+/// A 32-byte word whose last two bytes hold a small value, so offset and length words
+/// land near real fields more often than uniformly random words would.
+fn small_word() -> impl Strategy<Value = [u8; 32]> {
+    (0u16..1024).prop_map(|value| {
+        let mut word = [0u8; 32];
+        word[30..].copy_from_slice(&value.to_be_bytes());
+        word
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn encode_aba_round_trips(
+        ball in any::<[u8; 32]>(),
+        msg_type in aba_type(),
+        return_options in prop::collection::vec(any::<u8>(), 0..=MAX_RETURN_OPTIONS_LEN),
+    ) {
+        let encoded = codec::encode_aba(&ball, msg_type, &return_options).unwrap();
+        prop_assert_eq!(encoded.len() % 32, 0);
+        let decoded = codec::decode_aba(&encoded).unwrap();
+        prop_assert_eq!(decoded.ball, ball);
+        prop_assert_eq!(decoded.msg_type, msg_type);
+        prop_assert_eq!(decoded.return_options, return_options);
+        prop_assert_eq!(codec::peek_msg_type(&encoded), Ok(msg_type));
+    }
+
+    #[test]
+    fn aba_message_round_trips(
+        ball in any::<[u8; 32]>(),
+        msg_type in aba_type(),
+        return_options in prop::collection::vec(any::<u8>(), 0..=MAX_RETURN_OPTIONS_LEN),
+        hop_count in any::<u16>(),
+        origin_guid in any::<[u8; 32]>(),
+        compose_msg in prop::collection::vec(any::<u8>(), 0..=MAX_COMPOSE_MSG_LEN),
+        memo in prop::collection::vec(any::<u8>(), 0..=MAX_MEMO_LEN),
+        origin_caller in any::<[u8; 32]>(),
+        seq in any::<u64>(),
+    ) {
+        let message = AbaMessage {
+            ball,
+            msg_type,
+            return_options,
+            hop_count,
+            origin_guid,
+            compose_msg,
+            memo,
+            origin_caller,
+            seq,
+            ..Default::default()
+        };
+        let encoded = message.encode().unwrap();
+        let decoded = codec::decode_aba(&encoded).unwrap();
+        prop_assert_eq!(decoded.ball, message.ball);
+        prop_assert_eq!(decoded.msg_type, message.msg_type);
+        prop_assert_eq!(&decoded.return_options, &message.return_options);
+        prop_assert_eq!(decoded.hop_count, message.hop_count);
+        prop_assert_eq!(decoded.origin_guid, message.origin_guid);
+        prop_assert_eq!(&decoded.compose_msg, &message.compose_msg);
+        prop_assert_eq!(&decoded.memo, &message.memo);
+        prop_assert_eq!(decoded.origin_caller, message.origin_caller);
+        prop_assert_eq!(decoded.seq, message.seq);
+        // Re-encoding what was decoded gives the same bytes
+        prop_assert_eq!(decoded.encode().unwrap(), encoded);
+    }
+
+    #[test]
+    fn decoders_do_not_panic_on_arbitrary_bytes(
+        message in prop::collection::vec(any::<u8>(), 0..2200),
+    ) {
+        decode_everything(&message);
+    }
+
+    #[test]
+    fn decoders_do_not_panic_on_arbitrary_words(
+        words in prop::collection::vec(small_word(), 0..24),
+    ) {
+        decode_everything(&words.concat());
+    }
+
+    #[test]
+    fn decoders_do_not_panic_on_mutated_messages(
+        return_options in prop::collection::vec(any::<u8>(), 0..64),
+        compose_msg in prop::collection::vec(any::<u8>(), 0..64),
+        index in any::<u16>(),
+        byte in any::<u8>(),
+        truncate in any::<bool>(),
+    ) {
+        let mut message = AbaMessage {
+            msg_type: ABA_TYPE,
+            return_options,
+            compose_msg,
+            memo: b"memo".to_vec(),
+            seq: 1,
+            ..Default::default()
+        }
+        .encode()
+        .unwrap();
+        let index = index as usize % message.len();
+        if truncate {
+            message.truncate(index);
+        } else {
+            message[index] = byte;
+        }
+        decode_everything(&message);
+    }
+
+    #[test]
+    fn options_checks_do_not_panic_on_arbitrary_bytes(
+        options in prop::collection::vec(any::<u8>(), 0..600),
+    ) {
+        check_options(&options);
+    }
+
+    #[test]
+    fn options_checks_do_not_panic_on_type3_bytes(
+        body in prop::collection::vec(any::<u8>(), 0..128),
+    ) {
+        // A valid header makes the checks walk the worker options
+        let mut options = vec![0, 3];
+        options.extend_from_slice(&body);
+        check_options(&options);
+    }
+
+    #[test]
+    fn built_options_are_valid(items in prop::collection::vec(option_item(), 0..8)) {
+        let options = combine(&items);
+        prop_assert_eq!(validate_type3(&options, usize::MAX), Ok(()));
+        prop_assert_eq!(worker_options(&options).unwrap().len(), items.len());
+        // Merging duplicates away leaves valid options, unless an amount overflows
+        if let Ok(merged) = merge_type3(&[], &options, false) {
+            prop_assert_eq!(validate_type3(&merged, usize::MAX), Ok(()));
+        }
+    }
+}